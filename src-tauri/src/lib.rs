@@ -1,13 +1,14 @@
 use serde::{Deserialize, Serialize};
 use tauri_plugin_shell::ShellExt;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{LazyLock, Mutex};
 use base64::{Engine as _, engine::general_purpose};
 use std::fs;
 use std::time::{UNIX_EPOCH, Duration};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tauri::Emitter;
 use tauri::Manager;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 
 /// Sets the creation/birth time of a file on macOS using `setattrlist`.
 #[cfg(target_os = "macos")]
@@ -69,17 +70,283 @@ fn set_creation_time(_path: &std::path::Path, _timestamp_secs: u64) -> std::io::
     Ok(())
 }
 
+/// Returns the bytes available to unprivileged users on the volume containing `path`.
+/// Walks up to the nearest existing ancestor so it works for folders that don't exist yet.
+fn local_available_space(path: &std::path::Path) -> std::io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No existing parent folder"))?;
+
+    let c_path = CString::new(existing.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    #[allow(clippy::useless_conversion)]
+    let available = u64::from(stat.f_bavail) * u64::from(stat.f_frsize);
+    Ok(available)
+}
+
 // Global state to store custom ADB path
 static ADB_PATH: Mutex<Option<String>> = Mutex::new(None);
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveJob {
+    pub job_id: String,
+    pub kind: String,
+    pub device_id: Option<String>,
+    pub started_at: u64,
+}
+
+struct RegisteredJob {
+    info: ActiveJob,
+    child: CommandChild,
+}
+
+// Every adb child process DroidDock has spawned and not yet reaped, keyed by job id
+// Cancellable operations use the frontend's operation id as their job id.
+static ACTIVE_JOBS: LazyLock<Mutex<HashMap<String, RegisteredJob>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Track a spawned child; pass job_id to choose the id, otherwise one is generated
+fn register_job(job_id: Option<&str>, kind: &str, device_id: Option<&str>, child: CommandChild) -> String {
+    static JOB_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let job_id = job_id.map(str::to_string).unwrap_or_else(|| {
+        format!("job-{}", JOB_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    });
+
+    let info = ActiveJob {
+        job_id: job_id.clone(),
+        kind: kind.to_string(),
+        device_id: device_id.map(str::to_string),
+        started_at: now_millis(),
+    };
+    if let Ok(mut jobs) = ACTIVE_JOBS.lock() {
+        jobs.insert(job_id.clone(), RegisteredJob { info, child });
+    }
+    job_id
+}
+
+// Stop tracking a job, handing back its child if it was still registered
+fn unregister_job(job_id: &str) -> Option<CommandChild> {
+    ACTIVE_JOBS
+        .lock()
+        .ok()
+        .and_then(|mut jobs| jobs.remove(job_id))
+        .map(|job| job.child)
+}
+
+// Kill every tracked child; used on app exit so no adb subprocess outlives DroidDock
+fn kill_all_jobs() {
+    let jobs: Vec<RegisteredJob> = ACTIVE_JOBS
+        .lock()
+        .map(|mut jobs| jobs.drain().map(|(_, job)| job).collect())
+        .unwrap_or_default();
+    for job in jobs {
+        let _ = job.child.kill();
+    }
+}
+
+// List adb child processes currently running on DroidDock's behalf, oldest first
+#[tauri::command]
+fn list_active_jobs() -> Vec<ActiveJob> {
+    let mut jobs: Vec<ActiveJob> = ACTIVE_JOBS
+        .lock()
+        .map(|jobs| jobs.values().map(|job| job.info.clone()).collect())
+        .unwrap_or_default();
+    jobs.sort_by_key(|job| job.started_at);
+    jobs
+}
+
+// Kill a tracked job; returns false if it had already finished
+#[tauri::command]
+fn kill_job(job_id: String) -> Result<bool, String> {
+    match unregister_job(&job_id) {
+        Some(child) => {
+            child.kill().map_err(|e| format!("Failed to kill job {}: {}", job_id, e))?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+const OPERATION_CANCELLED: &str = "Operation cancelled";
+
+// Structured command error; serialized as { kind, message } so the frontend can branch on `kind`
+// and still show `message` as before
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdbError {
+    AdbNotFound,
+    DeviceUnauthorized,
+    PermissionDenied(String),
+    NotFound(String),
+    ReadOnlyFileSystem(String),
+    AlreadyExists(String),
+    DirectoryNotEmpty(String),
+    OutOfSpace(String),
+    InsufficientSpace { needed: u64, available: u64 },
+    InvalidArgument(String),
+    Cancelled,
+    CommandFailed { stderr: String },
+}
+
+impl AdbError {
+    fn kind(&self) -> &'static str {
+        match self {
+            AdbError::AdbNotFound => "adb_not_found",
+            AdbError::DeviceUnauthorized => "device_unauthorized",
+            AdbError::PermissionDenied(_) => "permission_denied",
+            AdbError::NotFound(_) => "not_found",
+            AdbError::ReadOnlyFileSystem(_) => "read_only_file_system",
+            AdbError::AlreadyExists(_) => "already_exists",
+            AdbError::DirectoryNotEmpty(_) => "directory_not_empty",
+            AdbError::OutOfSpace(_) => "out_of_space",
+            AdbError::InsufficientSpace { .. } => "insufficient_space",
+            AdbError::InvalidArgument(_) => "invalid_argument",
+            AdbError::Cancelled => "cancelled",
+            AdbError::CommandFailed { .. } => "command_failed",
+        }
+    }
+
+    // Map the adb/shell stderr patterns for `path` onto a variant
+    fn from_stderr(stderr: &str, path: &str) -> AdbError {
+        if stderr.contains("unauthorized") {
+            AdbError::DeviceUnauthorized
+        } else if stderr.contains("Permission denied") {
+            AdbError::PermissionDenied(path.to_string())
+        } else if stderr.contains("Read-only file system") {
+            AdbError::ReadOnlyFileSystem(path.to_string())
+        } else if stderr.contains("No such file") || stderr.contains("does not exist") {
+            AdbError::NotFound(path.to_string())
+        } else if stderr.contains("File exists") || stderr.contains("already exists") {
+            AdbError::AlreadyExists(path.to_string())
+        } else if stderr.contains("Directory not empty") {
+            AdbError::DirectoryNotEmpty(path.to_string())
+        } else if stderr.contains("No space left on device") {
+            AdbError::OutOfSpace(path.to_string())
+        } else {
+            AdbError::CommandFailed { stderr: stderr.trim().to_string() }
+        }
+    }
+
+    // The adb process itself couldn't be started; only a missing binary means adb isn't installed
+    fn from_spawn(error: tauri_plugin_shell::Error) -> AdbError {
+        match &error {
+            tauri_plugin_shell::Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound => AdbError::AdbNotFound,
+            _ => AdbError::CommandFailed { stderr: format!("Failed to execute adb command: {}", error) },
+        }
+    }
+}
+
+impl std::fmt::Display for AdbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdbError::AdbNotFound => write!(f, "ADB is not installed or not in PATH"),
+            AdbError::DeviceUnauthorized => write!(f, "Device is unauthorized: accept the USB debugging prompt on the device"),
+            AdbError::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
+            AdbError::NotFound(path) => write!(f, "File not found: {}", path),
+            AdbError::ReadOnlyFileSystem(path) => write!(f, "Read-only file system: {}", path),
+            AdbError::AlreadyExists(path) => write!(f, "Already exists: {}", path),
+            AdbError::DirectoryNotEmpty(path) => write!(f, "Directory not empty: {}", path),
+            AdbError::OutOfSpace(path) => write!(f, "Out of space: not enough room on the device for {}", path),
+            AdbError::InsufficientSpace { needed, available } => {
+                write!(f, "Insufficient device space: need {} bytes, {} available", needed, available)
+            }
+            AdbError::InvalidArgument(reason) => write!(f, "{}", reason),
+            AdbError::Cancelled => write!(f, "{}", OPERATION_CANCELLED),
+            AdbError::CommandFailed { stderr } => write!(f, "{}", stderr),
+        }
+    }
+}
+
+impl Serialize for AdbError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("AdbError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+// Messages from String-returning helpers become CommandFailed (or Cancelled for OPERATION_CANCELLED)
+impl From<String> for AdbError {
+    fn from(message: String) -> Self {
+        if message == OPERATION_CANCELLED {
+            return AdbError::Cancelled;
+        }
+        AdbError::CommandFailed { stderr: message }
+    }
+}
+
+// Lets String-returning callers keep using `?` on commands that return AdbError
+impl From<AdbError> for String {
+    fn from(error: AdbError) -> Self {
+        error.to_string()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AdbDevice {
     pub id: String,
     pub status: String,
     pub model: String,
+    pub alias: String,
+    pub is_emulator: bool,
+    pub transport_type: String, // "usb", "tcp", or "emulator"
+    pub tcp_host: Option<String>,
+    pub tcp_port: Option<u16>,
+    // From the `product:` and `transport_id:` tokens of `adb devices -l`, when present
+    pub product: Option<String>,
+    pub transport_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Parse `adb devices -l` output
+// Example line: 1A2B3C4D  device usb:1-1 product:panther model:Pixel_7 device:panther transport_id:2
+// The model token seeds `model` (underscores become spaces) until getprop provides a friendlier name.
+fn parse_adb_devices_output(output: &str) -> Vec<AdbDevice> {
+    output
+        .lines()
+        .skip(1) // Skip "List of devices attached" header
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 {
+                return None;
+            }
+            let token = |key: &str| {
+                parts[2..]
+                    .iter()
+                    .find_map(|part| part.strip_prefix(key))
+                    .map(str::to_string)
+            };
+
+            let (transport_type, tcp_host, tcp_port) = parse_transport(parts[0]);
+            Some(AdbDevice {
+                id: parts[0].to_string(),
+                status: parts[1].to_string(),
+                model: token("model:").map(|m| m.replace('_', " ")).unwrap_or_default(),
+                alias: String::new(),
+                is_emulator: transport_type == "emulator",
+                transport_type,
+                tcp_host,
+                tcp_port,
+                product: token("product:"),
+                transport_id: token("transport_id:"),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileEntry {
     pub name: String,
     pub permissions: String,
@@ -87,6 +354,11 @@ pub struct FileEntry {
     pub date: String,
     pub is_directory: bool,
     pub extension: Option<String>,
+    pub modified_epoch: Option<u64>,
+    pub mime_type: Option<String>,
+    pub permissions_octal: Option<String>,
+    pub is_symlink: bool,
+    pub symlink_target: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -149,6 +421,63 @@ fn get_adb_command() -> String {
     "adb".to_string()
 }
 
+// Run an adb shell command that can be stopped early with `cancel_operation`
+// Collects stdout; returns Err(OPERATION_CANCELLED) if the operation was cancelled
+async fn run_cancellable_shell(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    operation_id: &str,
+    shell_command: &str,
+) -> Result<String, String> {
+    let mut stdout = String::new();
+    run_cancellable_shell_lines(app, device_id, operation_id, shell_command, |line| {
+        stdout.push_str(line);
+        stdout.push('\n');
+    })
+    .await?;
+    Ok(stdout)
+}
+
+// Like run_cancellable_shell, but hands each stdout line to `on_line` as it arrives
+// Lets long-running commands report progress before they finish
+async fn run_cancellable_shell_lines(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    operation_id: &str,
+    shell_command: &str,
+    mut on_line: impl FnMut(&str),
+) -> Result<(), String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let (mut rx, child) = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, "shell", shell_command])
+        .spawn()
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+
+    register_job(Some(operation_id), "shell", Some(device_id), child);
+
+    while let Some(event) = rx.recv().await {
+        if let CommandEvent::Stdout(line) = event {
+            on_line(&String::from_utf8_lossy(&line));
+        }
+    }
+
+    // cancel_operation removes the entry before killing, so a missing entry means we were cancelled
+    if unregister_job(operation_id).is_some() {
+        Ok(())
+    } else {
+        Err(OPERATION_CANCELLED.to_string())
+    }
+}
+
+// Cancel a running cancellable operation by its id
+#[tauri::command]
+fn cancel_operation(operation_id: String) -> Result<bool, String> {
+    kill_job(operation_id)
+}
+
 fn is_critical_system_path(path: &str) -> bool {
     const CRITICAL_PATHS: [&str; 8] = [
         "/",
@@ -201,6 +530,139 @@ fn is_text_extension(ext: &str) -> bool {
     )
 }
 
+// Placeholder icon identifier for files that have no generated thumbnail
+fn placeholder_for_extension(ext: &str) -> &'static str {
+    match ext {
+        "zip" | "rar" | "7z" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "apk" | "aab" | "obb" => "placeholder:archive",
+        "pdf" | "doc" | "docx" | "odt" | "rtf" | "xls" | "xlsx" | "ods" | "ppt" | "pptx" | "odp" | "epub" => {
+            "placeholder:document"
+        }
+        "mp3" | "m4a" | "aac" | "flac" | "wav" | "ogg" | "opus" | "amr" => "placeholder:audio",
+        "json" | "xml" | "html" | "htm" | "css" | "js" | "jsx" | "ts" | "tsx" | "yaml" | "yml" | "toml" | "sh"
+        | "bash" | "py" | "rb" | "java" | "c" | "cpp" | "h" | "hpp" | "rs" | "go" | "swift" | "kt" | "gradle"
+        | "sql" => "placeholder:code",
+        _ if is_text_extension(ext) => "placeholder:text",
+        _ => "placeholder:file",
+    }
+}
+
+// User-chosen thumbnail cache location, loaded from app_settings.json at startup
+static THUMBNAIL_CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Directory holding generated thumbnails: the configured folder, or one in the system temp dir
+fn thumbnail_cache_dir() -> PathBuf {
+    THUMBNAIL_CACHE_DIR
+        .lock()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_else(|| std::env::temp_dir().join("droiddock_thumbnails"))
+}
+
+// App-wide preferences that aren't tied to a device
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    pub thumbnail_cache_dir: Option<String>,
+}
+
+fn app_settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(data_dir.join("app_settings.json"))
+}
+
+fn read_app_settings(app: &tauri::AppHandle) -> Result<AppSettings, String> {
+    let path = app_settings_path(app)?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read app settings: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse app settings: {}", e))
+}
+
+fn write_app_settings(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = app_settings_path(app)?;
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize app settings: {}", e))?;
+    fs::write(&path, content)
+        .map_err(|e| format!("Failed to write app settings: {}", e))
+}
+
+// Use a different folder for the thumbnail cache, or pass None to go back to the system temp dir
+// The folder must be writable; the choice is saved and restored on the next launch.
+#[tauri::command]
+fn set_thumbnail_cache_dir(app: tauri::AppHandle, path: Option<String>) -> Result<(), String> {
+    if let Some(path) = &path {
+        let dir = PathBuf::from(path);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        let probe_file = dir.join(format!(".write_test_{}", std::process::id()));
+        fs::write(&probe_file, b"droiddock").map_err(|e| format!("Folder is not writable: {}: {}", path, e))?;
+        let _ = fs::remove_file(&probe_file);
+    }
+
+    let mut settings = read_app_settings(&app)?;
+    settings.thumbnail_cache_dir = path.clone();
+    write_app_settings(&app, &settings)?;
+
+    if let Ok(mut guard) = THUMBNAIL_CACHE_DIR.lock() {
+        *guard = path.map(PathBuf::from);
+    }
+    Ok(())
+}
+
+// Hardware serial (ro.serialno) per adb device id, filled in by get_devices
+// The adb id changes from serial to ip:port when a device switches to WiFi; the hardware serial doesn't.
+static DEVICE_SERIALS: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Stable cache namespace for a device: its hardware serial when known, otherwise the adb id
+fn device_cache_namespace(device_id: &str) -> String {
+    DEVICE_SERIALS
+        .lock()
+        .ok()
+        .and_then(|serials| serials.get(device_id).cloned())
+        .unwrap_or_else(|| device_id.to_string())
+}
+
+// Cache key for a device file's thumbnail, shared across USB and WiFi connections of the same device
+fn thumbnail_cache_key(device_id: &str, file_path: &str) -> String {
+    format!("{:x}", md5::compute(format!("{}:{}", device_cache_namespace(device_id), file_path)))
+}
+
+// Move a thumbnail cached under the adb id (before stable namespaces) to its stable key
+// Returns true if an entry was re-keyed
+fn migrate_cached_thumbnail(device_id: &str, file_path: &str) -> bool {
+    let legacy_key = format!("{:x}", md5::compute(format!("{}:{}", device_id, file_path)));
+    let stable_key = thumbnail_cache_key(device_id, file_path);
+    if legacy_key == stable_key {
+        return false;
+    }
+
+    let cache_dir = thumbnail_cache_dir();
+    let legacy_path = cache_dir.join(format!("thumb_{}.png", legacy_key));
+    let stable_path = cache_dir.join(format!("thumb_{}.png", stable_key));
+    if !legacy_path.exists() {
+        return false;
+    }
+    if stable_path.exists() {
+        let _ = std::fs::remove_file(&legacy_path);
+        return false;
+    }
+    std::fs::rename(&legacy_path, &stable_path).is_ok()
+}
+
+// Re-key cached thumbnails for the given paths from the device's adb id to its stable namespace
+// Cache file names are hashes, so only paths the caller knows about (e.g. the open folder) can be migrated
+#[tauri::command]
+fn migrate_thumbnail_cache(device_id: String, file_paths: Vec<String>) -> usize {
+    file_paths
+        .iter()
+        .filter(|path| migrate_cached_thumbnail(&device_id, path))
+        .count()
+}
+
 // Get thumbnail for an image or video file
 #[tauri::command]
 async fn get_thumbnail(
@@ -221,15 +683,16 @@ async fn get_thumbnail(
     }
 
     // Create temp directory for thumbnails
-    let temp_dir = std::env::temp_dir().join("droiddock_thumbnails");
+    let temp_dir = thumbnail_cache_dir();
     std::fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp directory: {}", e))?;
 
     // Generate cache key from file path and device ID
-    let cache_key = format!("{:x}", md5::compute(format!("{}:{}", device_id, file_path)));
+    let cache_key = thumbnail_cache_key(&device_id, &file_path);
     let cached_thumb_path = temp_dir.join(format!("thumb_{}.png", cache_key));
 
-    // Check if thumbnail already exists in cache
+    // Check if thumbnail already exists in cache (picking up entries cached under an older adb id)
+    migrate_cached_thumbnail(&device_id, &file_path);
     if cached_thumb_path.exists() {
         let thumb_bytes = std::fs::read(&cached_thumb_path)
             .map_err(|e| format!("Failed to read cached thumbnail: {}", e))?;
@@ -365,2365 +828,9777 @@ async fn get_thumbnail(
     }
 }
 
-// Get list of connected ADB devices
+// Drop any cached thumbnail for a file and generate it again
+// Lets the UI retry files that previously fell back to a placeholder (e.g. before ffmpeg was installed)
 #[tauri::command]
-async fn get_devices(app: tauri::AppHandle) -> Result<Vec<AdbDevice>, String> {
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
-
-    let output = shell
-        .command(&adb_cmd)
-        .args(["devices"])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+async fn regenerate_thumbnail(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    extension: String,
+    file_size: String,
+) -> Result<String, String> {
+    let cached_thumb_path = thumbnail_cache_dir()
+        .join(format!("thumb_{}.png", thumbnail_cache_key(&device_id, &file_path)));
 
-    if !output.status.success() {
-        return Err(format!("ADB command failed: {}", String::from_utf8_lossy(&output.stderr)));
+    if cached_thumb_path.exists() {
+        std::fs::remove_file(&cached_thumb_path)
+            .map_err(|e| format!("Failed to remove cached thumbnail: {}", e))?;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut devices: Vec<AdbDevice> = stdout
-        .lines()
-        .skip(1) // Skip "List of devices attached" header
-        .filter(|line| !line.trim().is_empty())
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                Some(AdbDevice {
-                    id: parts[0].to_string(),
-                    status: parts[1].to_string(),
-                    model: String::new(),
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // Query each device for its friendly model name
-    for device in &mut devices {
-        if device.status != "device" {
-            continue;
-        }
-
-        let brand = shell
-            .command(&adb_cmd)
-            .args(["-s", &device.id, "shell", "getprop ro.product.brand"])
-            .output()
-            .await
-            .ok()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_default();
-
-        let model = shell
-            .command(&adb_cmd)
-            .args(["-s", &device.id, "shell", "getprop ro.product.model"])
-            .output()
-            .await
-            .ok()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_default();
-
-        device.model = match (brand.is_empty(), model.is_empty()) {
-            (false, false) => format!("{} {}", brand, model),
-            (false, true) => brand,
-            (true, false) => model,
-            (true, true) => String::new(),
-        };
-    }
+    get_thumbnail(app, device_id, file_path, extension, file_size).await
+}
 
-    Ok(devices)
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ThumbnailDebug {
+    pub file_kind: String,
+    pub pull_ok: bool,
+    pub pulled_bytes: u64,
+    pub pull_error: Option<String>,
+    pub detected_format: Option<String>,
+    pub ffmpeg_available: Option<bool>,
+    pub decode_ok: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub decode_error: Option<String>,
 }
 
-// List files in a directory on the Android device
+// Run the thumbnail pipeline step by step and report each outcome instead of a placeholder
+// Nothing is cached; intended for troubleshooting blank thumbnails
 #[tauri::command]
-async fn list_files(app: tauri::AppHandle, device_id: String, path: String) -> Result<Vec<FileEntry>, String> {
+async fn get_thumbnail_debug(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    extension: String,
+    file_size: String,
+) -> Result<ThumbnailDebug, String> {
     let shell = app.shell();
-    let adb_cmd = get_adb_command();
+    let ext_lower = extension.to_lowercase();
+    let mut report = ThumbnailDebug {
+        file_kind: if is_image_extension(&ext_lower) {
+            "image"
+        } else if is_video_extension(&ext_lower) {
+            "video"
+        } else {
+            "unsupported"
+        }
+        .to_string(),
+        ..Default::default()
+    };
 
-    // Escape single quotes in path and wrap in quotes to handle spaces
-    let escaped_path = path.replace("'", "'\\''");
-    let shell_command = format!("ls -la '{}'", escaped_path);
+    let temp_dir = thumbnail_cache_dir();
+    std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let cache_key = thumbnail_cache_key(&device_id, &file_path);
+    let temp_file = temp_dir.join(format!("debug_{}_{}", cache_key, file_path.rsplit('/').next().unwrap_or("file")));
 
+    // Step 1: pull
     let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &shell_command])
+        .command(get_adb_command())
+        .args(["-s", &device_id, "pull", &file_path, temp_file.to_str().unwrap()])
         .output()
         .await
-        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+        .map_err(|e| format!("Failed to pull file from device: {}", e))?;
 
+    report.pulled_bytes = std::fs::metadata(&temp_file).map(|m| m.len()).unwrap_or(0);
+    report.pull_ok = output.status.success() && report.pulled_bytes > 0;
     if !output.status.success() {
-        return Err(format!("ADB ls command failed: {}", String::from_utf8_lossy(&output.stderr)));
+        report.pull_error = Some(format!("ADB pull failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    } else if report.pulled_bytes == 0 {
+        report.pull_error = Some("Pulled file is empty (0 bytes)".to_string());
+    } else if report.pulled_bytes < 100 {
+        report.pull_error = Some(format!("Pulled file too small ({} bytes), possibly corrupted", report.pulled_bytes));
+    } else if file_size.parse::<u64>().is_ok_and(|expected| expected != report.pulled_bytes) {
+        report.pull_error = Some(format!("Pulled {} bytes, expected {}", report.pulled_bytes, file_size));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<FileEntry> = stdout
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .filter(|line| !line.starts_with("total"))
-        .filter_map(|line| parse_ls_line(line))
-        .collect();
-
-    Ok(files)
-}
-
-// Parse a single line of ls -la output
-// Android's ls -la format: permissions owner group size date time name
-// Example: drwxrwx--- root sdcard_rw 2025-02-01 06:31 .NightPearl
-fn parse_ls_line(line: &str) -> Option<FileEntry> {
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    if parts.len() < 7 {
-        return None;
+    if !report.pull_ok {
+        let _ = std::fs::remove_file(&temp_file);
+        return Ok(report);
     }
 
-    let permissions = parts[0].to_string();
-    let is_directory = permissions.starts_with('d');
-
-    // Find the time field (contains ':')
-    let time_idx = parts.iter().position(|p| p.contains(':'))?;
+    // Steps 2 and 3: format detection and decode
+    if report.file_kind == "image" {
+        match image::ImageReader::open(&temp_file).and_then(|reader| reader.with_guessed_format()) {
+            Ok(reader) => {
+                report.detected_format = reader.format().map(|f| format!("{:?}", f));
+                match reader.decode() {
+                    Ok(img) => {
+                        report.decode_ok = true;
+                        report.width = Some(img.width());
+                        report.height = Some(img.height());
+                    }
+                    Err(e) => report.decode_error = Some(format!("Failed to decode image: {}", e)),
+                }
+            }
+            Err(e) => report.decode_error = Some(format!("Failed to open image: {}", e)),
+        }
+    } else if report.file_kind == "video" {
+        let ffmpeg_available = shell.command("ffmpeg").args(["-version"]).output().await.is_ok();
+        report.ffmpeg_available = Some(ffmpeg_available);
+
+        if ffmpeg_available {
+            let frame_file = temp_dir.join(format!("debug_{}.png", cache_key));
+            let ffmpeg_output = shell
+                .command("ffmpeg")
+                .args(["-i", temp_file.to_str().unwrap(), "-ss", "00:00:01", "-vframes", "1", "-y", frame_file.to_str().unwrap()])
+                .output()
+                .await
+                .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
 
-    // Name is everything after the time
-    if time_idx + 1 >= parts.len() {
-        return None;
+            match image::open(&frame_file) {
+                Ok(frame) if ffmpeg_output.status.success() => {
+                    report.decode_ok = true;
+                    report.width = Some(frame.width());
+                    report.height = Some(frame.height());
+                }
+                _ => {
+                    let stderr = String::from_utf8_lossy(&ffmpeg_output.stderr);
+                    report.decode_error = Some(format!(
+                        "ffmpeg could not extract a frame: {}",
+                        stderr.lines().last().unwrap_or("no output").trim()
+                    ));
+                }
+            }
+            let _ = std::fs::remove_file(&frame_file);
+        } else {
+            report.decode_error = Some("ffmpeg is not installed".to_string());
+        }
+    } else {
+        report.decode_error = Some("Unsupported file type".to_string());
     }
 
-    let name = parts[time_idx + 1..].join(" ");
+    let _ = std::fs::remove_file(&temp_file);
+    Ok(report)
+}
+
+// Chunk types and attribute ids from Android's binary resource formats (ResourceTypes.h)
+const RES_STRING_POOL_TYPE: u16 = 0x0001;
+const RES_XML_START_ELEMENT_TYPE: u16 = 0x0102;
+const RES_XML_RESOURCE_MAP_TYPE: u16 = 0x0180;
+const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+const RES_TABLE_TYPE_TYPE: u16 = 0x0201;
+const ANDROID_ATTR_ICON: u32 = 0x0101_0002;
+const ANDROID_ATTR_DRAWABLE: u32 = 0x0101_0199;
+const RES_VALUE_REFERENCE: u8 = 0x01;
+const RES_VALUE_STRING: u8 = 0x03;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
 
-    // Skip . and .. entries
-    if name == "." || name == ".." || name.is_empty() {
-        return None;
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// Strings of the ResStringPool chunk at `offset` (UTF-8 or UTF-16)
+fn parse_string_pool(data: &[u8], offset: usize) -> Option<Vec<String>> {
+    let header_size = read_u16(data, offset + 2)? as usize;
+    let count = read_u32(data, offset + 8)? as usize;
+    let utf8 = read_u32(data, offset + 16)? & 0x100 != 0;
+    let strings_start = offset + read_u32(data, offset + 20)? as usize;
+
+    (0..count)
+        .map(|i| {
+            let mut pos = strings_start + read_u32(data, offset + header_size + i * 4)? as usize;
+            if utf8 {
+                // Character count, then byte count; each is one byte, or two with the high bit set
+                pos += if *data.get(pos)? & 0x80 != 0 { 2 } else { 1 };
+                let first = *data.get(pos)? as usize;
+                let (len, skip) = if first & 0x80 != 0 {
+                    (((first & 0x7f) << 8) | *data.get(pos + 1)? as usize, 2)
+                } else {
+                    (first, 1)
+                };
+                Some(String::from_utf8_lossy(data.get(pos + skip..pos + skip + len)?).to_string())
+            } else {
+                let first = read_u16(data, pos)? as usize;
+                let (len, skip) = if first & 0x8000 != 0 {
+                    (((first & 0x7fff) << 16) | read_u16(data, pos + 2)? as usize, 4)
+                } else {
+                    (first, 2)
+                };
+                let units: Vec<u16> = (0..len).map(|k| read_u16(data, pos + skip + k * 2)).collect::<Option<_>>()?;
+                Some(String::from_utf16_lossy(&units))
+            }
+        })
+        .collect()
+}
+
+// Resource id that attribute `attr_id` references on the first `element` tag of a binary XML file
+// e.g. android:icon on <application> in AndroidManifest.xml
+fn axml_reference_attr(data: &[u8], element: &str, attr_id: u32) -> Option<u32> {
+    let mut strings = Vec::new();
+    let mut resource_map = Vec::new();
+    let mut pos = read_u16(data, 2)? as usize;
+    while pos + 8 <= data.len() {
+        let chunk_type = read_u16(data, pos)?;
+        let chunk_size = read_u32(data, pos + 4)? as usize;
+        if chunk_size < 8 {
+            return None;
+        }
+        match chunk_type {
+            RES_STRING_POOL_TYPE => strings = parse_string_pool(data, pos)?,
+            // Attribute ids, indexed like the string pool
+            RES_XML_RESOURCE_MAP_TYPE => {
+                let header_size = read_u16(data, pos + 2)? as usize;
+                resource_map = (pos + header_size..pos + chunk_size)
+                    .step_by(4)
+                    .filter_map(|p| read_u32(data, p))
+                    .collect();
+            }
+            RES_XML_START_ELEMENT_TYPE => {
+                let ext = pos + read_u16(data, pos + 2)? as usize;
+                let name = read_u32(data, ext + 4)? as usize;
+                if strings.get(name).map(String::as_str) == Some(element) {
+                    let attr_start = read_u16(data, ext + 8)? as usize;
+                    let attr_size = read_u16(data, ext + 10)? as usize;
+                    let attr_count = read_u16(data, ext + 12)? as usize;
+                    return (0..attr_count).find_map(|i| {
+                        let attr = ext + attr_start + i * attr_size;
+                        let attr_name = read_u32(data, attr + 4)? as usize;
+                        let is_reference = *data.get(attr + 15)? == RES_VALUE_REFERENCE;
+                        (resource_map.get(attr_name) == Some(&attr_id) && is_reference)
+                            .then(|| read_u32(data, attr + 16))
+                            .flatten()
+                    });
+                }
+            }
+            _ => {}
+        }
+        pos += chunk_size;
     }
+    None
+}
 
-    // Date is one position before time
-    let date_part = if time_idx > 0 {
-        parts[time_idx - 1]
-    } else {
-        ""
-    };
+// Values resources.arsc holds for resource `id`, one per configuration, as (density, value type, data)
+fn arsc_values(arsc: &[u8], id: u32) -> Vec<(u16, u8, u32)> {
+    let (package_id, type_id, entry_index) = ((id >> 24) as u8, (id >> 16) as u8, (id & 0xffff) as usize);
+    let mut values = Vec::new();
 
-    let time_part = parts[time_idx];
-    let date = format!("{} {}", date_part, time_part);
+    let mut pos = read_u16(arsc, 2).unwrap_or(0) as usize;
+    while let (Some(chunk_type), Some(chunk_size)) = (read_u16(arsc, pos), read_u32(arsc, pos + 4)) {
+        let chunk_size = chunk_size as usize;
+        if chunk_size < 8 {
+            break;
+        }
+        if chunk_type == RES_TABLE_PACKAGE_TYPE && read_u32(arsc, pos + 8) == Some(package_id as u32) {
+            let mut sub = pos + read_u16(arsc, pos + 2).unwrap_or(0) as usize;
+            while sub + 8 <= pos + chunk_size {
+                let sub_size = read_u32(arsc, sub + 4).unwrap_or(0) as usize;
+                if sub_size < 8 {
+                    break;
+                }
+                if read_u16(arsc, sub) == Some(RES_TABLE_TYPE_TYPE) && arsc.get(sub + 8) == Some(&type_id) {
+                    if let Some(value) = arsc_type_entry(arsc, sub, entry_index) {
+                        values.push(value);
+                    }
+                }
+                sub += sub_size;
+            }
+        }
+        pos += chunk_size;
+    }
+    values
+}
 
-    // Size is two positions before time (at time_idx - 2)
-    let size = if time_idx >= 2 {
-        parts[time_idx - 2].to_string()
+// Entry `index` of the ResTable_type chunk at `chunk`, handling sparse, 16-bit offset and compact entries
+fn arsc_type_entry(arsc: &[u8], chunk: usize, index: usize) -> Option<(u16, u8, u32)> {
+    const FLAG_SPARSE: u8 = 0x01;
+    const FLAG_OFFSET16: u8 = 0x02;
+    const ENTRY_FLAG_COMPLEX: u16 = 0x01;
+    const ENTRY_FLAG_COMPACT: u16 = 0x08;
+
+    let header_size = read_u16(arsc, chunk + 2)? as usize;
+    let flags = *arsc.get(chunk + 9)?;
+    let entry_count = read_u32(arsc, chunk + 12)? as usize;
+    let entries_start = read_u32(arsc, chunk + 16)? as usize;
+    let density = read_u16(arsc, chunk + 20 + 14)?;
+    let offsets = chunk + header_size;
+
+    let offset = if flags & FLAG_SPARSE != 0 {
+        (0..entry_count)
+            .find(|k| read_u16(arsc, offsets + k * 4) == Some(index as u16))
+            .and_then(|k| read_u16(arsc, offsets + k * 4 + 2))
+            .map(|o| o as usize * 4)?
+    } else if index >= entry_count {
+        return None;
+    } else if flags & FLAG_OFFSET16 != 0 {
+        match read_u16(arsc, offsets + index * 2)? {
+            0xffff => return None,
+            o => o as usize * 4,
+        }
     } else {
-        "0".to_string()
+        match read_u32(arsc, offsets + index * 4)? {
+            0xffff_ffff => return None,
+            o => o as usize,
+        }
     };
 
-    // Extract file extension
-    let extension = if !is_directory {
-        name.rsplit('.').next()
-            .filter(|ext| ext.len() <= 10 && ext.len() > 0 && ext != &name)
-            .map(|ext| ext.to_lowercase())
-    } else {
-        None
+    let entry = chunk + entries_start + offset;
+    let entry_flags = read_u16(arsc, entry + 2)?;
+    if entry_flags & ENTRY_FLAG_COMPACT != 0 {
+        return Some((density, (entry_flags >> 8) as u8, read_u32(arsc, entry + 4)?));
+    }
+    if entry_flags & ENTRY_FLAG_COMPLEX != 0 {
+        return None;
+    }
+    let value = entry + read_u16(arsc, entry)? as usize;
+    Some((density, *arsc.get(value + 3)?, read_u32(arsc, value + 4)?))
+}
+
+// APK entry of the best bitmap for resource `id`: the highest-density PNG/WebP, otherwise following
+// references and adaptive icons' <foreground> drawable (depth guards against reference loops)
+fn resolve_icon_bitmap(
+    read_entry: &mut dyn FnMut(&str) -> Option<Vec<u8>>,
+    arsc: &[u8],
+    strings: &[String],
+    id: u32,
+    depth: u8,
+) -> Option<String> {
+    if depth > 4 {
+        return None;
+    }
+    let mut values = arsc_values(arsc, id);
+    // nodpi/anydpi (0xfffe/0xffff) rank below real densities
+    values.sort_by_key(|(density, _, _)| std::cmp::Reverse(if *density >= 0xfffe { 0 } else { *density }));
+
+    let paths: Vec<&String> = values
+        .iter()
+        .filter(|(_, value_type, _)| *value_type == RES_VALUE_STRING)
+        .filter_map(|(_, _, data)| strings.get(*data as usize))
+        .collect();
+    if let Some(bitmap) = paths.iter().find(|p| p.ends_with(".png") || p.ends_with(".webp")) {
+        return Some(bitmap.to_string());
+    }
+
+    let references = values.iter().filter(|(_, value_type, _)| *value_type == RES_VALUE_REFERENCE);
+    for (_, _, target) in references {
+        if let Some(bitmap) = resolve_icon_bitmap(read_entry, arsc, strings, *target, depth + 1) {
+            return Some(bitmap);
+        }
+    }
+
+    for xml_path in paths.iter().filter(|p| p.ends_with(".xml")) {
+        let foreground = read_entry(xml_path.as_str())
+            .and_then(|xml| axml_reference_attr(&xml, "foreground", ANDROID_ATTR_DRAWABLE));
+        if let Some(bitmap) = foreground.and_then(|fg| resolve_icon_bitmap(read_entry, arsc, strings, fg, depth + 1)) {
+            return Some(bitmap);
+        }
+    }
+    None
+}
+
+// Resolve the APK's android:icon through AndroidManifest.xml and resources.arsc to a bitmap entry
+// Works for apps whose resource names were shortened by R8/aapt2; None for vector-only icons
+fn resolve_manifest_icon(read_entry: &mut dyn FnMut(&str) -> Option<Vec<u8>>) -> Option<String> {
+    let manifest = read_entry("AndroidManifest.xml")?;
+    let arsc = read_entry("resources.arsc")?;
+    let icon_id = axml_reference_attr(&manifest, "application", ANDROID_ATTR_ICON)?;
+    let strings = parse_string_pool(&arsc, read_u16(&arsc, 2)? as usize)?;
+    resolve_icon_bitmap(read_entry, &arsc, &strings, icon_id, 0)
+}
+
+// Fallback when the manifest can't be resolved: pick a launcher bitmap by its conventional entry name
+// Prefers ic_launcher over ic_launcher_round and the highest density available.
+// Apps with shrunk/obfuscated resource names return None.
+fn pick_launcher_icon(entry_names: &[String]) -> Option<String> {
+    const DENSITIES: [&str; 6] = ["xxxhdpi", "xxhdpi", "xhdpi", "hdpi", "mdpi", ""];
+
+    let score = |name: &str| -> Option<(usize, usize)> {
+        let (folder, file) = name.strip_prefix("res/")?.split_once('/')?;
+        if !(folder.starts_with("mipmap") || folder.starts_with("drawable")) {
+            return None;
+        }
+        let stem = file.strip_suffix(".png").or_else(|| file.strip_suffix(".webp"))?;
+        let name_rank = match stem {
+            "ic_launcher" => 0,
+            "ic_launcher_round" => 1,
+            "ic_launcher_foreground" => 2,
+            _ => return None,
+        };
+        let density_rank = DENSITIES
+            .iter()
+            .position(|d| !d.is_empty() && folder.contains(d))
+            .unwrap_or(DENSITIES.len() - 1);
+        Some((name_rank, density_rank))
     };
 
-    Some(FileEntry {
-        name,
-        permissions,
-        size,
-        date,
-        is_directory,
-        extension,
-    })
+    entry_names
+        .iter()
+        .filter_map(|name| score(name).map(|rank| (rank, name)))
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, name)| name.clone())
 }
 
-// Detect the primary storage path for an Android device
+// Get an installed app's launcher icon as a PNG data URL, or "placeholder:app" if none can be found
+// The APK is pulled and android:icon resolved through its manifest and resources.arsc, falling back to
+// ic_launcher file names; vector-only icons get the placeholder. Results are cached per package version.
 #[tauri::command]
-async fn detect_storage_path(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+async fn get_app_icon(app: tauri::AppHandle, device_id: String, package_name: String) -> Result<String, String> {
+    let valid_name = package_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+    if package_name.is_empty() || !valid_name {
+        return Err(format!("Invalid package name: {}", package_name));
+    }
+
     let shell = app.shell();
     let adb_cmd = get_adb_command();
 
-    // Try 1: Get EXTERNAL_STORAGE environment variable
-    let output = shell
+    let version_command = format!("dumpsys package {} | grep -m1 versionCode", package_name);
+    let version_output = shell
         .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", "echo $EXTERNAL_STORAGE"])
+        .args(["-s", &device_id, "shell", &version_command])
         .output()
         .await
-        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+        .map_err(|e| format!("Failed to query package version: {}", e))?;
+    let version = String::from_utf8_lossy(&version_output.stdout)
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("versionCode="))
+        .unwrap_or("0")
+        .to_string();
+
+    let cache_dir = thumbnail_cache_dir();
+    fs::create_dir_all(&cache_dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let cache_key = format!("{:x}", md5::compute(format!("{}:{}", package_name, version)));
+    let cached_icon_path = cache_dir.join(format!("app_icon_{}.png", cache_key));
+
+    if !cached_icon_path.exists() {
+        let path_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &format!("pm path {}", package_name)])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to locate APK: {}", e))?;
+        // Split APKs list several paths; the icon lives in base.apk
+        let stdout = String::from_utf8_lossy(&path_output.stdout);
+        let apk_paths: Vec<&str> = stdout
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("package:"))
+            .collect();
+        let apk_path = apk_paths
+            .iter()
+            .find(|path| path.ends_with("/base.apk"))
+            .or(apk_paths.first())
+            .ok_or_else(|| format!("Package not found: {}", package_name))?
+            .to_string();
+
+        let local_apk = cache_dir.join(format!("{}.apk", cache_key));
+        let pull_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "pull", &apk_path, &local_apk.to_string_lossy()])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to pull APK: {}", e))?;
+        if !pull_output.status.success() {
+            let _ = fs::remove_file(&local_apk);
+            return Err(format!("ADB pull failed: {}", String::from_utf8_lossy(&pull_output.stderr)));
+        }
 
-    if output.status.success() {
-        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !path.is_empty() && path != "$EXTERNAL_STORAGE" {
-            // Verify the path exists
-            let verify_output = shell
-                .command(&adb_cmd)
-                .args(["-s", &device_id, "shell", &format!("test -d '{}' && echo exists", path)])
-                .output()
-                .await
-                .ok();
+        let icon = (|| -> Result<Option<image::DynamicImage>, String> {
+            use std::io::Read;
+            let file = fs::File::open(&local_apk).map_err(|e| format!("Failed to open APK: {}", e))?;
+            let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read APK: {}", e))?;
+            let names: Vec<String> = archive.file_names().map(|name| name.to_string()).collect();
+            let mut read_entry = |name: &str| -> Option<Vec<u8>> {
+                let mut bytes = Vec::new();
+                archive.by_name(name).ok()?.read_to_end(&mut bytes).ok()?;
+                Some(bytes)
+            };
+            let Some(icon_name) = resolve_manifest_icon(&mut read_entry).or_else(|| pick_launcher_icon(&names)) else {
+                return Ok(None);
+            };
 
-            if let Some(verify) = verify_output {
-                if String::from_utf8_lossy(&verify.stdout).contains("exists") {
-                    // Resolve symlink to get actual path
-                    let resolve_output = shell
-                        .command(&adb_cmd)
-                        .args(["-s", &device_id, "shell", &format!("readlink -f '{}'", path)])
-                        .output()
-                        .await
-                        .ok();
+            let mut bytes = Vec::new();
+            archive
+                .by_name(&icon_name)
+                .map_err(|e| format!("Failed to read {}: {}", icon_name, e))?
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read {}: {}", icon_name, e))?;
+            Ok(image::load_from_memory(&bytes).ok())
+        })();
+        let _ = fs::remove_file(&local_apk);
+
+        match icon? {
+            Some(icon) => icon
+                .thumbnail(256, 256)
+                .save(&cached_icon_path)
+                .map_err(|e| format!("Failed to save icon: {}", e))?,
+            None => return Ok("placeholder:app".to_string()),
+        }
+    }
 
-                    if let Some(resolved) = resolve_output {
-                        let resolved_path = String::from_utf8_lossy(&resolved.stdout).trim().to_string();
-                        if !resolved_path.is_empty() {
-                            return Ok(resolved_path);
-                        }
-                    }
+    let icon_bytes = fs::read(&cached_icon_path).map_err(|e| format!("Failed to read cached icon: {}", e))?;
+    Ok(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(&icon_bytes)))
+}
 
-                    // If readlink fails, use path as-is
-                    return Ok(path);
-                }
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InstalledPackage {
+    pub package_name: String,
+    pub version_name: Option<String>,
+    pub first_install_time: Option<String>,
+    pub last_update_time: Option<String>,
+    pub last_update_epoch: Option<u64>,
+}
+
+// Parse `dumpsys package packages` into one entry per "Package [name]" block
+// Timestamps look like "firstInstallTime=2024-01-05 10:12:33" (device-local time); only the first
+// occurrence per package is kept since newer releases repeat them under each user.
+fn parse_package_install_times(output: &str) -> Vec<InstalledPackage> {
+    let mut packages: Vec<InstalledPackage> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Package [") {
+            if let Some((name, _)) = rest.split_once(']') {
+                packages.push(InstalledPackage {
+                    package_name: name.to_string(),
+                    version_name: None,
+                    first_install_time: None,
+                    last_update_time: None,
+                    last_update_epoch: None,
+                });
+            }
+            continue;
+        }
+
+        let Some(package) = packages.last_mut() else { continue };
+        if let Some(value) = line.strip_prefix("versionName=") {
+            package.version_name.get_or_insert_with(|| value.to_string());
+        } else if let Some(value) = line.strip_prefix("firstInstallTime=") {
+            package.first_install_time.get_or_insert_with(|| value.to_string());
+        } else if let Some(value) = line.strip_prefix("lastUpdateTime=") {
+            if package.last_update_time.is_none() {
+                package.last_update_time = Some(value.to_string());
+                package.last_update_epoch = value
+                    .split_once(' ')
+                    .and_then(|(date, time)| parse_ls_datetime(date, time));
             }
         }
     }
 
-    // Try 2: Check common symlinks (/sdcard usually points to the right place)
-    let sdcard_paths = vec!["/sdcard", "/mnt/sdcard", "/storage/self/primary", "/mnt/user/0/primary"];
+    packages
+}
 
-    for sdcard_path in sdcard_paths {
-        let output = shell
-            .command(&adb_cmd)
-            .args(["-s", &device_id, "shell", &format!("test -d '{}' && echo exists", sdcard_path)])
-            .output()
-            .await
-            .ok();
+// List installed packages, most recently installed/updated first
+#[tauri::command]
+async fn list_packages_by_install_time(
+    app: tauri::AppHandle,
+    device_id: String,
+    limit: usize,
+) -> Result<Vec<InstalledPackage>, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", "dumpsys package packages"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list packages: {}", e))?;
 
-        if let Some(verify) = output {
-            if String::from_utf8_lossy(&verify.stdout).contains("exists") {
-                // Resolve symlink to get actual path
-                let resolve_output = shell
-                    .command(&adb_cmd)
-                    .args(["-s", &device_id, "shell", &format!("readlink -f '{}'", sdcard_path)])
-                    .output()
-                    .await
-                    .ok();
+    if !output.status.success() {
+        return Err(format!("Failed to list packages: {}", String::from_utf8_lossy(&output.stderr)));
+    }
 
-                if let Some(resolved) = resolve_output {
-                    let resolved_path = String::from_utf8_lossy(&resolved.stdout).trim().to_string();
-                    if !resolved_path.is_empty() {
-                        return Ok(resolved_path);
-                    }
-                }
+    let mut packages = parse_package_install_times(&String::from_utf8_lossy(&output.stdout));
+    packages.sort_by(|a, b| {
+        b.last_update_epoch
+            .cmp(&a.last_update_epoch)
+            .then_with(|| a.package_name.cmp(&b.package_name))
+    });
+    if limit > 0 {
+        packages.truncate(limit);
+    }
+    Ok(packages)
+}
 
-                // If readlink fails, just use the path as-is
-                return Ok(sdcard_path.to_string());
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AppMemInfo {
+    pub package_name: String,
+    pub total_pss_kb: Option<u64>,
+    pub private_dirty_kb: Option<u64>,
+    pub java_heap_kb: Option<u64>,
+    pub native_heap_kb: Option<u64>,
+    pub native_heap_alloc_kb: Option<u64>,
+    pub dalvik_heap_alloc_kb: Option<u64>,
+}
+
+// Parse `dumpsys meminfo <pkg>` (all figures in KB)
+// Uses the per-category table ("Native Heap  <pss> <private dirty> ... <heap size> <alloc> <free>",
+// "TOTAL <pss> <private dirty> ...") and the "App Summary" section ("Java Heap: <pss>", "TOTAL PSS: <kb>").
+fn parse_meminfo(package_name: &str, output: &str) -> AppMemInfo {
+    let numbers = |rest: &str| -> Vec<u64> {
+        rest.split_whitespace().map_while(|v| v.parse::<u64>().ok()).collect()
+    };
+
+    let mut info = AppMemInfo {
+        package_name: package_name.to_string(),
+        ..Default::default()
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("TOTAL PSS:") {
+            info.total_pss_kb = numbers(rest).first().copied().or(info.total_pss_kb);
+        } else if let Some(rest) = line.strip_prefix("TOTAL ") {
+            let values = numbers(rest);
+            if values.len() >= 2 {
+                info.total_pss_kb.get_or_insert(values[0]);
+                info.private_dirty_kb = Some(values[1]);
+            }
+        } else if let Some(rest) = line.strip_prefix("Java Heap:") {
+            info.java_heap_kb = numbers(rest).first().copied();
+        } else if let Some(rest) = line.strip_prefix("Native Heap:") {
+            info.native_heap_kb = numbers(rest).first().copied();
+        } else if let Some(rest) = line.strip_prefix("Native Heap ") {
+            let values = numbers(rest);
+            if values.len() >= 8 {
+                info.native_heap_alloc_kb = Some(values[values.len() - 2]);
+            }
+        } else if let Some(rest) = line.strip_prefix("Dalvik Heap ") {
+            let values = numbers(rest);
+            if values.len() >= 8 {
+                info.dalvik_heap_alloc_kb = Some(values[values.len() - 2]);
             }
         }
     }
 
-    // Try 3: Default to /storage/emulated/0 (most common path)
-    Ok("/storage/emulated/0".to_string())
+    info
 }
 
-// Check if ADB is available
+// Memory snapshot (PSS, private dirty, heaps) for a running app
 #[tauri::command]
-async fn check_adb(app: tauri::AppHandle) -> Result<bool, String> {
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+async fn get_app_meminfo(app: tauri::AppHandle, device_id: String, package_name: String) -> Result<AppMemInfo, String> {
+    let valid_name = package_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+    if package_name.is_empty() || !valid_name {
+        return Err(format!("Invalid package name: {}", package_name));
+    }
 
-    let output = shell
-        .command(&adb_cmd)
-        .args(["version"])
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &format!("dumpsys meminfo {}", package_name)])
         .output()
         .await
-        .map_err(|_| "ADB is not installed or not in PATH".to_string())?;
+        .map_err(|e| format!("Failed to read memory info: {}", e))?;
 
-    Ok(output.status.success())
-}
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.contains("No process found") {
+        return Err(format!("App is not running: {}", package_name));
+    }
+    if !output.status.success() {
+        return Err(format!("Failed to read memory info: {}", String::from_utf8_lossy(&output.stderr)));
+    }
 
-// Set custom ADB path
-#[tauri::command]
-fn set_adb_path(path: String) -> Result<(), String> {
-    let path_buf = PathBuf::from(&path);
-    if !path_buf.exists() {
-        return Err("ADB executable not found at specified path".to_string());
+    let info = parse_meminfo(&package_name, &stdout);
+    if info.total_pss_kb.is_none() {
+        return Err(format!("Unexpected meminfo output for {}", package_name));
     }
+    Ok(info)
+}
 
-    if let Ok(mut guard) = ADB_PATH.lock() {
-        *guard = Some(path);
-        Ok(())
+// Get a thumbnail for images/videos, or a `placeholder:<kind>` identifier for any other file
+// Keeps file type classification in one place so the frontend only maps identifiers to icons
+#[tauri::command]
+async fn get_thumbnail_or_placeholder(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    extension: String,
+    file_size: String,
+) -> Result<String, String> {
+    let ext_lower = extension.to_lowercase();
+    if is_image_extension(&ext_lower) || is_video_extension(&ext_lower) {
+        get_thumbnail(app, device_id, file_path, extension, file_size).await
     } else {
-        Err("Failed to set ADB path".to_string())
+        Ok(placeholder_for_extension(&ext_lower).to_string())
     }
 }
 
-// Get current ADB path (for display purposes)
-#[tauri::command]
-fn get_current_adb_path() -> String {
-    get_adb_command()
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThumbnailTicket {
+    pub token: String,
+    pub placeholder: String,
 }
 
-// Delete a file or directory on the Android device
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThumbnailReady {
+    pub token: String,
+    pub file_path: String,
+    pub thumbnail: Option<String>,
+    pub error: Option<String>,
+}
+
+// Two-phase thumbnail: immediately return a type-appropriate placeholder and a token,
+// then emit `thumbnail-ready` with the real image (or error) once it has been generated
 #[tauri::command]
-async fn delete_file(
+fn request_thumbnail(
     app: tauri::AppHandle,
     device_id: String,
     file_path: String,
-    is_directory: bool,
-) -> Result<(), String> {
-    if is_critical_system_path(&file_path) {
-        return Err(format!("Cannot delete critical system path: {}", file_path));
-    }
-
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+    extension: String,
+    file_size: String,
+) -> ThumbnailTicket {
+    static TOKEN_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let token = TOKEN_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed).to_string();
 
-    // Escape single quotes in path
-    let escaped_path = file_path.replace("'", "'\\''");
-
-    // Use rm -r for directories, rm for files
-    let rm_command = if is_directory {
-        format!("rm -r '{}'", escaped_path)
+    let ext_lower = extension.to_lowercase();
+    let placeholder = if is_image_extension(&ext_lower) {
+        "placeholder:image"
+    } else if is_video_extension(&ext_lower) {
+        "placeholder:video"
     } else {
-        format!("rm '{}'", escaped_path)
+        placeholder_for_extension(&ext_lower)
     };
 
-    let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &rm_command])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute delete command: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("Permission denied") {
-            return Err(format!("Permission denied: Cannot delete {}", file_path));
-        } else if stderr.contains("Directory not empty") {
-            return Err(format!("Directory not empty: {}", file_path));
-        } else if stderr.contains("No such file") {
-            return Err(format!("File not found: {}", file_path));
-        } else {
-            return Err(format!("Delete failed: {}", stderr));
-        }
+    // Non-media files only ever get the placeholder
+    if placeholder == "placeholder:image" || placeholder == "placeholder:video" {
+        let token = token.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = get_thumbnail(app.clone(), device_id, file_path.clone(), extension, file_size).await;
+            let (thumbnail, error) = match result {
+                Ok(thumbnail) => (Some(thumbnail), None),
+                Err(e) => (None, Some(e)),
+            };
+            let _ = app.emit("thumbnail-ready", ThumbnailReady { token, file_path, thumbnail, error });
+        });
     }
 
-    Ok(())
+    ThumbnailTicket {
+        token,
+        placeholder: placeholder.to_string(),
+    }
 }
 
-// Rename a file or directory on the Android device
+// Return a thumbnail only if it is already cached, without pulling anything from the device
+// Returns "not-cached" on a miss so the UI can defer the full get_thumbnail call
 #[tauri::command]
-async fn rename_file(
-    app: tauri::AppHandle,
-    device_id: String,
-    old_path: String,
-    new_name: String,
-) -> Result<(), String> {
-    // Validate new name doesn't contain path separators
-    if new_name.contains('/') || new_name.contains('\\') {
-        return Err("Invalid name: cannot contain path separators".to_string());
-    }
-
-    // Validate new name is not empty
-    if new_name.trim().is_empty() {
-        return Err("Invalid name: cannot be empty".to_string());
+fn get_cached_thumbnail(device_id: String, file_path: String, max_size: u32) -> Result<String, String> {
+    let cached_thumb_path = thumbnail_cache_dir()
+        .join(format!("thumb_{}.png", thumbnail_cache_key(&device_id, &file_path)));
+
+    // Pick up entries cached under an older adb id, as get_thumbnail does
+    migrate_cached_thumbnail(&device_id, &file_path);
+    if !cached_thumb_path.exists() {
+        return Ok("not-cached".to_string());
     }
 
-    if is_critical_system_path(&old_path) {
-        return Err(format!("Cannot rename critical system path: {}", old_path));
+    let mut thumb_bytes = std::fs::read(&cached_thumb_path)
+        .map_err(|e| format!("Failed to read cached thumbnail: {}", e))?;
+
+    // Cached thumbnails are 256px; shrink in memory when the caller wants smaller icons
+    if max_size > 0 && max_size < 256 {
+        let img = image::load_from_memory(&thumb_bytes)
+            .map_err(|e| format!("Failed to decode cached thumbnail: {}", e))?;
+        let mut resized = std::io::Cursor::new(Vec::new());
+        img.thumbnail(max_size, max_size)
+            .write_to(&mut resized, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+        thumb_bytes = resized.into_inner();
     }
 
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
-
-    // Build the new path by replacing the filename
-    let parent_path = old_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
-    let new_path = if parent_path.is_empty() {
-        format!("/{}", new_name)
-    } else {
-        format!("{}/{}", parent_path, new_name)
-    };
-
-    // Escape single quotes in paths
-    let escaped_old_path = old_path.replace("'", "'\\''");
-    let escaped_new_path = new_path.replace("'", "'\\''");
+    let base64_string = general_purpose::STANDARD.encode(&thumb_bytes);
+    Ok(format!("data:image/png;base64,{}", base64_string))
+}
 
-    // Use mv command to rename
-    let mv_command = format!("mv '{}' '{}'", escaped_old_path, escaped_new_path);
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TempDirHealth {
+    pub path: String,
+    pub writable: bool,
+    pub available_bytes: Option<u64>,
+    pub error: Option<String>,
+}
 
-    let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &mv_command])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute rename command: {}", e))?;
+// Check the thumbnail cache folder can be created and written, and how much space its volume has left
+#[tauri::command]
+fn check_temp_dir() -> TempDirHealth {
+    let cache_dir = thumbnail_cache_dir();
+    let probe_file = cache_dir.join(format!(".write_test_{}", std::process::id()));
+
+    let write_result = std::fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create {}: {}", cache_dir.display(), e))
+        .and_then(|_| {
+            std::fs::write(&probe_file, b"droiddock")
+                .map_err(|e| format!("Failed to write to {}: {}", cache_dir.display(), e))
+        });
+    let _ = std::fs::remove_file(&probe_file);
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("Permission denied") {
-            return Err(format!("Permission denied: Cannot rename {}", old_path));
-        } else if stderr.contains("File exists") || stderr.contains("already exists") {
-            return Err(format!("A file or folder named '{}' already exists", new_name));
-        } else if stderr.contains("No such file") {
-            return Err(format!("File not found: {}", old_path));
-        } else {
-            return Err(format!("Rename failed: {}", stderr));
-        }
+    TempDirHealth {
+        path: cache_dir.to_string_lossy().to_string(),
+        writable: write_result.is_ok(),
+        available_bytes: local_available_space(&cache_dir).ok(),
+        error: write_result.err(),
     }
-
-    Ok(())
 }
 
-// Search for files on the Android device
+// Number of thumbnails generated at once while prefetching
+const PREFETCH_CONCURRENCY: usize = 3;
+
+// Warm the thumbnail cache for files the user is likely to scroll to next
+// Returns immediately; thumbnails are generated in the background and nothing is sent back
+// The cache holds 256px thumbnails that get_cached_thumbnail scales down, so larger sizes are skipped
 #[tauri::command]
-async fn search_files(
-    app: tauri::AppHandle,
-    device_id: String,
-    search_path: String,
-    pattern: String,
-    recursive: bool,
-) -> Result<Vec<FileEntry>, String> {
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+fn prefetch_thumbnails(app: tauri::AppHandle, device_id: String, file_paths: Vec<String>, max_size: u32) {
+    if max_size > 256 {
+        return;
+    }
 
-    // Escape single quotes in path and pattern
-    let escaped_path = search_path.replace("'", "'\\''");
-    let escaped_pattern = pattern.replace("'", "'\\''");
+    let pending: std::collections::VecDeque<String> = file_paths
+        .into_iter()
+        .filter(|path| {
+            migrate_cached_thumbnail(&device_id, path);
+            !thumbnail_cache_dir()
+                .join(format!("thumb_{}.png", thumbnail_cache_key(&device_id, path)))
+                .exists()
+        })
+        .collect();
+    let pending = std::sync::Arc::new(Mutex::new(pending));
 
-    // Build find command
-    // Use -iname for case-insensitive search
-    // Exclude Android/data and Android/obb but keep Android/media
-    // Redirect stderr to /dev/null to suppress permission denied errors
-    let max_depth_arg = if recursive { "" } else { "-maxdepth 1" };
-    let exclusions = if recursive {
-        // Only add exclusions for recursive searches
-        r#"\( -path '*/Android/data' -o -path '*/Android/obb' \) -prune -o"#
-    } else {
-        ""
-    };
-    let find_command = format!(
-        "find '{}' {} {} -iname '*{}*' -exec ls -ld {{}} \\; 2>/dev/null",
-        escaped_path, max_depth_arg, exclusions, escaped_pattern
-    );
+    for _ in 0..PREFETCH_CONCURRENCY {
+        let app = app.clone();
+        let device_id = device_id.clone();
+        let pending = pending.clone();
 
-    let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &find_command])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute search command: {}", e))?;
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let next = pending.lock().ok().and_then(|mut queue| queue.pop_front());
+                let Some(file_path) = next else { break };
 
-    // Don't check exit status - find returns non-zero if it encounters permission errors
-    // but we've redirected stderr to /dev/null and want to process whatever results we got
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<FileEntry> = stdout
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .filter_map(|line| parse_ls_line(line))
-        .collect();
+                let extension = file_path.rsplit_once('.').map(|(_, ext)| ext.to_string()).unwrap_or_default();
+                let ext_lower = extension.to_lowercase();
+                if !is_image_extension(&ext_lower) && !is_video_extension(&ext_lower) {
+                    continue;
+                }
 
-    Ok(files)
+                // Look up the size so get_thumbnail still skips very large files
+                let file_size = get_remote_file_size(&app, &device_id, &file_path)
+                    .await
+                    .map(|size| size.to_string())
+                    .unwrap_or_default();
+                let _ = get_thumbnail(app.clone(), device_id.clone(), file_path, extension, file_size).await;
+            }
+        });
+    }
 }
 
-// Get storage information from the Android device
+// Get list of connected ADB devices
 #[tauri::command]
-async fn get_storage_info(
-    app: tauri::AppHandle,
-    device_id: String,
-    path: String,
-) -> Result<StorageInfo, String> {
+async fn get_devices(app: tauri::AppHandle) -> Result<Vec<AdbDevice>, String> {
     let shell = app.shell();
     let adb_cmd = get_adb_command();
 
-    // Escape single quotes in path
-    let escaped_path = path.replace("'", "'\\''");
-
-    // Use df command to get storage stats for the given path
-    let df_command = format!("df '{}'", escaped_path);
-
     let output = shell
         .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &df_command])
+        .args(["devices", "-l"])
         .output()
         .await
-        .map_err(|e| format!("Failed to get storage info: {}", e))?;
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Storage info failed: {}", stderr));
+        return Err(format!("ADB command failed: {}", String::from_utf8_lossy(&output.stderr)));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut devices = parse_adb_devices_output(&String::from_utf8_lossy(&output.stdout));
 
-    // Parse df output - format varies but typically:
-    // Filesystem     1K-blocks    Used Available Use% Mounted on
-    // /dev/block/... 123456789 45678901 77777888  37% /storage/emulated
+    // Query each device for its friendly model name
+    for device in &mut devices {
+        if device.status != "device" {
+            continue;
+        }
 
-    for line in stdout.lines().skip(1) {
-        // Skip header
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 4 {
-            // Try to parse the size fields (in KB typically)
-            if let (Ok(total_kb), Ok(used_kb), Ok(free_kb)) = (
-                parts[1].parse::<u64>(),
-                parts[2].parse::<u64>(),
-                parts[3].parse::<u64>(),
-            ) {
-                let total_bytes = total_kb * 1024;
-                let used_bytes = used_kb * 1024;
-                let free_bytes = free_kb * 1024;
-                let percentage_used = if total_bytes > 0 {
-                    (used_bytes as f64 / total_bytes as f64) * 100.0
-                } else {
-                    0.0
-                };
+        let brand = shell
+            .command(&adb_cmd)
+            .args(["-s", &device.id, "shell", "getprop ro.product.brand"])
+            .output()
+            .await
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default();
 
-                return Ok(StorageInfo {
-                    total_bytes,
-                    used_bytes,
-                    free_bytes,
-                    percentage_used,
-                });
+        let model = shell
+            .command(&adb_cmd)
+            .args(["-s", &device.id, "shell", "getprop ro.product.model"])
+            .output()
+            .await
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default();
+
+        device.model = match (brand.is_empty(), model.is_empty()) {
+            (false, false) => format!("{} {}", brand, model),
+            (false, true) => brand,
+            (true, false) => model,
+            (true, true) => std::mem::take(&mut device.model),
+        };
+
+        let serial = shell
+            .command(&adb_cmd)
+            .args(["-s", &device.id, "shell", "getprop ro.serialno"])
+            .output()
+            .await
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default();
+        if !serial.is_empty() {
+            if let Ok(mut serials) = DEVICE_SERIALS.lock() {
+                serials.insert(device.id.clone(), serial);
             }
         }
+
+        // Emulators attached over TCP don't use the emulator- serial, so also check the qemu props
+        if !device.is_emulator {
+            let qemu_props = shell
+                .command(&adb_cmd)
+                .args(["-s", &device.id, "shell", "getprop ro.kernel.qemu; getprop ro.boot.qemu"])
+                .output()
+                .await
+                .ok()
+                .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+                .unwrap_or_default();
+            device.is_emulator = is_qemu_prop_set(&qemu_props);
+        }
     }
 
-    Err("Failed to parse storage information".to_string())
-}
+    // Resolve display names: user nickname, then model, then serial
+    let mut settings = read_device_settings_file(&app).unwrap_or_default();
+    let mut reconnected = false;
+    for device in &mut devices {
+        device.alias = resolve_device_alias(
+            settings.get(&device.id).and_then(|s| s.nickname.as_deref()),
+            &device.model,
+            &device.id,
+        );
 
-// Download a file from the Android device to the local filesystem
-#[tauri::command]
-async fn download_file(
-    app: tauri::AppHandle,
-    device_id: String,
-    device_path: String,
-    local_path: String,
-    skip_existing: bool,
-) -> Result<String, String> {
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+        if device.status == "device" {
+            if let Some(device_settings) = settings.get_mut(&device.id).filter(|s| s.stale) {
+                device_settings.stale = false;
+                reconnected = true;
+            }
+        }
+    }
+    if reconnected {
+        let _ = write_device_settings_file(&app, &settings);
+    }
 
-    // First, get the file's modification time from the device
-    let escaped_path = device_path.replace("'", "'\\''");
-    let stat_command = format!("stat -c %Y '{}'", escaped_path);
+    Ok(devices)
+}
 
-    let stat_output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &stat_command])
-        .output()
-        .await
-        .ok();
+// Infer how a device is attached from its serial
+// "emulator-5554" is an emulator, "192.168.1.20:5555" (or an mDNS "._adb-tls-connect._tcp" name) is TCP,
+// anything else is USB. Returns (transport_type, tcp_host, tcp_port).
+fn parse_transport(id: &str) -> (String, Option<String>, Option<u16>) {
+    if id.starts_with("emulator-") {
+        return ("emulator".to_string(), None, None);
+    }
+    if let Some((host, port)) = id.rsplit_once(':') {
+        if let Ok(port) = port.parse::<u16>() {
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            return ("tcp".to_string(), Some(host.to_string()), Some(port));
+        }
+    }
+    if id.contains("._adb-tls-connect._tcp") {
+        return ("tcp".to_string(), None, None);
+    }
+    ("usb".to_string(), None, None)
+}
 
-    let mtime = stat_output
-        .and_then(|output| {
-            String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .parse::<u64>()
-                .ok()
-        });
+// True when any line of `getprop ro.kernel.qemu; getprop ro.boot.qemu` output is "1"
+fn is_qemu_prop_set(output: &str) -> bool {
+    output.lines().any(|line| line.trim() == "1")
+}
 
-    // Skip transfer when duplicate handling is enabled and destination already exists.
-    let local_file_path = std::path::Path::new(&local_path);
-    if skip_existing && local_file_path.exists() {
-        return Ok("skipped".to_string());
+// Pick the friendliest available name for a device
+fn resolve_device_alias(nickname: Option<&str>, model: &str, id: &str) -> String {
+    match nickname.map(str::trim) {
+        Some(nickname) if !nickname.is_empty() => nickname.to_string(),
+        _ if !model.is_empty() => model.to_string(),
+        _ => id.to_string(),
     }
+}
 
-    // Use adb pull to download the file
-    let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "pull", &device_path, &local_path])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to download file: {}", e))?;
+// How often the active device's connection state is checked
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("does not exist") {
-            return Err(format!("File not found: {}", device_path));
-        } else if stderr.contains("Permission denied") {
-            return Err(format!("Permission denied: {}", device_path));
-        } else {
-            return Err(format!("Download failed: {}", stderr));
-        }
+// Incremented whenever the health check is (re)started or stopped, so stale loops exit
+static HEALTH_CHECK_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceStateChange {
+    pub device_id: String,
+    pub previous_state: String,
+    pub state: String,
+}
+
+// Interpret `adb get-state` output; errors like "device 'X' not found" arrive on stderr
+fn parse_get_state(stdout: &str, stderr: &str) -> String {
+    let state = stdout.trim();
+    if !state.is_empty() {
+        return state.to_string();
+    }
+    if stderr.contains("unauthorized") {
+        "unauthorized".to_string()
+    } else if stderr.contains("offline") {
+        "offline".to_string()
+    } else if stderr.contains("not found") {
+        "disconnected".to_string()
+    } else {
+        "unknown".to_string()
     }
+}
 
-    // Set the modification time on the downloaded file to match the source
-    if let Some(timestamp) = mtime {
-        if local_file_path.exists() {
-            let mtime_system = UNIX_EPOCH + Duration::from_secs(timestamp);
-            let file = fs::File::open(local_file_path)
-                .map_err(|e| format!("Failed to open downloaded file: {}", e))?;
+// Query the current connection state of a device
+async fn get_device_state(app: &tauri::AppHandle, device_id: &str) -> String {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "get-state"])
+        .output()
+        .await;
 
-            file.set_modified(mtime_system)
-                .map_err(|e| format!("Failed to set modification time: {}", e))?;
+    match output {
+        Ok(o) => parse_get_state(&String::from_utf8_lossy(&o.stdout), &String::from_utf8_lossy(&o.stderr)),
+        Err(_) => "unknown".to_string(),
+    }
+}
 
-            // Set creation/birth time on macOS to match the source file
-            let _ = set_creation_time(local_file_path, timestamp);
+// Start watching the active device, emitting `device-state-changed` when its state changes
+// Replaces any previously watched device
+#[tauri::command]
+fn start_health_check(app: tauri::AppHandle, device_id: String) {
+    let generation = HEALTH_CHECK_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+
+    tauri::async_runtime::spawn(async move {
+        let mut previous_state = "device".to_string();
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+            if HEALTH_CHECK_GENERATION.load(std::sync::atomic::Ordering::SeqCst) != generation {
+                break;
+            }
+
+            let state = get_device_state(&app, &device_id).await;
+            if state != previous_state {
+                let _ = app.emit("device-state-changed", DeviceStateChange {
+                    device_id: device_id.clone(),
+                    previous_state: previous_state.clone(),
+                    state: state.clone(),
+                });
+                previous_state = state;
+            }
+        }
+    });
+}
+
+// Stop watching the active device
+#[tauri::command]
+fn stop_health_check() {
+    HEALTH_CHECK_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+// Poll a device's state until it is "device" or timeout_secs elapse, returning the last state seen
+// Covers the brief "unauthorized"/"authorizing" window right after plugging in while the user taps Allow
+#[tauri::command]
+async fn await_device_ready(app: tauri::AppHandle, device_id: String, timeout_secs: u64) -> String {
+    let deadline = std::time::Instant::now() + Duration::from_secs(timeout_secs);
+    loop {
+        let state = get_device_state(&app, &device_id).await;
+        if state == "device" || std::time::Instant::now() >= deadline {
+            return state;
         }
+        tokio::time::sleep(Duration::from_millis(500)).await;
     }
+}
 
-    Ok("downloaded".to_string())
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthorizationDiagnosis {
+    pub state: String,
+    pub guidance: Vec<String>,
+    pub server_restart_may_help: bool,
+    pub server_restarted: bool,
 }
 
-// Upload a file from the local filesystem to the Android device
+// Recovery steps for a device state, and whether restarting the adb server is worth trying
+fn authorization_guidance(state: &str) -> (Vec<String>, bool) {
+    let steps: &[&str] = match state {
+        "device" => &[],
+        "unauthorized" => &[
+            "Unlock the device and look for the \"Allow USB debugging?\" prompt; tick \"Always allow from this computer\" and tap Allow.",
+            "If no prompt appears, open Developer options, tap \"Revoke USB debugging authorizations\", then unplug and reconnect.",
+            "Restarting the adb server makes it present its key again, which often brings the prompt back.",
+            "If this computer's adb key was regenerated (e.g. ~/.android/adbkey deleted), the device must approve the new key.",
+        ],
+        "offline" => &[
+            "Unplug and reconnect the cable, preferably directly rather than through a hub.",
+            "Restarting the adb server clears stale connections that show up as offline.",
+        ],
+        "disconnected" => &[
+            "Check the cable and that USB debugging is enabled in Developer options.",
+            "Switch the USB mode to File transfer if the device isn't detected.",
+        ],
+        _ => &["Restart the adb server and reconnect the device."],
+    };
+    let restart_may_help = matches!(state, "unauthorized" | "offline" | "unknown");
+    (steps.iter().map(|s| s.to_string()).collect(), restart_may_help)
+}
+
+// Explain why a device isn't usable and how to recover; with restart_server, also restarts the adb server
+// when that's likely to help and reports the state afterwards
 #[tauri::command]
-async fn upload_file(
+async fn diagnose_authorization(
     app: tauri::AppHandle,
     device_id: String,
-    local_path: String,
-    device_path: String,
-) -> Result<(), String> {
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+    restart_server: bool,
+) -> AuthorizationDiagnosis {
+    let mut state = get_device_state(&app, &device_id).await;
+    let (mut guidance, server_restart_may_help) = authorization_guidance(&state);
+    let mut server_restarted = false;
+
+    if restart_server && server_restart_may_help {
+        let shell = app.shell();
+        let adb_cmd = get_adb_command();
+        let watcher_running = ACTIVE_JOBS.lock().is_ok_and(|jobs| jobs.contains_key(DEVICE_WATCHER_JOB));
+
+        let _ = shell.command(&adb_cmd).args(["kill-server"]).output().await;
+        server_restarted = shell
+            .command(&adb_cmd)
+            .args(["start-server"])
+            .output()
+            .await
+            .is_ok_and(|o| o.status.success());
 
-    // Verify local file exists
-    if !std::path::Path::new(&local_path).exists() {
-        return Err(format!("Local file not found: {}", local_path));
+        // track-devices dies with the old server
+        if watcher_running {
+            let _ = restart_device_watcher(app.clone());
+        }
+
+        state = get_device_state(&app, &device_id).await;
+        guidance = authorization_guidance(&state).0;
     }
 
-    // Use adb push to upload the file
-    let output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "push", &local_path, &device_path])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to upload file: {}", e))?;
+    AuthorizationDiagnosis {
+        state,
+        guidance,
+        server_restart_may_help,
+        server_restarted,
+    }
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("Permission denied") {
-            return Err(format!("Permission denied: {}", device_path));
-        } else if stderr.contains("Read-only file system") {
-            return Err(format!("Read-only file system: {}", device_path));
-        } else {
-            return Err(format!("Upload failed: {}", stderr));
-        }
+// Job id of the long-lived `adb track-devices` child while the device watcher is running
+const DEVICE_WATCHER_JOB: &str = "device-watcher";
+
+// Kill the device watcher child, if any
+fn stop_device_watcher() {
+    if let Some(child) = unregister_job(DEVICE_WATCHER_JOB) {
+        let _ = child.kill();
     }
+}
 
-    // Preserve the local file's modification time on the device
-    let local_path_obj = std::path::Path::new(&local_path);
-    if let Ok(metadata) = local_path_obj.metadata() {
-        if let Ok(modified) = metadata.modified() {
-            if let Ok(duration) = modified.duration_since(UNIX_EPOCH) {
-                let mtime = duration.as_secs();
-                let escaped_path = device_path.replace("'", "'\\''");
-                let touch_cmd = format!("touch -d @{} '{}'", mtime, escaped_path);
-                let _ = shell
-                    .command(&adb_cmd)
-                    .args(["-s", &device_id, "shell", &touch_cmd])
-                    .output()
-                    .await;
+// (Re)start `adb track-devices`, emitting `devices-changed` with the device list on every change
+// Call again after an adb server restart; the old watcher is killed first
+#[tauri::command]
+fn restart_device_watcher(app: tauri::AppHandle) -> Result<(), String> {
+    stop_device_watcher();
+
+    // Raw output keeps each track-devices report in one chunk instead of one event per line
+    let (mut rx, child) = app
+        .shell()
+        .command(get_adb_command())
+        .args(["track-devices"])
+        .set_raw_out(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start device watcher: {}", e))?;
+    let pid = child.pid();
+
+    register_job(Some(DEVICE_WATCHER_JOB), "track-devices", None, child);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(_) => {
+                    if let Ok(devices) = get_devices(app.clone()).await {
+                        let _ = app.emit("devices-changed", &devices);
+                    }
+                }
+                CommandEvent::Terminated(_) => break,
+                _ => {}
             }
         }
-    }
 
-    Ok(())
-}
+        // Forget the child if it exited on its own (e.g. adb server killed) and wasn't replaced
+        if let Ok(mut jobs) = ACTIVE_JOBS.lock() {
+            if jobs.get(DEVICE_WATCHER_JOB).is_some_and(|job| job.child.pid() == pid) {
+                jobs.remove(DEVICE_WATCHER_JOB);
+            }
+        }
+    });
 
-// Response type for file preview
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FilePreview {
-    pub file_type: String,  // "image", "text", or "unsupported"
-    pub content: String,    // base64 for images, text content for text files
-    pub size: u64,          // file size in bytes
+    Ok(())
 }
 
-// Preview a file from the Android device
+// List files in a directory on the Android device
 #[tauri::command]
-async fn preview_file(
+async fn list_files(
     app: tauri::AppHandle,
     device_id: String,
-    device_path: String,
-    extension: Option<String>,
-) -> Result<FilePreview, String> {
+    path: String,
+    sort_by: Option<String>,
+) -> Result<Vec<FileEntry>, AdbError> {
     let shell = app.shell();
     let adb_cmd = get_adb_command();
 
-    // Define max file size for preview (10MB for text, 50MB for images)
-    const MAX_TEXT_SIZE: u64 = 10 * 1024 * 1024;
-    const MAX_IMAGE_SIZE: u64 = 50 * 1024 * 1024;
-
-    // Get file size first
     // Escape single quotes in path and wrap in quotes to handle spaces
-    let escaped_path = device_path.replace("'", "'\\''");
-    let stat_command = format!("stat -c %s '{}'", escaped_path);
-
-    let stat_output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "shell", &stat_command])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to get file size: {}", e))?;
+    let escaped_path = path.replace("'", "'\\''");
 
-    if !stat_output.status.success() {
-        return Err("Failed to get file information".to_string());
-    }
+    // Minimal recovery shells may reject -la, so fall back to -l and then plain names
+    let mut files: Vec<FileEntry> = Vec::new();
+    for ls_flags in ["-la", "-l", ""] {
+        let shell_command = format!("ls {} '{}'", ls_flags, escaped_path);
 
-    let size_str = String::from_utf8_lossy(&stat_output.stdout).trim().to_string();
-    let file_size: u64 = size_str.parse()
-        .map_err(|_| "Failed to parse file size".to_string())?;
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &shell_command])
+            .output()
+            .await
+            .map_err(AdbError::from_spawn)?;
 
-    // Determine file type - handle extension with or without dot prefix
-    let ext = extension
-        .as_ref()
-        .map(|e| {
-            let trimmed = e.trim();
-            if trimmed.starts_with('.') {
-                &trimmed[1..]
-            } else {
-                trimmed
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !ls_flags.is_empty() && is_invalid_option_error(&stderr) {
+                continue;
             }
-        })
-        .unwrap_or("")
-        .to_lowercase();
+            return Err(AdbError::from_stderr(&stderr, &path));
+        }
 
-    // Add debug logging (using eprintln! for simplicity in debug builds)
-    // Note: For larger applications, consider using the 'log' crate
-    #[cfg(debug_assertions)]
-    {
-        eprintln!("Preview file debug - extension param: {:?}", extension);
-        eprintln!("Preview file debug - processed ext: {}", ext);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines = stdout
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter(|line| !line.starts_with("total"));
+        files = if ls_flags.is_empty() {
+            lines.filter_map(name_only_file_entry).collect()
+        } else {
+            lines.filter_map(parse_ls_line).collect()
+        };
+        break;
     }
 
-    let is_image = is_image_extension(&ext);
-    let is_text = is_text_extension(&ext);
-
-    #[cfg(debug_assertions)]
-    {
-        eprintln!("Preview file debug - is_image: {}, is_text: {}", is_image, is_text);
+    if sort_by.as_deref() == Some("type") {
+        files.sort_by(compare_by_type);
     }
 
-    if !is_image && !is_text {
-        return Ok(FilePreview {
-            file_type: "unsupported".to_string(),
-            content: String::new(),
-            size: file_size,
+    Ok(files)
+}
+
+// Entries per `listing-batch` event when streaming a directory listing
+const LISTING_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListingBatch {
+    pub operation_id: String,
+    pub path: String,
+    pub entries: Vec<FileEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListingComplete {
+    pub operation_id: String,
+    pub path: String,
+    pub total: usize,
+    pub cancelled: bool,
+}
+
+// List a directory by emitting `listing-batch` events as ls output arrives, then `listing-complete`
+// Entries arrive unsorted; cancel with cancel_operation(operation_id) when navigating away.
+#[tauri::command]
+async fn list_files_streamed(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    operation_id: String,
+) -> Result<usize, String> {
+    let escaped_path = path.replace("'", "'\\''");
+    let shell_command = format!("ls -la '{}'", escaped_path);
+
+    let mut batch: Vec<FileEntry> = Vec::new();
+    let mut total = 0;
+    let emit_batch = |batch: &mut Vec<FileEntry>| {
+        let _ = app.emit("listing-batch", ListingBatch {
+            operation_id: operation_id.clone(),
+            path: path.clone(),
+            entries: std::mem::take(batch),
         });
-    }
+    };
 
-    // Check file size limits
-    if is_text && file_size > MAX_TEXT_SIZE {
-        return Err(format!(
-            "Text file too large to preview ({} MB). Maximum size is 10 MB.",
-            file_size / (1024 * 1024)
-        ));
-    }
+    let result = run_cancellable_shell_lines(&app, &device_id, &operation_id, &shell_command, |line| {
+        if line.starts_with("total") {
+            return;
+        }
+        if let Some(entry) = parse_ls_line(line) {
+            batch.push(entry);
+            total += 1;
+            if batch.len() >= LISTING_BATCH_SIZE {
+                emit_batch(&mut batch);
+            }
+        }
+    })
+    .await;
 
-    if is_image && file_size > MAX_IMAGE_SIZE {
-        return Err(format!(
-            "Image file too large to preview ({} MB). Maximum size is 50 MB.",
-            file_size / (1024 * 1024)
-        ));
+    let cancelled = matches!(&result, Err(e) if e == OPERATION_CANCELLED);
+    if !batch.is_empty() && !cancelled {
+        emit_batch(&mut batch);
     }
+    let _ = app.emit("listing-complete", ListingComplete {
+        operation_id: operation_id.clone(),
+        path: path.clone(),
+        total,
+        cancelled,
+    });
 
-    // Create a temp file to store the pulled file
-    let temp_file = tempfile::NamedTempFile::new()
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-    let temp_path = temp_file.path().to_str()
-        .ok_or("Failed to get temp file path")?;
+    result.map(|_| total)
+}
 
-    // Pull the file from the device
-    let pull_output = shell
-        .command(&adb_cmd)
-        .args(["-s", &device_id, "pull", &device_path, temp_path])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to pull file: {}", e))?;
+// Incremented to stop the running directory watcher; each watcher loop remembers its own generation
+static DIRECTORY_WATCH_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
-    if !pull_output.status.success() {
-        let stderr = String::from_utf8_lossy(&pull_output.stderr);
-        return Err(format!("Failed to pull file: {}", stderr));
+// Shortest polling interval accepted by watch_directory
+const MIN_WATCH_INTERVAL_MS: u64 = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryChanges {
+    pub device_id: String,
+    pub path: String,
+    pub added: Vec<FileEntry>,
+    pub removed: Vec<String>,
+    pub modified: Vec<FileEntry>,
+}
+
+// Compare two listings of the same directory by name; size, date or permission changes count as modified
+fn diff_listings(previous: &[FileEntry], current: &[FileEntry]) -> (Vec<FileEntry>, Vec<String>, Vec<FileEntry>) {
+    let before: HashMap<&str, &FileEntry> = previous.iter().map(|e| (e.name.as_str(), e)).collect();
+    let after: HashSet<&str> = current.iter().map(|e| e.name.as_str()).collect();
+
+    let mut added = Vec::new();
+    let mut modified = Vec::new();
+    for entry in current {
+        match before.get(entry.name.as_str()) {
+            None => added.push(entry.clone()),
+            Some(old) if old.size != entry.size || old.date != entry.date || old.permissions != entry.permissions => {
+                modified.push(entry.clone())
+            }
+            Some(_) => {}
+        }
     }
+    let removed = previous
+        .iter()
+        .filter(|e| !after.contains(e.name.as_str()))
+        .map(|e| e.name.clone())
+        .collect();
 
-    // Read and process the file based on type
-    if is_image {
-        // Read image file and convert to base64
-        let image_bytes = fs::read(temp_path)
-            .map_err(|e| format!("Failed to read image file: {}", e))?;
-        let base64_content = general_purpose::STANDARD.encode(&image_bytes);
+    (added, removed, modified)
+}
 
-        Ok(FilePreview {
-            file_type: "image".to_string(),
-            content: base64_content,
-            size: file_size,
-        })
-    } else {
-        // Read text file
-        let text_content = fs::read_to_string(temp_path)
-            .map_err(|e| format!("Failed to read text file: {}", e))?;
+// Poll a directory every interval_ms and emit `directory-changed` with added/removed/modified entries
+// Only one directory is watched at a time; starting a new watch or calling stop_watching ends the previous one
+#[tauri::command]
+fn watch_directory(app: tauri::AppHandle, device_id: String, path: String, interval_ms: u64) {
+    let generation = DIRECTORY_WATCH_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+    let interval = Duration::from_millis(interval_ms.max(MIN_WATCH_INTERVAL_MS));
+
+    tauri::async_runtime::spawn(async move {
+        let mut previous = list_files(app.clone(), device_id.clone(), path.clone(), None).await.ok();
+        loop {
+            tokio::time::sleep(interval).await;
+            if DIRECTORY_WATCH_GENERATION.load(std::sync::atomic::Ordering::SeqCst) != generation {
+                break;
+            }
 
-        // Limit text content to reasonable size for display
-        // Use char-based truncation to avoid panicking on multi-byte UTF-8 characters
-        let display_content = if text_content.len() > 500_000 {
-            let truncated: String = text_content.chars().take(500_000).collect();
-            format!(
-                "{}\n\n... (truncated, showing first 500KB of {} KB file)",
-                truncated,
-                text_content.len() / 1024
-            )
-        } else {
-            text_content
-        };
-
-        Ok(FilePreview {
-            file_type: "text".to_string(),
-            content: display_content,
-            size: file_size,
-        })
-    }
-}
-
-// ========================
-// Folder Sync Types
-// ========================
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum SyncDirection {
-    PhoneToComputer,
-    ComputerToPhone,
-    BothWays,
+            // Transient listing failures (e.g. device briefly offline) keep the last good snapshot
+            let Ok(current) = list_files(app.clone(), device_id.clone(), path.clone(), None).await else {
+                continue;
+            };
+            if let Some(previous) = &previous {
+                let (added, removed, modified) = diff_listings(previous, &current);
+                if !added.is_empty() || !removed.is_empty() || !modified.is_empty() {
+                    let _ = app.emit("directory-changed", DirectoryChanges {
+                        device_id: device_id.clone(),
+                        path: path.clone(),
+                        added,
+                        removed,
+                        modified,
+                    });
+                }
+            }
+            previous = Some(current);
+        }
+    });
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SyncOptions {
-    pub local_path: String,
-    pub device_path: String,
-    pub direction: SyncDirection,
-    pub recursive: bool,
-    pub delete_missing: bool,
-    pub match_mode: String,
-    #[serde(default)]
-    pub file_patterns: Vec<String>,
+// Stop the directory watcher started by watch_directory
+#[tauri::command]
+fn stop_watching() {
+    DIRECTORY_WATCH_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct FileMetadata {
-    pub relative_path: String,
-    pub size: u64,
-    pub modified_time: u64,
-    pub is_directory: bool,
-    pub md5_hash: Option<String>,
-}
+// Group entries by kind: directories first (by name), then files by extension and name
+// Used for the "type" sort, matching how desktop file managers group by kind
+fn compare_by_type(a: &FileEntry, b: &FileEntry) -> std::cmp::Ordering {
+    let extension_key = |f: &FileEntry| f.extension.as_deref().unwrap_or("").to_lowercase();
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SyncAction {
-    pub file_path: String,
-    pub action_type: String,
-    pub direction: String,
-    pub size: u64,
-    pub reason: String,
-    pub rename_from: Option<String>,
+    b.is_directory
+        .cmp(&a.is_directory)
+        .then_with(|| {
+            if a.is_directory {
+                std::cmp::Ordering::Equal
+            } else {
+                extension_key(a).cmp(&extension_key(b))
+            }
+        })
+        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SyncPreview {
-    pub actions: Vec<SyncAction>,
-    pub total_transfer_bytes: u64,
-    pub copy_count: u32,
-    pub update_count: u32,
-    pub delete_count: u32,
-    pub skip_count: u32,
-    pub rename_count: u32,
+pub struct ResolvedListing {
+    pub display_path: String,
+    pub real_path: String,
+    pub files: Vec<FileEntry>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SyncProgress {
-    pub current_file: String,
-    pub completed_count: u32,
-    pub total_count: u32,
-    pub completed_bytes: u64,
-    pub total_bytes: u64,
-}
+// Resolve symlinks in a device path with `readlink -f`, returning the input if that fails
+async fn resolve_device_path(app: &tauri::AppHandle, device_id: &str, path: &str) -> String {
+    let escaped_path = path.replace("'", "'\\''");
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &format!("readlink -f '{}'", escaped_path)])
+        .output()
+        .await
+        .ok();
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SyncResult {
-    pub success_count: u32,
-    pub skip_count: u32,
-    pub error_count: u32,
-    pub errors: Vec<String>,
+    output
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|resolved| resolved.starts_with('/'))
+        .unwrap_or_else(|| path.to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SavedSync {
-    pub id: String,
-    pub name: String,
-    pub options: SyncOptions,
+// Canonical absolute path for any entry (symlinks and `..` resolved), or the input if that fails
+// Lets the UI keep breadcrumbs accurate after entering a symlinked directory
+#[tauri::command]
+async fn resolve_real_path(app: tauri::AppHandle, device_id: String, path: String) -> Result<String, String> {
+    Ok(resolve_device_path(&app, &device_id, &path).await)
 }
 
-fn saved_syncs_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let data_dir = app.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
-    Ok(data_dir.join("saved_syncs.json"))
+// Extract the `_data` column from `content query` output, e.g. "Row: 0 _data=/storage/emulated/0/DCIM/a.jpg"
+fn parse_content_query_data(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.split_once("_data=").map(|(_, path)| path.trim().to_string()))
+        .filter(|path| path.starts_with('/'))
 }
 
-fn read_saved_syncs_file(app: &tauri::AppHandle) -> Result<Vec<SavedSync>, String> {
-    let path = saved_syncs_path(app)?;
-    if !path.exists() {
-        return Ok(vec![]);
+// Resolve a content:// URI (e.g. from a share sheet or log) to its filesystem path via MediaStore's _data column
+#[tauri::command]
+async fn resolve_content_uri(app: tauri::AppHandle, device_id: String, uri: String) -> Result<String, String> {
+    if !uri.starts_with("content://") {
+        return Err(format!("Not a content URI: {}", uri));
     }
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read saved syncs: {}", e))?;
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse saved syncs: {}", e))
-}
 
-fn write_saved_syncs_file(app: &tauri::AppHandle, syncs: &[SavedSync]) -> Result<(), String> {
-    let path = saved_syncs_path(app)?;
-    let content = serde_json::to_string_pretty(syncs)
-        .map_err(|e| format!("Failed to serialize saved syncs: {}", e))?;
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write saved syncs: {}", e))
+    let escaped_uri = uri.replace("'", "'\\''");
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &format!("content query --uri '{}' --projection _data", escaped_uri)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to query content URI: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Some(path) = parse_content_query_data(&stdout) {
+        return Ok(path);
+    }
+
+    let detail = if stdout.trim().is_empty() || stdout.contains("No result found") {
+        "no matching row".to_string()
+    } else if stdout.contains("_data") || stdout.contains("Error") || stdout.contains("Exception") {
+        stdout.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim().to_string()
+    } else {
+        "the provider has no _data column".to_string()
+    };
+    Err(format!("Could not resolve {}: {}", uri, detail))
 }
 
+// List a directory, following it first if it is a symlink (e.g. /sdcard)
+// Returns both the path the user navigated to and the real path that was listed
 #[tauri::command]
-async fn list_saved_syncs(app: tauri::AppHandle) -> Result<Vec<SavedSync>, String> {
-    read_saved_syncs_file(&app)
+async fn list_files_resolved(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+) -> Result<ResolvedListing, String> {
+    let real_path = resolve_device_path(&app, &device_id, &path).await;
+    let files = list_files(app, device_id, real_path.clone(), None).await?;
+
+    Ok(ResolvedListing {
+        display_path: path,
+        real_path,
+        files,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenedDirectory {
+    pub files: Vec<FileEntry>,
+    // Media file name -> whether a thumbnail of the requested size is already cached
+    pub thumbnail_cached: HashMap<String, bool>,
 }
 
+// List a directory (directories first, then by name) and report which media files already have thumbnails
+// Lets the grid show cached icons immediately and prefetch only the missing ones
 #[tauri::command]
-async fn save_sync_config(
+async fn open_directory(
     app: tauri::AppHandle,
-    id: Option<String>,
-    name: String,
-    options: SyncOptions,
-) -> Result<SavedSync, String> {
-    let trimmed_name = name.trim().to_string();
-    if trimmed_name.is_empty() {
-        return Err("Sync name cannot be empty".to_string());
-    }
-    let mut syncs = read_saved_syncs_file(&app)?;
-    static ID_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-    let saved_id = id.unwrap_or_else(|| {
-        let ms = std::time::SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis();
-        let seq = ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        format!("{}-{}", ms, seq)
+    device_id: String,
+    path: String,
+    thumb_size: u32,
+) -> Result<OpenedDirectory, String> {
+    let mut files = list_files(app, device_id.clone(), path.clone(), None).await?;
+    files.sort_by(|a, b| {
+        b.is_directory
+            .cmp(&a.is_directory)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
     });
-    let saved = SavedSync {
-        id: saved_id.clone(),
-        name: trimmed_name,
-        options,
-    };
-    if let Some(pos) = syncs.iter().position(|s| s.id == saved_id) {
-        syncs[pos] = saved.clone();
-    } else {
-        syncs.push(saved.clone());
-    }
-    write_saved_syncs_file(&app, &syncs)?;
-    Ok(saved)
+
+    // Cached thumbnails are 256px and can be scaled down, but not up
+    let cache_dir = thumbnail_cache_dir();
+    let base = path.trim_end_matches('/');
+    let thumbnail_cached = files
+        .iter()
+        .filter(|f| {
+            f.extension
+                .as_deref()
+                .is_some_and(|ext| is_image_extension(ext) || is_video_extension(ext))
+        })
+        .map(|f| {
+            let file_path = format!("{}/{}", base, f.name);
+            migrate_cached_thumbnail(&device_id, &file_path);
+            let cached = thumb_size <= 256
+                && cache_dir
+                    .join(format!("thumb_{}.png", thumbnail_cache_key(&device_id, &file_path)))
+                    .exists();
+            (f.name.clone(), cached)
+        })
+        .collect();
+
+    Ok(OpenedDirectory { files, thumbnail_cached })
 }
 
-#[tauri::command]
-async fn delete_saved_sync(app: tauri::AppHandle, id: String) -> Result<(), String> {
-    let mut syncs = read_saved_syncs_file(&app)?;
-    syncs.retain(|s| s.id != id);
-    write_saved_syncs_file(&app, &syncs)
+// True when an ls error means the flags aren't supported (rather than e.g. a missing path)
+fn is_invalid_option_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    ["invalid option", "unknown option", "illegal option", "unrecognized option"]
+        .iter()
+        .any(|message| stderr.contains(message))
 }
 
-fn compute_local_md5(path: &std::path::Path) -> Option<String> {
-    use std::io::Read;
-    let mut file = std::fs::File::open(path).ok()?;
-    let mut context = md5::Context::new();
-    let mut buffer = [0u8; 8192];
-    loop {
-        let bytes_read = file.read(&mut buffer).ok()?;
-        if bytes_read == 0 {
-            break;
-        }
-        context.consume(&buffer[..bytes_read]);
+// Build an entry from plain `ls` output, where only the name is known
+// Directories are recognised only when the shell's ls marks them with a trailing '/'
+fn name_only_file_entry(line: &str) -> Option<FileEntry> {
+    let name = line.trim_end_matches(['\r', '\n']);
+    let is_directory = name.ends_with('/');
+    let name = name.trim_end_matches('/');
+    if name.is_empty() || name == "." || name == ".." {
+        return None;
     }
-    Some(format!("{:x}", context.compute()))
+
+    let extension = if is_directory {
+        None
+    } else {
+        name.rsplit_once('.')
+            .map(|(_, ext)| ext)
+            .filter(|ext| !ext.is_empty() && ext.len() <= 10)
+            .map(|ext| ext.to_lowercase())
+    };
+    let mime_type = extension
+        .as_deref()
+        .and_then(|ext| mime_guess::from_ext(ext).first_raw())
+        .map(|mime| mime.to_string());
+
+    Some(FileEntry {
+        name: name.to_string(),
+        permissions: String::new(),
+        size: "0".to_string(),
+        date: String::new(),
+        is_directory,
+        extension,
+        modified_epoch: None,
+        mime_type,
+        permissions_octal: None,
+        is_symlink: false,
+        symlink_target: None,
+    })
 }
 
-/// Returns true if a file/directory should be excluded from sync operations.
-fn is_sync_excluded(name: &str) -> bool {
-    // macOS AppleDouble resource fork files (e.g. "._IMG001.jpg")
-    if name.starts_with("._") {
-        return true;
+// Size from the columns between permissions and date, which vary by ls implementation:
+// toolbox prints "owner group [size]" (no size for directories), toybox/busybox "links owner group size"
+// Returns None when there is no size column (or it can't be told apart, e.g. device nodes' "major, minor")
+fn ls_size_column<'a>(columns: &[&'a str]) -> Option<&'a str> {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match *columns {
+        [_links, _owner, _group, size] if is_number(size) => Some(size),
+        [links, _owner, group] if is_number(links) && !is_number(group) => None,
+        [_owner, _group, size] if is_number(size) => Some(size),
+        _ => None,
     }
-    matches!(
-        name,
-        ".DS_Store"
-            | ".Spotlight-V100"
-            | ".Trashes"
-            | ".fseventsd"
-            | ".TemporaryItems"
-            | "Thumbs.db"
-            | "desktop.ini"
-            | ".thumbnails"
-            | ".thumbdata3"
-            | ".thumbdata4"
-            | ".nomedia"
-    )
 }
 
-/// Normalize file patterns: strip device_path prefix and append /**/* for bare directories.
-fn normalize_patterns(patterns: &[String], device_path: &str) -> Vec<String> {
-    if patterns.is_empty() {
-        return Vec::new();
+// Parse a single line of ls -la output
+// Android's ls -la format: permissions [links] owner group [size] date time name
+// Example: drwxrwx--- root sdcard_rw 2025-02-01 06:31 .NightPearl
+fn parse_ls_line(line: &str) -> Option<FileEntry> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 6 {
+        return None;
     }
-    let prefix = device_path.trim_end_matches('/');
-    patterns.iter().map(|p| {
-        // Strip device path prefix if present (user may paste absolute paths)
-        let stripped = if p.starts_with(prefix) {
-            p[prefix.len()..].trim_start_matches('/').to_string()
-        } else {
-            p.trim_start_matches('/').to_string()
-        };
-        // Strip trailing slash
-        let stripped = stripped.trim_end_matches('/').to_string();
-        // If pattern has no glob characters, treat as directory: append /**/*
-        if !stripped.contains('*') && !stripped.contains('?') && !stripped.contains('[') {
-            format!("{}/**/*", stripped)
-        } else {
-            stripped
-        }
-    }).collect()
-}
 
-fn matches_any_pattern(rel_path: &str, patterns: &[String]) -> bool {
-    if patterns.is_empty() {
-        return true;
+    let permissions = parts[0].to_string();
+    let is_directory = permissions.starts_with('d');
+
+    // Find the time field (contains ':'); it follows at least owner, group and date
+    let time_idx = parts.iter().skip(4).position(|p| p.contains(':'))? + 4;
+
+    // Name is everything after the time
+    if time_idx + 1 >= parts.len() {
+        return None;
     }
-    let options = glob::MatchOptions {
-        case_sensitive: true,
-        require_literal_separator: false,
-        require_literal_leading_dot: false,
+
+    // Symlinks are listed as "name -> target"
+    let is_symlink = permissions.starts_with('l');
+    let (name, symlink_target) = if is_symlink {
+        split_symlink_name(&parts[time_idx + 1..].join(" "))
+    } else {
+        (parts[time_idx + 1..].join(" "), None)
     };
-    patterns.iter().any(|p| {
-        glob::Pattern::new(p)
-            .map(|pat| pat.matches_with(rel_path, options))
-            .unwrap_or(false)
-    })
-}
 
-// List files on the local Mac filesystem for sync
-#[tauri::command]
-fn list_local_files(path: String, recursive: bool, match_mode: String, file_patterns: Vec<String>) -> Result<Vec<FileMetadata>, String> {
-    let root = PathBuf::from(&path);
-    if !root.exists() {
-        return Err(format!("Local path does not exist: {}", path));
+    // Skip . and .. entries
+    if name == "." || name == ".." || name.is_empty() {
+        return None;
     }
 
-    let mut result = Vec::new();
-
-    if recursive {
-        for entry in walkdir::WalkDir::new(&root)
-            .min_depth(1)
-            .into_iter()
-            .filter_entry(|e| {
-                let name = e.file_name().to_str().unwrap_or("");
-                !is_sync_excluded(name)
-            })
-            .filter_map(|e| e.ok())
-        {
-            let rel_path = entry.path().strip_prefix(&root)
-                .map_err(|e| format!("Path error: {}", e))?
-                .to_string_lossy()
-                .to_string();
-
-            if rel_path.is_empty() {
-                continue;
-            }
+    // Date is one position before time
+    let date_part = parts[time_idx - 1];
 
-            let metadata = entry.metadata()
-                .map_err(|e| format!("Failed to read metadata for {}: {}", rel_path, e))?;
+    let time_part = parts[time_idx];
+    let date = format!("{} {}", date_part, time_part);
+    let modified_epoch = parse_ls_datetime(date_part, time_part);
 
-            // Skip directories when filtering by patterns (only match files)
-            if !metadata.is_dir() && !matches_any_pattern(&rel_path, &file_patterns) {
-                continue;
-            }
+    let size = ls_size_column(&parts[1..time_idx - 1]).unwrap_or("0").to_string();
 
-            let modified_time = metadata.modified()
-                .unwrap_or(UNIX_EPOCH)
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+    // Extract file extension
+    let extension = if !is_directory {
+        name.rsplit('.').next()
+            .filter(|ext| ext.len() <= 10 && ext.len() > 0 && ext != &name)
+            .map(|ext| ext.to_lowercase())
+    } else {
+        None
+    };
 
-            let md5_hash = if match_mode == "content" && !metadata.is_dir() {
-                compute_local_md5(entry.path())
-            } else {
-                None
-            };
+    let permissions_octal = permissions_to_octal(&permissions);
 
-            result.push(FileMetadata {
-                relative_path: rel_path,
-                size: metadata.len(),
-                modified_time,
-                is_directory: metadata.is_dir(),
-                md5_hash,
-            });
-        }
-    } else {
-        let entries = fs::read_dir(&root)
-            .map_err(|e| format!("Failed to read directory {}: {}", path, e))?;
+    // Guess the MIME type from the extension (cheap, no device round trip)
+    let mime_type = extension
+        .as_deref()
+        .and_then(|ext| mime_guess::from_ext(ext).first_raw())
+        .map(|mime| mime.to_string());
 
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-            let metadata = entry.metadata()
-                .map_err(|e| format!("Failed to read metadata: {}", e))?;
-            let name = entry.file_name().to_string_lossy().to_string();
+    Some(FileEntry {
+        name,
+        permissions,
+        size,
+        date,
+        is_directory,
+        extension,
+        modified_epoch,
+        mime_type,
+        permissions_octal,
+        is_symlink,
+        symlink_target,
+    })
+}
 
-            if is_sync_excluded(&name) {
-                continue;
-            }
+// Convert a symbolic mode like "drwxr-sr-x" to octal ("2755"), including setuid/setgid/sticky bits
+// Returns None if the string isn't a 10-character ls mode
+fn permissions_to_octal(permissions: &str) -> Option<String> {
+    let chars: Vec<char> = permissions.chars().collect();
+    if chars.len() < 10 {
+        return None;
+    }
 
-            if !metadata.is_dir() && !matches_any_pattern(&name, &file_patterns) {
-                continue;
+    let mut special = 0;
+    let mut digits = String::new();
+    // (special bit, character marking it when execute is set / not set) for user, group, other
+    let special_bits = [(4, 's', 'S'), (2, 's', 'S'), (1, 't', 'T')];
+    for (i, (bit, with_exec, without_exec)) in special_bits.into_iter().enumerate() {
+        let triad = &chars[1 + i * 3..4 + i * 3];
+        let mut value = 0;
+        match triad[0] {
+            'r' => value += 4,
+            '-' => {}
+            _ => return None,
+        }
+        match triad[1] {
+            'w' => value += 2,
+            '-' => {}
+            _ => return None,
+        }
+        match triad[2] {
+            'x' => value += 1,
+            '-' => {}
+            c if c == with_exec => {
+                value += 1;
+                special += bit;
             }
+            c if c == without_exec => special += bit,
+            _ => return None,
+        }
+        digits.push(char::from(b'0' + value));
+    }
 
-            let modified_time = metadata.modified()
-                .unwrap_or(UNIX_EPOCH)
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+    Some(if special > 0 { format!("{}{}", special, digits) } else { digits })
+}
 
-            let md5_hash = if match_mode == "content" && !metadata.is_dir() {
-                compute_local_md5(&root.join(&name))
-            } else {
-                None
-            };
+// Convert an ls "YYYY-MM-DD HH:MM" timestamp to seconds since the epoch
+// ls prints device-local time, so the result is only meant for ordering entries
+fn parse_ls_datetime(date: &str, time: &str) -> Option<u64> {
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>());
+    let (year, month, day) = match (date_parts.next(), date_parts.next(), date_parts.next()) {
+        (Some(Ok(y)), Some(Ok(m)), Some(Ok(d))) if (1..=12).contains(&m) && (1..=31).contains(&d) => (y, m, d),
+        _ => return None,
+    };
 
-            result.push(FileMetadata {
-                relative_path: name,
-                size: metadata.len(),
-                modified_time,
-                is_directory: metadata.is_dir(),
-                md5_hash,
-            });
-        }
-    }
+    let mut time_parts = time.splitn(3, ':').map(|p| p.parse::<i64>());
+    let (hour, minute) = match (time_parts.next(), time_parts.next()) {
+        (Some(Ok(h)), Some(Ok(m))) if h < 24 && m < 60 => (h, m),
+        _ => return None,
+    };
 
-    Ok(result)
+    // Days since 1970-01-01 for a proleptic Gregorian date
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60;
+    u64::try_from(secs).ok()
 }
 
-// List files on the Android device for sync
+// Detect the primary storage path for an Android device
 #[tauri::command]
-async fn list_device_files_for_sync(
-    app: tauri::AppHandle,
-    device_id: String,
-    path: String,
-    recursive: bool,
-    match_mode: String,
-    file_patterns: Vec<String>,
-) -> Result<Vec<FileMetadata>, String> {
+async fn detect_storage_path(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
     let shell = app.shell();
     let adb_cmd = get_adb_command();
-    let escaped_path = path.replace("'", "'\\''");
-
-    let mut result = Vec::new();
-
-    if recursive {
-        // When patterns are provided, only scan targeted directories instead of the entire root
-        let scan_dirs: Vec<String> = if !file_patterns.is_empty() {
-            // Extract directory prefixes from patterns (everything before the first glob character)
-            let mut dirs: Vec<String> = file_patterns.iter().map(|p| {
-                // Find the directory portion before any glob characters
-                let glob_pos = p.find(|c: char| c == '*' || c == '?' || c == '[')
-                    .unwrap_or(p.len());
-                let dir_part = &p[..glob_pos];
-                // Trim to last slash to get the directory
-                let dir = if let Some(slash_pos) = dir_part.rfind('/') {
-                    &dir_part[..slash_pos]
-                } else {
-                    ""
-                };
-                if dir.is_empty() {
-                    path.clone()
-                } else {
-                    format!("{}/{}", path, dir)
-                }
-            }).collect();
-            dirs.sort();
-            dirs.dedup();
-            // Remove directories that are subdirectories of other entries
-            let mut filtered: Vec<String> = Vec::new();
-            for dir in &dirs {
-                if !filtered.iter().any(|parent| dir.starts_with(&format!("{}/", parent))) {
-                    filtered.push(dir.clone());
-                }
-            }
-            filtered
-        } else {
-            vec![path.clone()]
-        };
 
-        #[cfg(debug_assertions)]
-        {
-            eprintln!("[sync] scan_dirs: {:?}", scan_dirs);
-            eprintln!("[sync] file_patterns for filtering: {:?}", file_patterns);
-        }
-
-        // Use find + stat in one command per scan_dir. Each output line is self-contained
-        // using '|' as delimiter: size|mtime|type|full_path
-        // This avoids the fragile batch-stat approach where one failed stat misaligns all subsequent entries.
-        for scan_dir in &scan_dirs {
-            let escaped_scan_dir = scan_dir.replace("'", "'\\''");
-            let find_stat_command = format!(
-                "find '{}' -mindepth 1 \\( -type f -o -type d \\) -exec stat -c '%s|%Y|%F|%n' {{}} + 2>/dev/null",
-                escaped_scan_dir
-            );
+    // Try 1: Get EXTERNAL_STORAGE environment variable
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", "echo $EXTERNAL_STORAGE"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
 
-            let output = shell
+    if output.status.success() {
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !path.is_empty() && path != "$EXTERNAL_STORAGE" {
+            // Verify the path exists
+            let verify_output = shell
                 .command(&adb_cmd)
-                .args(["-s", &device_id, "shell", &find_stat_command])
+                .args(["-s", &device_id, "shell", &format!("test -d '{}' && echo exists", path)])
                 .output()
                 .await
-                .map_err(|e| format!("Failed to list device files: {}", e))?;
+                .ok();
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(verify) = verify_output {
+                if String::from_utf8_lossy(&verify.stdout).contains("exists") {
+                    // Resolve symlink to get actual path
+                    let resolve_output = shell
+                        .command(&adb_cmd)
+                        .args(["-s", &device_id, "shell", &format!("readlink -f '{}'", path)])
+                        .output()
+                        .await
+                        .ok();
 
-            #[cfg(debug_assertions)]
-            eprintln!("[sync] find+stat returned {} lines for {}", stdout.lines().count(), scan_dir);
+                    if let Some(resolved) = resolve_output {
+                        let resolved_path = String::from_utf8_lossy(&resolved.stdout).trim().to_string();
+                        if !resolved_path.is_empty() {
+                            return Ok(resolved_path);
+                        }
+                    }
 
-            for line in stdout.lines() {
-                let line = line.trim();
-                if line.is_empty() {
-                    continue;
+                    // If readlink fails, use path as-is
+                    return Ok(path);
                 }
+            }
+        }
+    }
 
-                // Parse: size|mtime|type|full_path
-                let parts: Vec<&str> = line.splitn(4, '|').collect();
-                if parts.len() < 4 {
-                    continue;
-                }
+    // Try 2: Check common symlinks (/sdcard usually points to the right place)
+    let sdcard_paths = vec!["/sdcard", "/mnt/sdcard", "/storage/self/primary", "/mnt/user/0/primary"];
 
-                let size = parts[0].parse::<u64>().unwrap_or(0);
-                let mtime = parts[1].parse::<u64>().unwrap_or(0);
-                let is_dir = parts[2].contains("directory");
-                let file_path = parts[3];
+    for sdcard_path in sdcard_paths {
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &format!("test -d '{}' && echo exists", sdcard_path)])
+            .output()
+            .await
+            .ok();
 
-                // Compute relative path
-                let rel_path = if file_path.starts_with(&path) {
-                    file_path[path.len()..].trim_start_matches('/').to_string()
-                } else {
-                    file_path.to_string()
-                };
+        if let Some(verify) = output {
+            if String::from_utf8_lossy(&verify.stdout).contains("exists") {
+                // Resolve symlink to get actual path
+                let resolve_output = shell
+                    .command(&adb_cmd)
+                    .args(["-s", &device_id, "shell", &format!("readlink -f '{}'", sdcard_path)])
+                    .output()
+                    .await
+                    .ok();
 
-                if rel_path.is_empty() {
-                    continue;
+                if let Some(resolved) = resolve_output {
+                    let resolved_path = String::from_utf8_lossy(&resolved.stdout).trim().to_string();
+                    if !resolved_path.is_empty() {
+                        return Ok(resolved_path);
+                    }
                 }
 
-                // Skip if any path component is excluded
-                if rel_path.split('/').any(|part| is_sync_excluded(part)) {
-                    continue;
-                }
+                // If readlink fails, just use the path as-is
+                return Ok(sdcard_path.to_string());
+            }
+        }
+    }
 
-                // Apply glob pattern filtering (skip directories from filtering)
-                if !is_dir && !matches_any_pattern(&rel_path, &file_patterns) {
-                    continue;
-                }
+    // Try 3: Default to /storage/emulated/0 (most common path)
+    Ok("/storage/emulated/0".to_string())
+}
 
-                let md5_hash = if match_mode == "content" && !is_dir {
-                    let escaped_file = file_path.replace("'", "'\\''");
-                    let md5_cmd = format!("md5sum '{}' 2>/dev/null", escaped_file);
-                    let md5_output = shell
-                        .command(&adb_cmd)
-                        .args(["-s", &device_id, "shell", &md5_cmd])
-                        .output()
-                        .await
-                        .ok();
-                    md5_output.and_then(|o| {
-                        let out = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                        out.split_whitespace().next().map(|s| s.to_string())
-                    })
-                } else {
-                    None
-                };
+// Well-known media folders relative to primary storage, with their sidebar labels
+const STANDARD_FOLDERS: &[(&str, &str)] = &[
+    ("DCIM", "Camera"),
+    ("Download", "Downloads"),
+    ("Pictures", "Pictures"),
+    ("Movies", "Movies"),
+    ("Music", "Music"),
+    ("Documents", "Documents"),
+    ("WhatsApp/Media", "WhatsApp Media"),
+];
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct StandardFolder {
+    pub label: String,
+    pub path: String,
+    pub item_count: u64,
+}
 
-                result.push(FileMetadata {
-                    relative_path: rel_path,
-                    size,
-                    modified_time: mtime,
-                    is_directory: is_dir,
-                    md5_hash,
-                });
-            }
-        }
-        // Deduplicate results from overlapping scan_dirs
-        result.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
-        result.dedup_by(|a, b| a.relative_path == b.relative_path);
-    } else {
-        // Flat listing: use ls -la and stat for mtime
-        let ls_command = format!("ls -la '{}'", escaped_path);
+// Parse "relative|count" lines printed for each standard folder that exists
+fn parse_standard_folders(output: &str, storage_path: &str) -> Vec<StandardFolder> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (relative, count) = line.trim().rsplit_once('|')?;
+            let (_, label) = STANDARD_FOLDERS.iter().find(|(folder, _)| *folder == relative)?;
+            Some(StandardFolder {
+                label: label.to_string(),
+                path: format!("{}/{}", storage_path.trim_end_matches('/'), relative),
+                item_count: count.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
 
-        let output = shell
-            .command(&adb_cmd)
-            .args(["-s", &device_id, "shell", &ls_command])
-            .output()
-            .await
-            .map_err(|e| format!("Failed to list device files: {}", e))?;
+// List the well-known folders (DCIM, Download, ...) that exist on the device, with item counts
+// Powers the sidebar quick-access shortcuts
+#[tauri::command]
+async fn get_standard_folders(app: tauri::AppHandle, device_id: String) -> Result<Vec<StandardFolder>, String> {
+    let storage_path = detect_storage_path(app.clone(), device_id.clone()).await?;
+    let escaped_root = storage_path.trim_end_matches('/').replace("'", "'\\''");
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let entries: Vec<FileEntry> = stdout
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .filter(|line| !line.starts_with("total"))
-            .filter_map(|line| parse_ls_line(line))
-            .collect();
+    // Check every folder in a single shell round trip
+    let check_command = STANDARD_FOLDERS
+        .iter()
+        .map(|(folder, _)| {
+            format!(
+                "[ -d '{root}/{folder}' ] && echo \"{folder}|$(ls -A '{root}/{folder}' | wc -l)\"",
+                root = escaped_root,
+                folder = folder
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
 
-        for entry in &entries {
-            if is_sync_excluded(&entry.name) {
-                continue;
-            }
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &check_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check standard folders: {}", e))?;
 
-            if !entry.is_directory && !matches_any_pattern(&entry.name, &file_patterns) {
-                continue;
-            }
+    Ok(parse_standard_folders(&String::from_utf8_lossy(&output.stdout), &storage_path))
+}
 
-            let file_full_path = format!("{}/{}", path, entry.name);
-            let escaped_file = file_full_path.replace("'", "'\\''");
-            let stat_cmd = format!("stat -c '%s %Y' '{}' 2>/dev/null", escaped_file);
+// List browsing roots: the whole filesystem (`/`) when show_system is set,
+// otherwise only user-accessible storage volumes (primary storage and SD cards/USB drives)
+// Volume entries are named by their full path
+#[tauri::command]
+async fn list_root_entries(
+    app: tauri::AppHandle,
+    device_id: String,
+    show_system: bool,
+) -> Result<Vec<FileEntry>, String> {
+    if show_system {
+        return Ok(list_files(app, device_id, "/".to_string(), None).await?);
+    }
 
-            let stat_output = shell
-                .command(&adb_cmd)
-                .args(["-s", &device_id, "shell", &stat_cmd])
-                .output()
-                .await
-                .ok();
+    let primary = detect_storage_path(app.clone(), device_id.clone()).await?;
 
-            let (size, mtime) = if let Some(stat_out) = stat_output {
-                let stat_str = String::from_utf8_lossy(&stat_out.stdout).trim().to_string();
-                let parts: Vec<&str> = stat_str.split(' ').collect();
-                if parts.len() >= 2 {
-                    (
-                        parts[0].parse::<u64>().unwrap_or(0),
-                        parts[1].parse::<u64>().unwrap_or(0),
-                    )
-                } else {
-                    (entry.size.parse::<u64>().unwrap_or(0), 0)
-                }
-            } else {
-                (entry.size.parse::<u64>().unwrap_or(0), 0)
-            };
+    // Removable volumes appear under /storage by UUID (e.g. /storage/1234-ABCD)
+    let list_command = "for v in /storage/*; do \
+                        case \"$v\" in /storage/emulated|/storage/self) ;; *) [ -d \"$v\" ] && ls -ld \"$v\";; esac; \
+                        done";
+    let escaped_primary = primary.replace("'", "'\\''");
+    let shell_command = format!("ls -ld '{}'; {}", escaped_primary, list_command);
 
-            let md5_hash = if match_mode == "content" && !entry.is_directory {
-                let md5_cmd = format!("md5sum '{}' 2>/dev/null", escaped_file);
-                let md5_output = shell
-                    .command(&adb_cmd)
-                    .args(["-s", &device_id, "shell", &md5_cmd])
-                    .output()
-                    .await
-                    .ok();
-                md5_output.and_then(|o| {
-                    let out = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                    out.split_whitespace().next().map(|s| s.to_string())
-                })
-            } else {
-                None
-            };
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &shell_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list storage volumes: {}", e))?;
 
-            result.push(FileMetadata {
-                relative_path: entry.name.clone(),
-                size,
-                modified_time: mtime,
-                is_directory: entry.is_directory,
-                md5_hash,
-            });
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .collect())
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct StorageVolume {
+    pub name: String,
+    pub path: String,
+    pub is_otg: bool,
+    // false when the volume is present but can't be listed (e.g. not yet mounted or adopted)
+    pub accessible: bool,
+}
+
+// Parse "ok|<path>" / "denied|<path>" lines for removable volumes under /storage and /mnt/media_rw
+// A volume seen in both places is reported once, preferring the /storage path.
+// USB drives are recognised by their vold block device: major 8 (SCSI/USB mass storage) rather than 179 (MMC/SD).
+fn parse_storage_volumes(output: &str, mounts: &[MountInfo]) -> Vec<StorageVolume> {
+    let mut volumes: Vec<StorageVolume> = Vec::new();
+    for line in output.lines() {
+        let Some((status, path)) = line.trim().split_once('|') else { continue };
+        let Some(name) = path.rsplit('/').next().filter(|name| !name.is_empty()) else { continue };
+        if matches!(name, "emulated" | "self" | "*") {
+            continue;
+        }
+
+        let is_otg = mounts.iter().any(|m| {
+            (m.mount_point == format!("/mnt/media_rw/{}", name) || m.mount_point == format!("/storage/{}", name))
+                && m.device.contains("public:8,")
+        });
+        let volume = StorageVolume {
+            name: name.to_string(),
+            path: path.to_string(),
+            is_otg,
+            accessible: status == "ok",
+        };
+
+        match volumes.iter_mut().find(|v| v.name == volume.name) {
+            Some(existing) => {
+                if !existing.path.starts_with("/storage/") || (!existing.accessible && volume.accessible) {
+                    *existing = volume;
+                }
+            }
+            None => volumes.push(volume),
         }
     }
+    volumes
+}
 
-    Ok(result)
+// List removable storage (SD cards and USB-OTG drives), including volumes that exist but aren't accessible
+#[tauri::command]
+async fn list_storage_volumes(app: tauri::AppHandle, device_id: String) -> Result<Vec<StorageVolume>, String> {
+    let probe_command = "for v in /storage/* /mnt/media_rw/*; do [ -e \"$v\" ] || continue; \
+                         if [ -d \"$v\" ] && ls \"$v\" >/dev/null 2>&1; then echo \"ok|$v\"; else echo \"denied|$v\"; fi; \
+                         done";
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", probe_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list storage volumes: {}", e))?;
+
+    let mounts = get_mount_info(app.clone(), device_id.clone()).await.unwrap_or_default();
+    Ok(parse_storage_volumes(&String::from_utf8_lossy(&output.stdout), &mounts))
 }
 
-// Compute sync actions from two file lists
-fn compute_sync_actions(
-    local_files: &[FileMetadata],
-    device_files: &[FileMetadata],
-    direction: &SyncDirection,
-    delete_missing: bool,
-    match_mode: &str,
-) -> Vec<SyncAction> {
-    if match_mode == "content" {
-        return compute_sync_actions_by_content(local_files, device_files, direction, delete_missing);
+// Check whether a path on the device is writable by the adb shell user
+#[tauri::command]
+async fn is_path_writable(app: tauri::AppHandle, device_id: String, path: String) -> Result<bool, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let escaped_path = path.replace("'", "'\\''");
+    let test_command = format!("test -w '{}' && echo writable", escaped_path);
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &test_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).contains("writable"))
+}
+
+// Check if ADB is available
+#[tauri::command]
+async fn check_adb(app: tauri::AppHandle) -> Result<bool, AdbError> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["version"])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    Ok(output.status.success())
+}
+
+// Set custom ADB path
+#[tauri::command]
+fn set_adb_path(path: String) -> Result<(), String> {
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        return Err("ADB executable not found at specified path".to_string());
     }
 
-    let local_map: HashMap<&str, &FileMetadata> = local_files
-        .iter()
-        .filter(|f| !f.is_directory)
-        .map(|f| (f.relative_path.as_str(), f))
-        .collect();
+    if let Ok(mut guard) = ADB_PATH.lock() {
+        *guard = Some(path);
+        Ok(())
+    } else {
+        Err("Failed to set ADB path".to_string())
+    }
+}
 
-    let device_map: HashMap<&str, &FileMetadata> = device_files
-        .iter()
-        .filter(|f| !f.is_directory)
-        .map(|f| (f.relative_path.as_str(), f))
-        .collect();
+// Get current ADB path (for display purposes)
+#[tauri::command]
+fn get_current_adb_path() -> String {
+    get_adb_command()
+}
 
-    let mut actions = Vec::new();
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub adb_found: bool,
+    pub adb_path: String,
+    pub adb_version: Option<String>,
+    pub devices: Vec<AdbDevice>,
+    pub ffmpeg_found: bool,
+}
 
-    match direction {
-        SyncDirection::PhoneToComputer => {
-            // Source = device, Dest = local
-            for (rel_path, dev_file) in &device_map {
-                if let Some(local_file) = local_map.get(rel_path) {
-                    if dev_file.size != local_file.size || dev_file.modified_time > local_file.modified_time {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "update".to_string(),
-                            direction: "\u{2192} Computer".to_string(),
-                            size: dev_file.size,
-                            reason: "Device file is newer or different size".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                } else {
-                    actions.push(SyncAction {
-                        file_path: rel_path.to_string(),
-                        action_type: "copy".to_string(),
-                        direction: "\u{2192} Computer".to_string(),
-                        size: dev_file.size,
-                        reason: "File only exists on device".to_string(),
-                        rename_from: None,
-                    });
-                }
-            }
-            if delete_missing {
-                for (rel_path, local_file) in &local_map {
-                    if !device_map.contains_key(rel_path) {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "delete".to_string(),
-                            direction: "\u{2716} Computer".to_string(),
-                            size: local_file.size,
-                            reason: "File not on device, will be deleted locally".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                }
-            }
-        }
-        SyncDirection::ComputerToPhone => {
-            // Source = local, Dest = device
-            for (rel_path, local_file) in &local_map {
-                if let Some(dev_file) = device_map.get(rel_path) {
-                    if local_file.size != dev_file.size || local_file.modified_time > dev_file.modified_time {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "update".to_string(),
-                            direction: "\u{2192} Phone".to_string(),
-                            size: local_file.size,
-                            reason: "Local file is newer or different size".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                } else {
-                    actions.push(SyncAction {
-                        file_path: rel_path.to_string(),
-                        action_type: "copy".to_string(),
-                        direction: "\u{2192} Phone".to_string(),
-                        size: local_file.size,
-                        reason: "File only exists locally".to_string(),
-                        rename_from: None,
-                    });
-                }
-            }
-            if delete_missing {
-                for (rel_path, dev_file) in &device_map {
-                    if !local_map.contains_key(rel_path) {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "delete".to_string(),
-                            direction: "\u{2716} Phone".to_string(),
-                            size: dev_file.size,
-                            reason: "File not on computer, will be deleted from device".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                }
-            }
-        }
-        SyncDirection::BothWays => {
-            // Merge: files unique to one side get copied, conflicts resolved by mtime
-            let mut all_paths: std::collections::HashSet<&str> = std::collections::HashSet::new();
-            all_paths.extend(local_map.keys());
-            all_paths.extend(device_map.keys());
+// Extract the version from `adb version` output
+// Prefers the platform-tools release ("Version 35.0.2-12147458") over the protocol version ("1.0.41")
+fn parse_adb_version(output: &str) -> Option<String> {
+    let platform_tools = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Version "))
+        .map(|v| v.trim().to_string());
+    platform_tools.or_else(|| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Android Debug Bridge version "))
+            .map(|v| v.trim().to_string())
+    })
+}
 
-            for rel_path in all_paths {
-                match (local_map.get(rel_path), device_map.get(rel_path)) {
-                    (Some(_local_file), None) => {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "copy".to_string(),
-                            direction: "\u{2192} Phone".to_string(),
-                            size: _local_file.size,
-                            reason: "File only exists locally".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    (None, Some(_dev_file)) => {
-                        actions.push(SyncAction {
-                            file_path: rel_path.to_string(),
-                            action_type: "copy".to_string(),
-                            direction: "\u{2192} Computer".to_string(),
-                            size: _dev_file.size,
-                            reason: "File only exists on device".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    (Some(local_file), Some(dev_file)) => {
-                        if local_file.size != dev_file.size || local_file.modified_time != dev_file.modified_time {
-                            if local_file.modified_time >= dev_file.modified_time {
-                                actions.push(SyncAction {
-                                    file_path: rel_path.to_string(),
-                                    action_type: "update".to_string(),
-                                    direction: "\u{2192} Phone".to_string(),
-                                    size: local_file.size,
-                                    reason: "Local file is newer".to_string(),
-                                    rename_from: None,
-                                });
-                            } else {
-                                actions.push(SyncAction {
-                                    file_path: rel_path.to_string(),
-                                    action_type: "update".to_string(),
-                                    direction: "\u{2192} Computer".to_string(),
-                                    size: dev_file.size,
-                                    reason: "Device file is newer".to_string(),
-                                    rename_from: None,
-                                });
-                            }
-                        }
-                        // Same size and mtime → skip (no action needed)
-                    }
-                    (None, None) => {} // shouldn't happen
-                }
-            }
-        }
+// Everything the setup screen needs in one call: adb location/version, connected devices and ffmpeg
+#[tauri::command]
+async fn get_diagnostics(app: tauri::AppHandle) -> Diagnostics {
+    let shell = app.shell();
+    let adb_path = get_current_adb_path();
+
+    let adb_output = shell.command(&adb_path).args(["version"]).output().await.ok();
+    let adb_found = adb_output.as_ref().is_some_and(|o| o.status.success());
+    let adb_version = adb_output
+        .as_ref()
+        .filter(|_| adb_found)
+        .and_then(|o| parse_adb_version(&String::from_utf8_lossy(&o.stdout)));
+
+    let devices = if adb_found {
+        get_devices(app.clone()).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let ffmpeg_found = shell
+        .command("ffmpeg")
+        .args(["-version"])
+        .output()
+        .await
+        .is_ok_and(|o| o.status.success());
+
+    Diagnostics {
+        adb_found,
+        adb_path,
+        adb_version,
+        devices,
+        ffmpeg_found,
     }
+}
 
-    actions.sort_by(|a, b| a.file_path.cmp(&b.file_path));
-    actions
+// Interpret the output of `adb root` / `adb unroot`
+// Returns "restarted" when adbd is restarting, "unchanged" when it was already in the requested mode
+fn parse_adb_root_output(output: &str, to_root: bool) -> Result<String, String> {
+    let text = output.trim();
+    if to_root {
+        if text.contains("already running as root") {
+            Ok("unchanged".to_string())
+        } else if text.contains("restarting adbd as root") {
+            Ok("restarted".to_string())
+        } else if text.contains("cannot run as root") {
+            Err("adbd cannot run as root on this device (production build)".to_string())
+        } else {
+            Err(format!("adb root failed: {}", text))
+        }
+    } else if text.contains("not running as root") {
+        Ok("unchanged".to_string())
+    } else if text.contains("restarting adbd as non root") {
+        Ok("restarted".to_string())
+    } else {
+        Err(format!("adb unroot failed: {}", text))
+    }
 }
 
-// Content-based (MD5) sync action computation
-fn compute_sync_actions_by_content(
-    local_files: &[FileMetadata],
-    device_files: &[FileMetadata],
-    direction: &SyncDirection,
-    delete_missing: bool,
-) -> Vec<SyncAction> {
-    use std::collections::HashSet;
+// Restart adbd on the device as root or non-root
+async fn restart_adbd(app: &tauri::AppHandle, device_id: &str, to_root: bool) -> Result<String, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let subcommand = if to_root { "root" } else { "unroot" };
 
-    let mut actions = Vec::new();
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, subcommand])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute adb {}: {}", subcommand, e))?;
 
-    // Build hash -> files maps (only files with hashes)
-    let mut local_by_hash: HashMap<&str, Vec<&FileMetadata>> = HashMap::new();
-    for f in local_files.iter().filter(|f| !f.is_directory && f.md5_hash.is_some()) {
-        local_by_hash.entry(f.md5_hash.as_ref().unwrap().as_str())
-            .or_default().push(f);
+    // adb prints these messages on either stream depending on the version
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result = parse_adb_root_output(&combined, to_root)?;
+
+    // The transport drops while adbd restarts, so let the UI know the device will reconnect
+    if result == "restarted" {
+        let _ = app.emit("device-reconnecting", device_id.to_string());
     }
-    let mut device_by_hash: HashMap<&str, Vec<&FileMetadata>> = HashMap::new();
-    for f in device_files.iter().filter(|f| !f.is_directory && f.md5_hash.is_some()) {
-        device_by_hash.entry(f.md5_hash.as_ref().unwrap().as_str())
-            .or_default().push(f);
+
+    Ok(result)
+}
+
+// Restart adbd as root (userdebug/eng builds only)
+#[tauri::command]
+async fn adb_root(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+    restart_adbd(&app, &device_id, true).await
+}
+
+// Restart adbd without root
+#[tauri::command]
+async fn adb_unroot(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+    restart_adbd(&app, &device_id, false).await
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PortMapping {
+    pub local: String,
+    pub remote: String,
+}
+
+// Parse `adb forward --list` / `adb reverse --list` output ("<serial> <local> <remote>" per line)
+// forward lists mappings for every device, so rows are filtered to `device_id` when given
+fn parse_port_mappings(output: &str, device_id: Option<&str>) -> Vec<PortMapping> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 3 || device_id.is_some_and(|id| parts[0] != id) {
+                return None;
+            }
+            Some(PortMapping {
+                local: parts[1].to_string(),
+                remote: parts[2].to_string(),
+            })
+        })
+        .collect()
+}
+
+// Run `adb -s <device> forward|reverse ...` and return its stdout
+async fn run_port_command(app: &tauri::AppHandle, device_id: &str, args: &[&str]) -> Result<String, String> {
+    let mut full_args = vec!["-s", device_id];
+    full_args.extend_from_slice(args);
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(&full_args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute adb {}: {}", args[0], e))?;
+
+    if !output.status.success() {
+        return Err(format!("adb {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
     }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
 
-    // Also build path maps for update detection
-    let local_by_path: HashMap<&str, &FileMetadata> = local_files.iter()
-        .filter(|f| !f.is_directory && f.md5_hash.is_some())
-        .map(|f| (f.relative_path.as_str(), f))
-        .collect();
-    let device_by_path: HashMap<&str, &FileMetadata> = device_files.iter()
-        .filter(|f| !f.is_directory && f.md5_hash.is_some())
-        .map(|f| (f.relative_path.as_str(), f))
-        .collect();
+// List host -> device port forwards for a device
+#[tauri::command]
+async fn list_forwards(app: tauri::AppHandle, device_id: String) -> Result<Vec<PortMapping>, String> {
+    let stdout = run_port_command(&app, &device_id, &["forward", "--list"]).await?;
+    Ok(parse_port_mappings(&stdout, Some(&device_id)))
+}
 
-    let mut handled_local: HashSet<String> = HashSet::new();
-    let mut handled_device: HashSet<String> = HashSet::new();
+// Forward host tcp:<local> to device tcp:<remote>
+#[tauri::command]
+async fn add_forward(app: tauri::AppHandle, device_id: String, local: u16, remote: u16) -> Result<Vec<PortMapping>, String> {
+    let local = format!("tcp:{}", local);
+    let remote = format!("tcp:{}", remote);
+    run_port_command(&app, &device_id, &["forward", &local, &remote]).await?;
+    list_forwards(app, device_id).await
+}
 
-    // Collect all unique hashes
-    let mut all_hashes: HashSet<&str> = HashSet::new();
-    all_hashes.extend(local_by_hash.keys());
-    all_hashes.extend(device_by_hash.keys());
+// Remove the forward listening on host tcp:<local>
+#[tauri::command]
+async fn remove_forward(app: tauri::AppHandle, device_id: String, local: u16) -> Result<Vec<PortMapping>, String> {
+    let local = format!("tcp:{}", local);
+    run_port_command(&app, &device_id, &["forward", "--remove", &local]).await?;
+    list_forwards(app, device_id).await
+}
 
-    for hash in &all_hashes {
-        let local_for_hash = local_by_hash.get(hash).cloned().unwrap_or_default();
-        let device_for_hash = device_by_hash.get(hash).cloned().unwrap_or_default();
+// List device -> host reverse mappings (`local` is the device port, `remote` the host port)
+#[tauri::command]
+async fn list_reverses(app: tauri::AppHandle, device_id: String) -> Result<Vec<PortMapping>, String> {
+    let stdout = run_port_command(&app, &device_id, &["reverse", "--list"]).await?;
+    // reverse --list prints the transport name (e.g. "UsbFfs") instead of the serial
+    Ok(parse_port_mappings(&stdout, None))
+}
 
-        if !local_for_hash.is_empty() && !device_for_hash.is_empty() {
-            // Hash exists on both sides
-            let lf = local_for_hash[0];
-            let df = device_for_hash[0];
-            handled_local.insert(lf.relative_path.clone());
-            handled_device.insert(df.relative_path.clone());
+// Reverse device tcp:<device_port> to host tcp:<host_port>
+#[tauri::command]
+async fn add_reverse(
+    app: tauri::AppHandle,
+    device_id: String,
+    device_port: u16,
+    host_port: u16,
+) -> Result<Vec<PortMapping>, String> {
+    let device_port = format!("tcp:{}", device_port);
+    let host_port = format!("tcp:{}", host_port);
+    run_port_command(&app, &device_id, &["reverse", &device_port, &host_port]).await?;
+    list_reverses(app, device_id).await
+}
 
-            if lf.relative_path != df.relative_path {
-                // Same content, different path → rename
-                match direction {
-                    SyncDirection::PhoneToComputer => {
-                        // Source is device, so rename local to match device name
-                        actions.push(SyncAction {
-                            file_path: df.relative_path.clone(),
-                            action_type: "rename".to_string(),
-                            direction: "\u{2192} Computer".to_string(),
-                            size: df.size,
-                            reason: format!("Renamed: {} \u{2192} {}", lf.relative_path, df.relative_path),
-                            rename_from: Some(lf.relative_path.clone()),
-                        });
-                    }
-                    SyncDirection::ComputerToPhone => {
-                        // Source is local, so rename device to match local name
-                        actions.push(SyncAction {
-                            file_path: lf.relative_path.clone(),
-                            action_type: "rename".to_string(),
-                            direction: "\u{2192} Phone".to_string(),
-                            size: lf.size,
-                            reason: format!("Renamed: {} \u{2192} {}", df.relative_path, lf.relative_path),
-                            rename_from: Some(df.relative_path.clone()),
-                        });
-                    }
-                    SyncDirection::BothWays => {
-                        // Rename the older side to match the newer
-                        if lf.modified_time >= df.modified_time {
-                            actions.push(SyncAction {
-                                file_path: lf.relative_path.clone(),
-                                action_type: "rename".to_string(),
-                                direction: "\u{2192} Phone".to_string(),
-                                size: lf.size,
-                                reason: format!("Renamed: {} \u{2192} {}", df.relative_path, lf.relative_path),
-                                rename_from: Some(df.relative_path.clone()),
-                            });
-                        } else {
-                            actions.push(SyncAction {
-                                file_path: df.relative_path.clone(),
-                                action_type: "rename".to_string(),
-                                direction: "\u{2192} Computer".to_string(),
-                                size: df.size,
-                                reason: format!("Renamed: {} \u{2192} {}", lf.relative_path, df.relative_path),
-                                rename_from: Some(lf.relative_path.clone()),
-                            });
-                        }
-                    }
+// Remove the reverse mapping listening on device tcp:<device_port>
+#[tauri::command]
+async fn remove_reverse(app: tauri::AppHandle, device_id: String, device_port: u16) -> Result<Vec<PortMapping>, String> {
+    let device_port = format!("tcp:{}", device_port);
+    run_port_command(&app, &device_id, &["reverse", "--remove", &device_port]).await?;
+    list_reverses(app, device_id).await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub user: String,
+    pub name: String,
+}
+
+// Parse `ps` output into processes
+// The header is used to locate USER and PID because the column set differs between
+// toybox ps (Android 8+) and the older toolbox ps. NAME is always the last column.
+// Example (toybox): root      1     0 10782796 11744 0     0 S init
+// Example (toolbox): root      1     0  8904  788  ffffffff 00000000 S /init
+fn parse_ps_output(output: &str) -> Vec<ProcessInfo> {
+    let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+
+    let header: Vec<&str> = match lines.next() {
+        Some(header) => header.split_whitespace().collect(),
+        None => return Vec::new(),
+    };
+    let pid_idx = match header.iter().position(|col| *col == "PID") {
+        Some(idx) => idx,
+        None => return Vec::new(),
+    };
+    let user_idx = header.iter().position(|col| *col == "USER");
+
+    lines
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() <= pid_idx + 1 {
+                return None;
+            }
+            let pid = parts[pid_idx].parse::<u32>().ok()?;
+            let user = user_idx
+                .and_then(|idx| parts.get(idx))
+                .map(|user| user.to_string())
+                .unwrap_or_default();
+            let name = parts.last()?.to_string();
+
+            Some(ProcessInfo { pid, user, name })
+        })
+        .collect()
+}
+
+// List running processes on the Android device
+#[tauri::command]
+async fn list_processes(app: tauri::AppHandle, device_id: String) -> Result<Vec<ProcessInfo>, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // toybox ps needs -A to show every process; older toolbox ps lists everything by default
+    // and treats -A as a name filter, so fall back to plain ps when -A yields nothing
+    for ps_command in ["ps -A", "ps"] {
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", ps_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute ps command: {}", e))?;
+
+        if !output.status.success() {
+            continue;
+        }
+
+        let processes = parse_ps_output(&String::from_utf8_lossy(&output.stdout));
+        if !processes.is_empty() {
+            return Ok(processes);
+        }
+    }
+
+    Err("Failed to list processes".to_string())
+}
+
+// Kill a process on the Android device
+#[tauri::command]
+async fn kill_process(app: tauri::AppHandle, device_id: String, pid: u32) -> Result<(), String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let kill_command = format!("kill {}", pid);
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &kill_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute kill command: {}", e))?;
+
+    // Older adb versions don't propagate the shell exit code, so check the output as well
+    let messages = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !output.status.success() || !messages.trim().is_empty() {
+        if messages.contains("Operation not permitted") {
+            return Err(format!("Permission denied: Cannot kill process {}", pid));
+        } else if messages.contains("No such process") {
+            return Err(format!("Process not found: {}", pid));
+        } else if !output.status.success() {
+            return Err(format!("Kill failed: {}", messages.trim()));
+        }
+    }
+
+    Ok(())
+}
+
+// Upper bound on lines returned by get_logcat_snapshot
+const MAX_LOGCAT_SNAPSHOT_LINES: u32 = 5000;
+
+// Split a logcat filter spec ("ActivityManager:I MyApp:D *:S") into arguments, rejecting malformed entries
+fn parse_logcat_filter(filter: &str) -> Result<Vec<String>, String> {
+    filter
+        .split_whitespace()
+        .map(|spec| {
+            let valid = match spec.rsplit_once(':') {
+                Some((tag, priority)) => {
+                    !tag.is_empty()
+                        && tag.chars().all(|c| c.is_alphanumeric() || "_.-*$/".contains(c))
+                        && matches!(priority, "V" | "D" | "I" | "W" | "E" | "F" | "S")
                 }
+                None => false,
+            };
+            if valid {
+                Ok(spec.to_string())
+            } else {
+                Err(format!("Invalid logcat filter: '{}' (expected TAG:PRIORITY)", spec))
             }
-            // Same hash + same path → skip (identical, no action)
+        })
+        .collect()
+}
 
-            // Mark additional files with same hash as handled
-            for f in &local_for_hash { handled_local.insert(f.relative_path.clone()); }
-            for f in &device_for_hash { handled_device.insert(f.relative_path.clone()); }
-        } else if !local_for_hash.is_empty() {
-            // Hash only on local side
-            for lf in &local_for_hash {
-                handled_local.insert(lf.relative_path.clone());
-                match direction {
-                    SyncDirection::ComputerToPhone | SyncDirection::BothWays => {
-                        actions.push(SyncAction {
-                            file_path: lf.relative_path.clone(),
-                            action_type: "copy".to_string(),
-                            direction: "\u{2192} Phone".to_string(),
-                            size: lf.size,
-                            reason: "File only exists locally".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    SyncDirection::PhoneToComputer => {
-                        // Source is device, local-only file: delete if enabled
-                        if delete_missing {
-                            actions.push(SyncAction {
-                                file_path: lf.relative_path.clone(),
-                                action_type: "delete".to_string(),
-                                direction: "\u{2716} Computer".to_string(),
-                                size: lf.size,
-                                reason: "File not on device, will be deleted locally".to_string(),
-                                rename_from: None,
-                            });
-                        }
-                    }
+// Dump the most recent logcat lines once instead of streaming
+// `lines` is capped at MAX_LOGCAT_SNAPSHOT_LINES; `tag_filter` takes a standard filter spec
+#[tauri::command]
+async fn get_logcat_snapshot(
+    app: tauri::AppHandle,
+    device_id: String,
+    lines: u32,
+    tag_filter: Option<String>,
+) -> Result<String, String> {
+    let line_count = lines.clamp(1, MAX_LOGCAT_SNAPSHOT_LINES).to_string();
+    let mut args = vec![
+        "-s".to_string(),
+        device_id,
+        "logcat".to_string(),
+        "-d".to_string(),
+        "-t".to_string(),
+        line_count,
+    ];
+    if let Some(filter) = tag_filter.as_deref().filter(|f| !f.trim().is_empty()) {
+        args.extend(parse_logcat_filter(filter)?);
+    }
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read logcat: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("logcat failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DeviceLocale {
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+}
+
+// Parse the output of `getprop persist.sys.locale; getprop ro.product.locale; getprop persist.sys.timezone`
+// The user-selected locale wins; the factory locale is used when it was never changed
+fn parse_device_locale(output: &str) -> DeviceLocale {
+    let values: Vec<Option<String>> = output
+        .lines()
+        .map(|line| Some(line.trim().to_string()).filter(|value| !value.is_empty()))
+        .collect();
+    let value = |i: usize| values.get(i).cloned().flatten();
+
+    DeviceLocale {
+        locale: value(0).or_else(|| value(1)),
+        timezone: value(2),
+    }
+}
+
+// Get the device's locale (e.g. "de-DE") and time zone (e.g. "Europe/Berlin")
+// Lets the UI show dates in the device's time zone and interpret localized listings
+#[tauri::command]
+async fn get_device_locale(app: tauri::AppHandle, device_id: String) -> Result<DeviceLocale, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args([
+            "-s",
+            &device_id,
+            "shell",
+            "getprop persist.sys.locale; getprop ro.product.locale; getprop persist.sys.timezone",
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read device locale: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to read device locale: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(parse_device_locale(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceIdentity {
+    pub serial: Option<String>,
+    pub boot_serial: Option<String>,
+    pub bluetooth_name: Option<String>,
+    pub hostname: Option<String>,
+}
+
+// Parse one value per line in the order queried by get_device_identity
+// Empty lines and the `settings` placeholder "null" become None
+fn parse_device_identity(output: &str) -> DeviceIdentity {
+    let values: Vec<Option<String>> = output
+        .lines()
+        .map(|line| Some(line.trim().to_string()).filter(|value| !value.is_empty() && value != "null"))
+        .collect();
+    let value = |i: usize| values.get(i).cloned().flatten();
+
+    DeviceIdentity {
+        serial: value(0),
+        boot_serial: value(1),
+        bluetooth_name: value(2),
+        hostname: value(3),
+    }
+}
+
+// Identifiers that tell apart units of the same model: serials, Bluetooth name and hostname
+#[tauri::command]
+async fn get_device_identity(app: tauri::AppHandle, device_id: String) -> Result<DeviceIdentity, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args([
+            "-s",
+            &device_id,
+            "shell",
+            "getprop ro.serialno; getprop ro.boot.serialno; settings get secure bluetooth_name; getprop net.hostname",
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read device identity: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to read device identity: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(parse_device_identity(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub model: String,
+    pub manufacturer: String,
+    pub android_version: String,
+    pub sdk: String,
+}
+
+// Parse a full `getprop` dump of "[key]: [value]" lines
+fn parse_getprop(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once("]: [")?;
+            let key = key.strip_prefix('[')?;
+            let value = value.strip_suffix(']')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// Model, manufacturer and Android version of a device, read from a single getprop dump
+#[tauri::command]
+async fn get_device_info(app: tauri::AppHandle, device_id: String) -> Result<DeviceInfo, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", "getprop"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read device properties: {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("unauthorized") {
+        return Err(format!("Device {} is unauthorized: accept the USB debugging prompt on the device", device_id));
+    }
+    if !output.status.success() {
+        return Err(format!("Failed to read device properties: {}", stderr));
+    }
+
+    let props = parse_getprop(&String::from_utf8_lossy(&output.stdout));
+    let prop = |key: &str| props.get(key).cloned().unwrap_or_default();
+
+    Ok(DeviceInfo {
+        model: prop("ro.product.model"),
+        manufacturer: prop("ro.product.manufacturer"),
+        android_version: prop("ro.build.version.release"),
+        sdk: prop("ro.build.version.sdk"),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DeviceCapabilities {
+    pub supports_exec_out: bool,
+    pub supports_cmd: bool,
+    pub supports_time_style: bool,
+    pub supports_stat_format: bool,
+}
+
+// Probed capabilities per device id; cleared when a device is refreshed after reconnecting
+static DEVICE_CAPABILITIES: LazyLock<Mutex<HashMap<String, DeviceCapabilities>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Shell probes printing "<feature>=1" or "<feature>=0" on their own line
+const CAPABILITY_PROBES: &str = "cmd -l >/dev/null 2>&1 && echo cmd=1 || echo cmd=0; \
+                                 ls -d --time-style=+%s / >/dev/null 2>&1 && echo time_style=1 || echo time_style=0; \
+                                 stat -c %Y / >/dev/null 2>&1 && echo stat_format=1 || echo stat_format=0";
+
+// Parse the output of CAPABILITY_PROBES; missing lines count as unsupported
+fn parse_capability_probes(output: &str, supports_exec_out: bool) -> DeviceCapabilities {
+    let flag = |name: &str| {
+        output
+            .lines()
+            .any(|line| line.trim() == format!("{}=1", name))
+    };
+
+    DeviceCapabilities {
+        supports_exec_out,
+        supports_cmd: flag("cmd"),
+        supports_time_style: flag("time_style"),
+        supports_stat_format: flag("stat_format"),
+    }
+}
+
+// Probe which adb/shell features a device supports, caching the result per device
+// Lets commands pick the best implementation instead of sniffing versions individually
+#[tauri::command]
+async fn get_capabilities(app: tauri::AppHandle, device_id: String) -> Result<DeviceCapabilities, String> {
+    let cached = DEVICE_CAPABILITIES.lock().ok().and_then(|cache| cache.get(&device_id).cloned());
+    if let Some(cached) = cached {
+        return Ok(cached);
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let exec_out = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "exec-out", "echo ok"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to probe device capabilities: {}", e))?;
+    let supports_exec_out = exec_out.status.success() && String::from_utf8_lossy(&exec_out.stdout).trim() == "ok";
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", CAPABILITY_PROBES])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to probe device capabilities: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to probe device capabilities: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let capabilities = parse_capability_probes(&String::from_utf8_lossy(&output.stdout), supports_exec_out);
+    if let Ok(mut cache) = DEVICE_CAPABILITIES.lock() {
+        cache.insert(device_id, capabilities.clone());
+    }
+    Ok(capabilities)
+}
+
+// Extract the data payload from `am broadcast` output
+// Example: Broadcast completed: result=-1, data="copied text"
+fn parse_broadcast_data(output: &str) -> Option<String> {
+    let line = output.lines().find(|line| line.contains("Broadcast completed"))?;
+    let start = line.find("data=\"")? + "data=\"".len();
+    let end = line.rfind('"')?;
+    if end < start {
+        return None;
+    }
+    Some(line[start..end].to_string())
+}
+
+// Read the device clipboard
+// Shell access to the clipboard is blocked for background callers since Android 10, so this goes
+// through the Clipper helper app's broadcast receiver (`clipper.get`)
+#[tauri::command]
+async fn get_device_clipboard(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", "am broadcast -a clipper.get"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read device clipboard: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_broadcast_data(&stdout).ok_or_else(|| {
+        "Could not read the device clipboard. Android 10+ only allows the foreground app to read \
+         the clipboard, so install and open the Clipper helper app on the device and try again."
+            .to_string()
+    })
+}
+
+// Write text to the device clipboard
+// Uses the Clipper helper app broadcast (`clipper.set`); a result of 0 means nothing handled it
+#[tauri::command]
+async fn set_device_clipboard(app: tauri::AppHandle, device_id: String, text: String) -> Result<(), String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let escaped_text = text.replace("'", "'\\''");
+    let broadcast_command = format!("am broadcast -a clipper.set -e text '{}'", escaped_text);
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &broadcast_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to set device clipboard: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !stdout.contains("Broadcast completed") {
+        return Err(format!("Set clipboard failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    if stdout.contains("result=0") {
+        return Err(
+            "No clipboard receiver on the device. Android does not allow setting the clipboard \
+             from the shell, so install the Clipper helper app on the device and try again."
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+// IME ids look like "com.android.inputmethod.latin/.LatinIME"
+fn is_valid_ime_id(ime_id: &str) -> bool {
+    ime_id.contains('/')
+        && ime_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '$'))
+}
+
+// List enabled input methods (keyboards) by id
+#[tauri::command]
+async fn list_input_methods(app: tauri::AppHandle, device_id: String) -> Result<Vec<String>, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", "ime list -s"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list input methods: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to list input methods: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_valid_ime_id(line))
+        .map(str::to_string)
+        .collect())
+}
+
+// Get the id of the currently selected input method
+#[tauri::command]
+async fn get_default_input_method(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", "settings get secure default_input_method"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read default input method: {}", e))?;
+
+    let ime_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || ime_id.is_empty() || ime_id == "null" {
+        return Err("No default input method set".to_string());
+    }
+    Ok(ime_id)
+}
+
+// Switch the active input method, e.g. to a plain keyboard before scripted `input text`
+#[tauri::command]
+async fn set_input_method(app: tauri::AppHandle, device_id: String, ime_id: String) -> Result<(), String> {
+    if !is_valid_ime_id(&ime_id) {
+        return Err(format!("Invalid input method id: {}", ime_id));
+    }
+
+    // `ime set` only accepts enabled input methods
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &format!("ime enable '{0}' && ime set '{0}'", ime_id)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to set input method: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("Unknown input method") || stdout.contains("Error") {
+        return Err(format!(
+            "Failed to set input method {}: {}{}",
+            ime_id,
+            stdout.trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// List every path a delete would remove (the entry itself plus, for directories, its contents)
+async fn list_paths_to_delete(app: &tauri::AppHandle, device_id: &str, path: &str) -> Result<Vec<String>, String> {
+    let escaped_path = path.replace("'", "'\\''");
+    let find_command = format!("find '{}' 2>/dev/null", escaped_path);
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &find_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute find command: {}", e))?;
+
+    let paths: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if paths.is_empty() {
+        return Err(format!("File not found: {}", path));
+    }
+
+    Ok(paths)
+}
+
+// Delete a file or directory on the Android device
+// With dry_run, nothing is deleted and the paths that would be removed are returned instead
+#[tauri::command]
+async fn delete_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    is_directory: bool,
+    dry_run: bool,
+) -> Result<Vec<String>, AdbError> {
+    if is_critical_system_path(&file_path) {
+        return Err(AdbError::InvalidArgument(format!("Cannot delete critical system path: {}", file_path)));
+    }
+
+    if dry_run {
+        return Ok(list_paths_to_delete(&app, &device_id, &file_path).await?);
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Escape single quotes in path
+    let escaped_path = file_path.replace("'", "'\\''");
+
+    // Use rm -r for directories, rm for files
+    let rm_command = if is_directory {
+        format!("rm -r '{}'", escaped_path)
+    } else {
+        format!("rm '{}'", escaped_path)
+    };
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &rm_command])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AdbError::from_stderr(&stderr, &file_path));
+    }
+
+    Ok(Vec::new())
+}
+
+// Directories with more entries than this are removed with a single `rm -rf`
+const FAST_DELETE_THRESHOLD: u64 = 10_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteProgress {
+    pub path: String,
+    pub current_item: String,
+    pub completed: u32,
+    pub total: u32,
+    pub total_entries: u64,
+    pub finished: bool,
+}
+
+// Delete a directory on the Android device, emitting `delete-progress` events as it goes
+// With dry_run, nothing is deleted and the paths that would be removed are returned instead
+#[tauri::command]
+async fn delete_directory_with_progress(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    if is_critical_system_path(&path) {
+        return Err(format!("Cannot delete critical system path: {}", path));
+    }
+
+    if dry_run {
+        return list_paths_to_delete(&app, &device_id, &path).await;
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let escaped_path = path.replace("'", "'\\''");
+
+    // Count every entry in the tree so the UI can show how much is being removed
+    let count_command = format!("find '{}' -mindepth 1 2>/dev/null | wc -l", escaped_path);
+    let count_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &count_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute delete command: {}", e))?;
+    let total_entries = String::from_utf8_lossy(&count_output.stdout).trim().parse::<u64>().unwrap_or(0);
+
+    // Top-level children are deleted one at a time so progress can be reported between them
+    let children: Vec<String> = if total_entries > FAST_DELETE_THRESHOLD {
+        Vec::new()
+    } else {
+        let list_command = format!("find '{}' -mindepth 1 -maxdepth 1 2>/dev/null", escaped_path);
+        let list_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &list_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute delete command: {}", e))?;
+        String::from_utf8_lossy(&list_output.stdout)
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+
+    let total = children.len() as u32;
+    let mut progress = DeleteProgress {
+        path: path.clone(),
+        current_item: String::new(),
+        completed: 0,
+        total,
+        total_entries,
+        finished: false,
+    };
+    let _ = app.emit("delete-progress", progress.clone());
+
+    let mut errors: Vec<String> = Vec::new();
+    for child in &children {
+        progress.current_item = child.clone();
+        let escaped_child = child.replace("'", "'\\''");
+        let rm_command = format!("rm -rf '{}'", escaped_child);
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &rm_command])
+            .output()
+            .await;
+        match output {
+            Ok(o) if o.status.success() => {}
+            Ok(o) => errors.push(format!("{}: {}", child, String::from_utf8_lossy(&o.stderr).trim())),
+            Err(e) => errors.push(format!("{}: {}", child, e)),
+        }
+        progress.completed += 1;
+        let _ = app.emit("delete-progress", progress.clone());
+    }
+
+    // Remove whatever is left (the whole tree on the fast path, the empty directory otherwise)
+    let rm_command = format!("rm -rf '{}'", escaped_path);
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &rm_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute delete command: {}", e))?;
+
+    progress.current_item = String::new();
+    progress.finished = true;
+    let _ = app.emit("delete-progress", progress);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Permission denied") {
+            return Err(format!("Permission denied: Cannot delete {}", path));
+        }
+        errors.push(format!("{}: {}", path, stderr.trim()));
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("Delete failed: {}", errors.join("; ")));
+    }
+
+    Ok(Vec::new())
+}
+
+// True when two names differ only by letter case (e.g. photo.JPG -> photo.jpg)
+fn is_case_only_change(old_name: &str, new_name: &str) -> bool {
+    old_name != new_name && old_name.to_lowercase() == new_name.to_lowercase()
+}
+
+// Shell check that echoes `marker` when renaming old to new would replace another file
+// A case-only rename "finds" itself on case-insensitive filesystems, so there the target only
+// counts as existing when it is a different file (different inode), e.g. on a case-sensitive volume
+fn rename_conflict_check(escaped_old: &str, escaped_new: &str, case_only: bool, marker: &str) -> String {
+    if case_only {
+        format!(
+            "a=$(stat -c %i '{}' 2>/dev/null); b=$(stat -c %i '{}' 2>/dev/null); [ -n \"$b\" ] && [ \"$a\" != \"$b\" ] && echo '{}'",
+            escaped_old, escaped_new, marker
+        )
+    } else {
+        format!("[ -e '{}' ] && echo '{}'", escaped_new, marker)
+    }
+}
+
+// Rename a file or directory on the Android device, returning the final name
+// Case-only renames go through a temporary name, since a direct mv fails or no-ops on FAT/exFAT SD cards.
+// An existing target is only replaced when force is set.
+#[tauri::command]
+async fn rename_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    old_path: String,
+    new_name: String,
+    force: Option<bool>,
+) -> Result<String, AdbError> {
+    // Validate new name doesn't contain path separators
+    if new_name.contains('/') || new_name.contains('\\') {
+        return Err(AdbError::InvalidArgument("Invalid name: cannot contain path separators".to_string()));
+    }
+
+    // Validate new name is not empty
+    if new_name.trim().is_empty() {
+        return Err(AdbError::InvalidArgument("Invalid name: cannot be empty".to_string()));
+    }
+
+    if is_critical_system_path(&old_path) {
+        return Err(AdbError::InvalidArgument(format!("Cannot rename critical system path: {}", old_path)));
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Build the new path by replacing the filename
+    let parent_path = old_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+    let new_path = if parent_path.is_empty() {
+        format!("/{}", new_name)
+    } else {
+        format!("{}/{}", parent_path, new_name)
+    };
+
+    // Escape single quotes in paths
+    let escaped_old_path = old_path.replace("'", "'\\''");
+    let escaped_new_path = new_path.replace("'", "'\\''");
+
+    // Use mv command to rename
+    let old_name = old_path.rsplit('/').next().unwrap_or_default();
+    let case_only = is_case_only_change(old_name, &new_name);
+
+    // mv silently replaces files, so check for an existing target first
+    if !force.unwrap_or(false) {
+        let test_command = rename_conflict_check(&escaped_old_path, &escaped_new_path, case_only, "exists");
+        let test_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &test_command])
+            .output()
+            .await
+            .map_err(AdbError::from_spawn)?;
+
+        if String::from_utf8_lossy(&test_output.stdout).contains("exists") {
+            return Err(AdbError::AlreadyExists(new_name));
+        }
+    }
+
+    let mv_command = if case_only {
+        let temp_path = format!("{}/.droiddock_rename_{}", parent_path, now_millis());
+        // Move back to the original name if the second step fails, so the file isn't left under the temp name
+        format!(
+            "mv '{old}' '{tmp}' && {{ mv '{tmp}' '{new}' || {{ mv '{tmp}' '{old}'; exit 1; }}; }}",
+            old = escaped_old_path,
+            tmp = temp_path.replace("'", "'\\''"),
+            new = escaped_new_path
+        )
+    } else {
+        format!("mv '{}' '{}'", escaped_old_path, escaped_new_path)
+    };
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &mv_command])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match AdbError::from_stderr(&stderr, &old_path) {
+            AdbError::AlreadyExists(_) => AdbError::AlreadyExists(new_name),
+            error => error,
+        });
+    }
+
+    Ok(new_name)
+}
+
+// Create a directory (and any missing parents) on the device
+#[tauri::command]
+async fn create_directory(app: tauri::AppHandle, device_id: String, path: String) -> Result<(), AdbError> {
+    // Escape single quotes in path
+    let escaped_path = path.replace("'", "'\\''");
+    let mkdir_command = format!(
+        "if [ -e '{0}' ] && [ ! -d '{0}' ]; then echo 'not a directory'; exit 1; fi; mkdir -p '{0}'",
+        escaped_path
+    );
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &mkdir_command])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stdout.contains("not a directory") {
+            return Err(AdbError::AlreadyExists(path));
+        }
+        return Err(AdbError::from_stderr(&stderr, &path));
+    }
+
+    Ok(())
+}
+
+// Copy a file (cp) or directory (cp -r) to another location on the device
+#[tauri::command]
+async fn copy_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    source_path: String,
+    dest_path: String,
+    is_directory: bool,
+) -> Result<(), AdbError> {
+    if is_critical_system_path(&dest_path) {
+        return Err(AdbError::InvalidArgument(format!("Cannot copy into critical system path: {}", dest_path)));
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Escape single quotes in paths
+    let escaped_source = source_path.replace("'", "'\\''");
+    let escaped_dest = dest_path.replace("'", "'\\''");
+
+    let test_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("test -e '{}' && echo exists", escaped_source)])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if !String::from_utf8_lossy(&test_output.stdout).contains("exists") {
+        return Err(AdbError::NotFound(source_path));
+    }
+
+    let cp_command = if is_directory {
+        format!("cp -r '{}' '{}'", escaped_source, escaped_dest)
+    } else {
+        format!("cp '{}' '{}'", escaped_source, escaped_dest)
+    };
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &cp_command])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AdbError::from_stderr(&stderr, &dest_path));
+    }
+
+    Ok(())
+}
+
+// mv refuses to cross mount points on some devices ("Invalid cross-device link" / "Cross-device link")
+fn is_cross_device_error(stderr: &str) -> bool {
+    stderr.contains("Invalid cross-device link") || stderr.contains("Cross-device")
+}
+
+// Move a file or directory on the device, returning the strategy used: "mv", or "copy-delete"
+// when mv can't cross filesystems and the slower copy, verify and delete fallback was needed.
+// An existing destination is only replaced when force is set.
+#[tauri::command]
+async fn move_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    source_path: String,
+    dest_path: String,
+    force: Option<bool>,
+) -> Result<String, AdbError> {
+    if is_critical_system_path(&source_path) {
+        return Err(AdbError::InvalidArgument(format!("Cannot move critical system path: {}", source_path)));
+    }
+    if is_critical_system_path(&dest_path) {
+        return Err(AdbError::InvalidArgument(format!("Cannot move into critical system path: {}", dest_path)));
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Escape single quotes in paths
+    let escaped_source = source_path.replace("'", "'\\''");
+    let escaped_dest = dest_path.replace("'", "'\\''");
+
+    // mv silently replaces files, so check for an existing destination first
+    if !force.unwrap_or(false) {
+        let test_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &format!("test -e '{}' && echo exists", escaped_dest)])
+            .output()
+            .await
+            .map_err(AdbError::from_spawn)?;
+
+        if String::from_utf8_lossy(&test_output.stdout).contains("exists") {
+            return Err(AdbError::AlreadyExists(dest_path));
+        }
+    }
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("mv '{}' '{}'", escaped_source, escaped_dest)])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+
+    if output.status.success() {
+        return Ok("mv".to_string());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !is_cross_device_error(&stderr) {
+        let path = if stderr.contains("Read-only file system") { &dest_path } else { &source_path };
+        return Err(AdbError::from_stderr(&stderr, path));
+    }
+
+    // Same path as move_across_volumes: the source is only removed once the copy's checksums match
+    copy_verify_delete(&app, &device_id, &source_path, &dest_path)
+        .await
+        .map_err(|e| AdbError::from_stderr(&e, &dest_path))?;
+
+    Ok("copy-delete".to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchRenameResult {
+    pub old_path: String,
+    pub new_path: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// Compute new paths for a batch rename from a template
+// Supports {name} (file name without extension), {ext} and {index} (1-based, zero-padded)
+// Fails if any name is invalid or two files would end up with the same path
+fn compute_batch_rename(paths: &[String], template: &str) -> Result<Vec<(String, String)>, String> {
+    let width = paths.len().to_string().len();
+    let mut targets = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (i, path) in paths.iter().enumerate() {
+        let (parent, file_name) = path.rsplit_once('/').unwrap_or(("", path.as_str()));
+        let (stem, ext) = match file_name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, ext),
+            _ => (file_name, ""),
+        };
+
+        let mut new_name = template
+            .replace("{name}", stem)
+            .replace("{index}", &format!("{:0width$}", i + 1, width = width))
+            .replace("{ext}", ext);
+        // A template without {ext} keeps the original extension
+        if !template.contains("{ext}") && !ext.is_empty() {
+            new_name = format!("{}.{}", new_name, ext);
+        }
+
+        if new_name.trim().is_empty() || new_name.contains('/') || new_name.contains('\\') {
+            return Err(format!("Invalid name generated for {}: '{}'", path, new_name));
+        }
+
+        let new_path = format!("{}/{}", parent, new_name);
+        if !seen.insert(new_path.clone()) {
+            return Err(format!("Rename would create duplicate name: {}", new_path));
+        }
+        targets.push((path.clone(), new_path));
+    }
+
+    // Renames run one by one, so a target that is another file in the batch would overwrite it
+    let sources: HashSet<&String> = paths.iter().collect();
+    if let Some((old, new)) = targets.iter().find(|(old, new)| old != new && sources.contains(new)) {
+        return Err(format!("Renaming {} would overwrite {}, which is also being renamed", old, new));
+    }
+
+    Ok(targets)
+}
+
+// Rename many files using a name template, e.g. "Trip_{index}" or "{name}_edited"
+// All names are validated and checked for collisions before anything is renamed
+#[tauri::command]
+async fn batch_rename(
+    app: tauri::AppHandle,
+    device_id: String,
+    paths: Vec<String>,
+    template: String,
+) -> Result<Vec<BatchRenameResult>, String> {
+    let targets = compute_batch_rename(&paths, &template)?;
+
+    // Refuse to clobber files that exist on the device but are not part of the batch
+    // Case-only renames use rename_file's inode comparison, since on FAT/exFAT the target is the file itself
+    let checks: Vec<String> = targets
+        .iter()
+        .filter(|(old, new)| old != new)
+        .map(|(old, new)| {
+            let old_name = old.rsplit('/').next().unwrap_or_default();
+            let new_name = new.rsplit('/').next().unwrap_or_default();
+            let escaped = new.replace("'", "'\\''");
+            rename_conflict_check(&old.replace("'", "'\\''"), &escaped, is_case_only_change(old_name, new_name), &escaped)
+        })
+        .collect();
+    if !checks.is_empty() {
+        let output = app
+            .shell()
+            .command(get_adb_command())
+            .args(["-s", &device_id, "shell", &checks.join("; ")])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to check rename targets: {}", e))?;
+
+        let existing = String::from_utf8_lossy(&output.stdout);
+        if let Some(first) = existing.lines().find(|line| !line.trim().is_empty()) {
+            return Err(format!("A file named {} already exists", first.trim()));
+        }
+    }
+
+    let mut results = Vec::new();
+    for (old_path, new_path) in targets {
+        if old_path == new_path {
+            results.push(BatchRenameResult { old_path, new_path, success: true, error: None });
+            continue;
+        }
+
+        let new_name = new_path.rsplit('/').next().unwrap_or_default().to_string();
+        let outcome = rename_file(app.clone(), device_id.clone(), old_path.clone(), new_name, None).await;
+        results.push(BatchRenameResult {
+            old_path,
+            new_path,
+            success: outcome.is_ok(),
+            error: outcome.err().map(String::from),
+        });
+    }
+
+    Ok(results)
+}
+
+// Folders skipped by recursive search when the caller passes an empty list
+const DEFAULT_SEARCH_EXCLUSIONS: &[&str] = &["Android/data", "Android/obb"];
+
+// Build the `find` prune clause for excluded folders, falling back to DEFAULT_SEARCH_EXCLUSIONS when empty
+// Relative entries match at any depth (`*/<path>`); absolute entries match exactly
+fn build_prune_clause(exclude_paths: &[String]) -> Result<String, String> {
+    let defaults: Vec<String>;
+    let exclude_paths = if exclude_paths.is_empty() {
+        defaults = DEFAULT_SEARCH_EXCLUSIONS.iter().map(|p| p.to_string()).collect();
+        &defaults
+    } else {
+        exclude_paths
+    };
+
+    let mut conditions = Vec::new();
+    for path in exclude_paths {
+        let trimmed = path.trim().trim_end_matches('/');
+        if trimmed.is_empty() || trimmed.contains('\n') {
+            return Err(format!("Invalid exclusion path: '{}'", path));
+        }
+
+        let pattern = if trimmed.starts_with('/') {
+            trimmed.to_string()
+        } else {
+            format!("*/{}", trimmed)
+        };
+        conditions.push(format!("-path '{}'", pattern.replace("'", "'\\''")));
+    }
+
+    Ok(format!("\\( {} \\) -prune -o", conditions.join(" -o ")))
+}
+
+// Build the `find` command used by search_files
+// Uses -iname unless case_sensitive is set, and redirects stderr to /dev/null to suppress permission errors
+fn build_search_command(
+    search_path: &str,
+    pattern: &str,
+    recursive: bool,
+    exclusions: &str,
+    case_sensitive: bool,
+    follow_symlinks: bool,
+) -> String {
+    // Escape single quotes in path and pattern
+    let escaped_path = search_path.replace("'", "'\\''");
+    let escaped_pattern = pattern.replace("'", "'\\''");
+
+    let max_depth_arg = if recursive { "" } else { "-maxdepth 1" };
+    let name_test = if case_sensitive { "-name" } else { "-iname" };
+    // -L traverses symlinked directories; find detects loops itself, but large linked trees slow the search
+    let follow_arg = if follow_symlinks { "-L " } else { "" };
+    format!(
+        "find {}'{}' {} {} {} '*{}*' -exec ls -ld {{}} \\; 2>/dev/null",
+        follow_arg, escaped_path, max_depth_arg, exclusions, name_test, escaped_pattern
+    )
+}
+
+// Search for files on the Android device
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn search_files(
+    app: tauri::AppHandle,
+    device_id: String,
+    search_path: String,
+    pattern: String,
+    recursive: bool,
+    exclude_paths: Vec<String>,
+    case_sensitive: bool,
+    follow_symlinks: bool,
+) -> Result<Vec<FileEntry>, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let exclusions = if recursive {
+        // Only add exclusions for recursive searches
+        build_prune_clause(&exclude_paths)?
+    } else {
+        String::new()
+    };
+    let find_command = build_search_command(
+        &search_path,
+        &pattern,
+        recursive,
+        &exclusions,
+        case_sensitive,
+        follow_symlinks,
+    );
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &find_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute search command: {}", e))?;
+
+    // Don't check exit status - find returns non-zero if it encounters permission errors
+    // but we've redirected stderr to /dev/null and want to process whatever results we got
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let files: Vec<FileEntry> = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| parse_ls_line(line))
+        .collect();
+
+    Ok(files)
+}
+
+// Validate user-supplied find predicates against a whitelist and quote them for the shell
+// Only tests, depth limits and boolean operators are allowed; actions like -exec or -delete are rejected
+fn build_advanced_find_args(raw_find_args: &[String]) -> Result<String, String> {
+    // Digits with an optional leading +/- and optional unit suffix
+    let is_number = |value: &str, allow_sign: bool, suffixes: &str| {
+        let value = if allow_sign { value.trim_start_matches(['+', '-']) } else { value };
+        let digits = value.trim_end_matches(|c| suffixes.contains(c));
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+
+    let mut quoted = Vec::new();
+    let mut args = raw_find_args.iter();
+    while let Some(arg) = args.next() {
+        let takes_value = match arg.as_str() {
+            "!" | "-not" | "-o" | "-or" | "-a" | "-and" | "(" | ")" | "-empty" => false,
+            "-name" | "-iname" | "-path" | "-ipath" | "-type" | "-size" | "-mtime" | "-mmin" | "-maxdepth"
+            | "-mindepth" => true,
+            other => return Err(format!("find argument not allowed: {}", other)),
+        };
+        quoted.push(format!("'{}'", arg));
+        if !takes_value {
+            continue;
+        }
+
+        let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+        let valid = match arg.as_str() {
+            "-type" => matches!(value.as_str(), "f" | "d" | "l"),
+            "-size" => is_number(value, true, "ckMG"),
+            "-mtime" | "-mmin" => is_number(value, true, ""),
+            "-maxdepth" | "-mindepth" => is_number(value, false, ""),
+            _ => !value.is_empty(),
+        };
+        if !valid {
+            return Err(format!("Invalid value for {}: '{}'", arg, value));
+        }
+        quoted.push(format!("'{}'", value.replace("'", "'\\''")));
+    }
+
+    Ok(quoted.join(" "))
+}
+
+// Full find command for advanced_search; predicates are grouped so that `-o` doesn't
+// leave -exec bound to the last alternative only
+fn build_advanced_find_command(search_path: &str, raw_find_args: &[String]) -> Result<String, String> {
+    let predicates = build_advanced_find_args(raw_find_args)?;
+    let escaped_path = search_path.replace("'", "'\\''");
+    let predicates = if predicates.is_empty() { String::new() } else { format!(" \\( {} \\)", predicates) };
+    Ok(format!("find '{}'{} -exec ls -ld {{}} \\; 2>/dev/null", escaped_path, predicates))
+}
+
+// Search with custom find predicates, e.g. ["-type", "f", "-size", "+100M", "-mtime", "-7"]
+// Arguments are whitelisted and quoted, so no raw shell access is possible
+#[tauri::command]
+async fn advanced_search(
+    app: tauri::AppHandle,
+    device_id: String,
+    search_path: String,
+    raw_find_args: Vec<String>,
+) -> Result<Vec<FileEntry>, String> {
+    let find_command = build_advanced_find_command(&search_path, &raw_find_args)?;
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &find_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute search command: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .collect())
+}
+
+// Find files modified within the last N days anywhere under a path, newest first
+#[tauri::command]
+async fn find_recent_files(
+    app: tauri::AppHandle,
+    device_id: String,
+    search_path: String,
+    within_days: u32,
+    limit: usize,
+    operation_id: String,
+) -> Result<Vec<FileEntry>, String> {
+    // -mtime -0 matches nothing, so a zero-day window is a caller mistake rather than an empty result
+    if within_days == 0 {
+        return Err("within_days must be at least 1".to_string());
+    }
+
+    let escaped_path = search_path.replace("'", "'\\''");
+    let find_command = format!(
+        "find '{}' -type f -mtime -{} -exec ls -ld {{}} \\; 2>/dev/null",
+        escaped_path, within_days
+    );
+
+    let stdout = run_cancellable_shell(&app, &device_id, &operation_id, &find_command).await?;
+
+    let mut files: Vec<FileEntry> = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .collect();
+
+    files.sort_by_key(|f| std::cmp::Reverse(f.modified_epoch));
+    files.truncate(limit);
+
+    Ok(files)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrokenSymlink {
+    pub entry: FileEntry,
+    pub target: String,
+}
+
+// Split an `ls -l` symlink name ("link -> target") into the link and its target
+fn split_symlink_name(name: &str) -> (String, Option<String>) {
+    match name.split_once(" -> ") {
+        Some((link, target)) => (link.to_string(), Some(target.to_string())),
+        None => (name.to_string(), None),
+    }
+}
+
+// Find symlinks under a path whose targets no longer exist
+// Entry names are full link paths; cancellable via cancel_operation
+#[tauri::command]
+async fn find_broken_symlinks(
+    app: tauri::AppHandle,
+    device_id: String,
+    search_path: String,
+    operation_id: String,
+) -> Result<Vec<BrokenSymlink>, String> {
+    let escaped_path = search_path.replace("'", "'\\''");
+    let find_command = format!(
+        "find '{}' -type l ! -exec test -e {{}} \\; -exec ls -ld {{}} \\; 2>/dev/null",
+        escaped_path
+    );
+
+    let stdout = run_cancellable_shell(&app, &device_id, &operation_id, &find_command).await?;
+
+    Ok(stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .map(|entry| {
+            let target = entry.symlink_target.clone().unwrap_or_default();
+            BrokenSymlink { entry, target }
+        })
+        .collect())
+}
+
+// Shell pipeline hashing a file's size plus its first and last `sample_bytes` bytes
+fn quick_hash_command(file_path: &str, sample_bytes: u64) -> String {
+    let escaped_path = file_path.replace("'", "'\\''");
+    format!(
+        "f='{path}'; {{ stat -c %s \"$f\"; head -c {n} \"$f\"; tail -c {n} \"$f\"; }} | md5sum",
+        path = escaped_path,
+        n = sample_bytes
+    )
+}
+
+// Fast fingerprint of a file from its size and first/last `sample_bytes` bytes
+// Equal fingerprints only suggest duplicates; confirm with a full hash
+#[tauri::command]
+async fn quick_hash(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    sample_bytes: u64,
+) -> Result<String, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &quick_hash_command(&file_path, sample_bytes.max(1))])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to hash {}: {}", file_path, e))?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 32)
+        .map(|hash| hash.to_lowercase())
+        .ok_or_else(|| format!("Failed to hash {}: {}", file_path, String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+// Groups of at least two paths sharing the same key, each group and the list sorted for stable output
+fn collision_groups(items: Vec<(String, String)>) -> Vec<Vec<String>> {
+    let mut by_key: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, path) in items {
+        by_key.entry(key).or_default().push(path);
+    }
+    let mut groups: Vec<Vec<String>> = by_key
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|mut paths| {
+            paths.sort();
+            paths
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+// Find duplicate files under a path
+// Narrows candidates by size, then by quick_hash, and only full-hashes files whose quick fingerprints collide.
+// Cancellable via cancel_operation while listing.
+#[tauri::command]
+async fn find_duplicates(
+    app: tauri::AppHandle,
+    device_id: String,
+    search_path: String,
+    sample_bytes: u64,
+    operation_id: String,
+) -> Result<Vec<Vec<String>>, String> {
+    let escaped_path = search_path.replace("'", "'\\''");
+    let find_command = format!("find '{}' -type f -size +0 -exec stat -c '%s|%n' {{}} + 2>/dev/null", escaped_path);
+    let stdout = run_cancellable_shell(&app, &device_id, &operation_id, &find_command).await?;
+
+    let sized: Vec<(String, String)> = stdout
+        .lines()
+        .filter_map(|line| line.trim().split_once('|'))
+        .map(|(size, path)| (size.to_string(), path.to_string()))
+        .collect();
+
+    let mut quick = Vec::new();
+    for path in collision_groups(sized).into_iter().flatten() {
+        if let Ok(hash) = quick_hash(app.clone(), device_id.clone(), path.clone(), sample_bytes).await {
+            quick.push((hash, path));
+        }
+    }
+
+    let mut full = Vec::new();
+    for path in collision_groups(quick).into_iter().flatten() {
+        if let Some(hash) = get_device_md5(&app, &device_id, &path).await {
+            full.push((hash, path));
+        }
+    }
+
+    Ok(collision_groups(full))
+}
+
+// Count files (or directories) under a path on the Android device
+#[tauri::command]
+async fn count_files(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    recursive: bool,
+    directories: bool,
+    operation_id: String,
+) -> Result<u64, String> {
+    let escaped_path = path.replace("'", "'\\''");
+    let max_depth_arg = if recursive { "" } else { "-maxdepth 1" };
+    let type_arg = if directories { "d" } else { "f" };
+    let count_command = format!(
+        "find '{}' -mindepth 1 {} -type {} 2>/dev/null | wc -l",
+        escaped_path, max_depth_arg, type_arg
+    );
+
+    let stdout = run_cancellable_shell(&app, &device_id, &operation_id, &count_command).await?;
+
+    stdout
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("Failed to count files in {}", path))
+}
+
+// Deepest level get_directory_tree will descend to
+const MAX_TREE_DEPTH: u32 = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectoryTreeNode {
+    pub path: String,
+    pub entry: FileEntry,
+    pub children: Vec<DirectoryTreeNode>,
+}
+
+// Nest flat `find ... -exec ls -ld` entries (whose names are full paths) under `root`
+// Entry names are reduced to their base name; the full path is kept on the node
+fn build_directory_tree(root: &str, entries: Vec<FileEntry>) -> Vec<DirectoryTreeNode> {
+    let mut by_parent: HashMap<String, Vec<(String, FileEntry)>> = HashMap::new();
+    for mut entry in entries {
+        let path = entry.name.trim_end_matches('/').to_string();
+        let Some((parent, name)) = path.rsplit_once('/') else { continue };
+        let parent = if parent.is_empty() { "/".to_string() } else { parent.to_string() };
+        entry.name = name.to_string();
+        by_parent.entry(parent).or_default().push((path, entry));
+    }
+
+    fn attach(parent: &str, by_parent: &mut HashMap<String, Vec<(String, FileEntry)>>) -> Vec<DirectoryTreeNode> {
+        let mut children: Vec<DirectoryTreeNode> = by_parent
+            .remove(parent)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(path, entry)| {
+                let children = if entry.is_directory { attach(&path, by_parent) } else { Vec::new() };
+                DirectoryTreeNode { path, entry, children }
+            })
+            .collect();
+        children.sort_by(|a, b| compare_by_type(&a.entry, &b.entry));
+        children
+    }
+
+    let root = root.trim_end_matches('/');
+    attach(if root.is_empty() { "/" } else { root }, &mut by_parent)
+}
+
+// List a directory recursively as a nested tree, down to `max_depth` levels (capped at MAX_TREE_DEPTH)
+// Cancellable via cancel_operation
+#[tauri::command]
+async fn get_directory_tree(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    max_depth: u32,
+    operation_id: String,
+) -> Result<Vec<DirectoryTreeNode>, String> {
+    let depth = max_depth.clamp(1, MAX_TREE_DEPTH);
+    let escaped_path = path.trim_end_matches('/').replace("'", "'\\''");
+    let find_command = format!(
+        "find '{}' -mindepth 1 -maxdepth {} -exec ls -ld {{}} \\; 2>/dev/null",
+        escaped_path, depth
+    );
+
+    let stdout = run_cancellable_shell(&app, &device_id, &operation_id, &find_command).await?;
+    let entries: Vec<FileEntry> = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .collect();
+
+    Ok(build_directory_tree(&path, entries))
+}
+
+// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Render (full path, entry) pairs as CSV with a header row
+fn listing_to_csv(entries: &[(String, FileEntry)]) -> String {
+    let mut csv = String::from("name,full_path,size_bytes,modified,type,permissions\n");
+    for (full_path, entry) in entries {
+        let file_type = if entry.is_symlink {
+            "symlink"
+        } else if entry.is_directory {
+            "directory"
+        } else {
+            "file"
+        };
+        let size_bytes = entry.size.parse::<u64>().map(|s| s.to_string()).unwrap_or_default();
+        let row = [
+            csv_field(&entry.name),
+            csv_field(full_path),
+            size_bytes,
+            csv_field(&entry.date),
+            file_type.to_string(),
+            csv_field(&entry.permissions),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Write a directory listing (optionally recursive) to a local CSV file for auditing in a spreadsheet
+// Returns the number of entries written
+#[tauri::command]
+async fn export_listing_csv(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    recursive: bool,
+    local_dest: String,
+) -> Result<usize, String> {
+    let base = path.trim_end_matches('/').to_string();
+
+    let entries: Vec<(String, FileEntry)> = if recursive {
+        let escaped_path = base.replace("'", "'\\''");
+        let find_command = format!("find '{}' -mindepth 1 -exec ls -ld {{}} \\; 2>/dev/null", escaped_path);
+        let output = app
+            .shell()
+            .command(get_adb_command())
+            .args(["-s", &device_id, "shell", &find_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to list files: {}", e))?;
+
+        // find prints full paths, so split off the base name for the name column
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(parse_ls_line)
+            .map(|mut entry| {
+                let full_path = entry.name.clone();
+                entry.name = full_path.rsplit('/').next().unwrap_or_default().to_string();
+                (full_path, entry)
+            })
+            .collect()
+    } else {
+        list_files(app.clone(), device_id.clone(), path.clone(), None)
+            .await?
+            .into_iter()
+            .filter(|entry| entry.name != "." && entry.name != "..")
+            .map(|entry| (format!("{}/{}", base, entry.name), entry))
+            .collect()
+    };
+
+    fs::write(&local_dest, listing_to_csv(&entries))
+        .map_err(|e| format!("Failed to write {}: {}", local_dest, e))?;
+
+    Ok(entries.len())
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ManifestEntry {
+    pub relative_path: String,
+    pub hash: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestProgress {
+    pub operation_id: String,
+    pub completed: u64,
+    pub total: u64,
+}
+
+// Map a checksum algorithm name to the toybox/coreutils tool that computes it
+fn checksum_tool(algorithm: &str) -> Result<&'static str, String> {
+    match algorithm.to_lowercase().as_str() {
+        "md5" => Ok("md5sum"),
+        "sha1" => Ok("sha1sum"),
+        "sha256" => Ok("sha256sum"),
+        other => Err(format!("Unsupported checksum algorithm: {}", other)),
+    }
+}
+
+// Parse one "<size> <hash>  ./<relative path>" line printed by the manifest command
+fn parse_manifest_line(line: &str) -> Option<ManifestEntry> {
+    let (size, rest) = line.trim_start().split_once(' ')?;
+    let (hash, path) = rest.split_once("  ")?;
+    let relative_path = path.trim_end_matches(['\r', '\n']).trim_start_matches("./");
+    if relative_path.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(ManifestEntry {
+        relative_path: relative_path.to_string(),
+        hash: hash.to_lowercase(),
+        size: size.parse().ok()?,
+    })
+}
+
+// Hash every file under a folder into a manifest of { relative_path, hash, size }
+// Emits `manifest-progress` as files are hashed, is cancellable, and optionally writes the manifest as JSON
+#[tauri::command]
+async fn generate_checksum_manifest(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    algorithm: String,
+    operation_id: String,
+    local_dest: Option<String>,
+) -> Result<Vec<ManifestEntry>, String> {
+    let tool = checksum_tool(&algorithm)?;
+    let escaped_path = path.replace("'", "'\\''");
+
+    let count_command = format!("cd '{}' && find . -type f 2>/dev/null | wc -l", escaped_path);
+    let total = run_cancellable_shell(&app, &device_id, &operation_id, &count_command)
+        .await?
+        .trim()
+        .parse::<u64>()
+        .unwrap_or(0);
+
+    // Batch files per `sh -c` invocation; each prints "<size> <hash>  ./<path>"
+    let manifest_command = format!(
+        "cd '{}' && find . -type f -exec sh -c 'for f; do printf \"%s \" \"$(stat -c %s \"$f\")\"; {} \"$f\"; done' sh {{}} + 2>/dev/null",
+        escaped_path, tool
+    );
+
+    let mut entries = Vec::new();
+    let mut last_report = std::time::Instant::now();
+    run_cancellable_shell_lines(&app, &device_id, &operation_id, &manifest_command, |line| {
+        if let Some(entry) = parse_manifest_line(line) {
+            entries.push(entry);
+            if last_report.elapsed() >= Duration::from_millis(250) {
+                last_report = std::time::Instant::now();
+                let _ = app.emit("manifest-progress", ManifestProgress {
+                    operation_id: operation_id.clone(),
+                    completed: entries.len() as u64,
+                    total,
+                });
+            }
+        }
+    })
+    .await?;
+
+    entries.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    let _ = app.emit("manifest-progress", ManifestProgress {
+        operation_id: operation_id.clone(),
+        completed: entries.len() as u64,
+        total: total.max(entries.len() as u64),
+    });
+
+    if let Some(local_dest) = local_dest {
+        let content = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        fs::write(&local_dest, content).map_err(|e| format!("Failed to write {}: {}", local_dest, e))?;
+    }
+
+    Ok(entries)
+}
+
+// Get storage information from the Android device
+#[tauri::command]
+async fn get_storage_info(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+) -> Result<StorageInfo, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Escape single quotes in path
+    let escaped_path = path.replace("'", "'\\''");
+
+    // Use df command to get storage stats for the given path
+    let df_command = format!("df '{}'", escaped_path);
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &df_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to get storage info: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Storage info failed: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Parse df output - format varies but typically:
+    // Filesystem     1K-blocks    Used Available Use% Mounted on
+    // /dev/block/... 123456789 45678901 77777888  37% /storage/emulated
+
+    for line in stdout.lines().skip(1) {
+        // Skip header
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 4 {
+            // Try to parse the size fields (in KB typically)
+            if let (Ok(total_kb), Ok(used_kb), Ok(free_kb)) = (
+                parts[1].parse::<u64>(),
+                parts[2].parse::<u64>(),
+                parts[3].parse::<u64>(),
+            ) {
+                let total_bytes = total_kb * 1024;
+                let used_bytes = used_kb * 1024;
+                let free_bytes = free_kb * 1024;
+                let percentage_used = if total_bytes > 0 {
+                    (used_bytes as f64 / total_bytes as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                return Ok(StorageInfo {
+                    total_bytes,
+                    used_bytes,
+                    free_bytes,
+                    percentage_used,
+                });
+            }
+        }
+    }
+
+    Err("Failed to parse storage information".to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VolumeUsage {
+    pub filesystem: String,
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub use_percent: f64,
+}
+
+// Returns true for kernel pseudo filesystems that aren't real storage
+fn is_pseudo_filesystem(filesystem: &str) -> bool {
+    matches!(
+        filesystem,
+        "tmpfs" | "proc" | "sysfs" | "devpts" | "none" | "cgroup" | "selinuxfs"
+            | "debugfs" | "tracefs" | "pstore" | "bpf" | "configfs" | "functionfs"
+    )
+}
+
+// Parse `df -k` output into one entry per real volume
+// Filesystem     1K-blocks    Used Available Use% Mounted on
+// /dev/fuse      114248172 47213928  66903908  42% /storage/emulated
+fn parse_df_output(output: &str) -> Vec<VolumeUsage> {
+    let mut volumes: Vec<VolumeUsage> = Vec::new();
+    let mut pending: Option<String> = None;
+
+    for line in output.lines().skip(1) {
+        // Long filesystem names make some df versions wrap the rest of the row onto the next line
+        let line = match pending.take() {
+            Some(prefix) => format!("{} {}", prefix, line.trim()),
+            None => line.to_string(),
+        };
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() == 1 {
+            pending = Some(parts[0].to_string());
+            continue;
+        }
+        if parts.len() < 6 {
+            continue;
+        }
+
+        let filesystem = parts[0];
+        if is_pseudo_filesystem(filesystem) {
+            continue;
+        }
+
+        let (total_kb, used_kb, free_kb) = match (
+            parts[1].parse::<u64>(),
+            parts[2].parse::<u64>(),
+            parts[3].parse::<u64>(),
+        ) {
+            (Ok(total), Ok(used), Ok(free)) => (total, used, free),
+            _ => continue,
+        };
+        if total_kb == 0 {
+            continue;
+        }
+
+        let mount_point = parts[5..].join(" ");
+        if volumes.iter().any(|v| v.mount_point == mount_point) {
+            continue;
+        }
+
+        volumes.push(VolumeUsage {
+            filesystem: filesystem.to_string(),
+            mount_point,
+            total_bytes: total_kb * 1024,
+            used_bytes: used_kb * 1024,
+            available_bytes: free_kb * 1024,
+            use_percent: (used_kb as f64 / total_kb as f64) * 100.0,
+        });
+    }
+
+    volumes
+}
+
+// Get storage usage for every mounted volume on the Android device
+#[tauri::command]
+async fn get_all_storage_usage(app: tauri::AppHandle, device_id: String) -> Result<Vec<VolumeUsage>, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", "df -k"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to get storage info: {}", e))?;
+
+    // df exits non-zero when a single mount can't be read, so parse whatever was printed
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let volumes = parse_df_output(&stdout);
+    if volumes.is_empty() {
+        return Err(format!("Storage info failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(volumes)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StorageCategory {
+    pub category: String,
+    pub total_bytes: u64,
+    pub file_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageAnalysisProgress {
+    pub operation_id: String,
+    pub files_scanned: u64,
+    pub bytes_scanned: u64,
+}
+
+// Storage breakdown category for a lowercase extension
+fn storage_category(ext: &str) -> &'static str {
+    match ext {
+        "apk" | "apks" | "xapk" | "aab" => "apks",
+        "heic" | "heif" | "dng" => "images",
+        _ if is_image_extension(ext) => "images",
+        _ if is_video_extension(ext) => "videos",
+        _ => match placeholder_for_extension(ext) {
+            "placeholder:audio" => "audio",
+            "placeholder:document" | "placeholder:text" | "placeholder:code" => "documents",
+            _ => "other",
+        },
+    }
+}
+
+// Aggregate "<size>|<path>" lines into per-category totals, largest first
+fn aggregate_storage_by_type<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<StorageCategory> {
+    let mut totals: HashMap<&'static str, (u64, u64)> = HashMap::new();
+    for line in lines {
+        let Some((size, path)) = line.trim().split_once('|') else { continue };
+        let Ok(size) = size.parse::<u64>() else { continue };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let ext = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+
+        let entry = totals.entry(storage_category(&ext)).or_default();
+        entry.0 += size;
+        entry.1 += 1;
+    }
+
+    let mut breakdown: Vec<StorageCategory> = totals
+        .into_iter()
+        .map(|(category, (total_bytes, file_count))| StorageCategory {
+            category: category.to_string(),
+            total_bytes,
+            file_count,
+        })
+        .collect();
+    breakdown.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then_with(|| a.category.cmp(&b.category)));
+    breakdown
+}
+
+// Break down the space used under a path by file type (images, videos, audio, documents, apks, other)
+// One find+stat pass on the device; emits `storage-analysis-progress` and is cancellable via cancel_operation
+#[tauri::command]
+async fn analyze_storage_by_type(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    operation_id: String,
+) -> Result<Vec<StorageCategory>, String> {
+    let escaped_path = path.replace("'", "'\\''");
+    let find_command = format!("find '{}' -type f -exec stat -c '%s|%n' {{}} + 2>/dev/null", escaped_path);
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut bytes_scanned: u64 = 0;
+    let mut last_report = std::time::Instant::now();
+    run_cancellable_shell_lines(&app, &device_id, &operation_id, &find_command, |line| {
+        if let Some((size, _)) = line.split_once('|') {
+            bytes_scanned += size.trim().parse::<u64>().unwrap_or(0);
+        }
+        lines.push(line.to_string());
+
+        if last_report.elapsed() >= Duration::from_millis(500) {
+            last_report = std::time::Instant::now();
+            let _ = app.emit("storage-analysis-progress", StorageAnalysisProgress {
+                operation_id: operation_id.clone(),
+                files_scanned: lines.len() as u64,
+                bytes_scanned,
+            });
+        }
+    })
+    .await?;
+
+    let _ = app.emit("storage-analysis-progress", StorageAnalysisProgress {
+        operation_id: operation_id.clone(),
+        files_scanned: lines.len() as u64,
+        bytes_scanned,
+    });
+
+    Ok(aggregate_storage_by_type(lines.iter().map(String::as_str)))
+}
+
+// Device folder used for temporary archives; writable by the adb shell user
+const DEVICE_TEMP_DIR: &str = "/data/local/tmp";
+
+// Compress a device folder into a single .tar.gz, pull it to `local_dest`, then remove the device copy
+// Fails early if the device temp folder can't hold an archive the size of the uncompressed folder
+#[tauri::command]
+async fn archive_and_pull(
+    app: tauri::AppHandle,
+    device_id: String,
+    remote_path: String,
+    local_dest: String,
+) -> Result<String, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let remote_path = remote_path.trim_end_matches('/').to_string();
+    let (parent, folder_name) = remote_path
+        .rsplit_once('/')
+        .filter(|(_, name)| !name.is_empty())
+        .ok_or_else(|| format!("Invalid folder path: {}", remote_path))?;
+    let parent = if parent.is_empty() { "/" } else { parent };
+    let escaped_parent = parent.replace("'", "'\\''");
+    let escaped_name = folder_name.replace("'", "'\\''");
+
+    // Worst case the archive is as large as the folder itself
+    let du_command = format!("du -sk '{}/{}' 2>/dev/null", escaped_parent, escaped_name);
+    let size_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &du_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to measure folder: {}", e))?;
+    let folder_kb = String::from_utf8_lossy(&size_output.stdout)
+        .split_whitespace()
+        .next()
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .ok_or_else(|| format!("Folder not found: {}", remote_path))?;
+
+    let df_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("df -k {}", DEVICE_TEMP_DIR)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check device free space: {}", e))?;
+    if let Some(volume) = parse_df_output(&String::from_utf8_lossy(&df_output.stdout)).first() {
+        if volume.available_bytes < folder_kb * 1024 {
+            return Err(format!(
+                "Not enough space on device to create archive: need {} bytes, {} available",
+                folder_kb * 1024,
+                volume.available_bytes
+            ));
+        }
+    }
+
+    let archive_path = format!("{}/droiddock_archive_{}.tar.gz", DEVICE_TEMP_DIR, now_millis());
+    let tar_command = format!("tar -czf '{}' -C '{}' '{}'", archive_path, escaped_parent, escaped_name);
+
+    let result = async {
+        let tar_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &tar_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to create archive: {}", e))?;
+        if !tar_output.status.success() {
+            return Err(format!("Failed to create archive: {}", String::from_utf8_lossy(&tar_output.stderr)));
+        }
+
+        download_file(app.clone(), device_id.clone(), archive_path.clone(), local_dest.clone(), false).await?;
+        Ok(local_dest.clone())
+    }
+    .await;
+
+    // Always remove the device-side archive, even if creating or pulling it failed
+    let _ = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("rm -f '{}'", archive_path)])
+        .output()
+        .await;
+
+    result
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalFreeSpace {
+    pub available_bytes: u64,
+    pub required_bytes: u64,
+    pub fits: bool,
+}
+
+// Check whether the Mac volume holding local_path has room for a download
+#[tauri::command]
+fn check_local_free_space(local_path: String, required_bytes: u64) -> Result<LocalFreeSpace, String> {
+    let available_bytes = local_available_space(std::path::Path::new(&local_path))
+        .map_err(|e| format!("Failed to check free space for {}: {}", local_path, e))?;
+
+    Ok(LocalFreeSpace {
+        available_bytes,
+        required_bytes,
+        fits: required_bytes <= available_bytes,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MountInfo {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub options: Vec<String>,
+    pub read_only: bool,
+}
+
+// Parse mount tables in either /proc/mounts form or `mount` command form
+// /proc/mounts: /dev/block/dm-0 /system ext4 ro,seclabel,relatime 0 0
+// mount:        /dev/block/dm-0 on /system type ext4 (ro,seclabel,relatime)
+fn parse_mounts(output: &str) -> Vec<MountInfo> {
+    // /proc/mounts escapes whitespace in paths as octal sequences
+    let unescape = |field: &str| field.replace("\\040", " ").replace("\\011", "\t");
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (device, mount_point, fs_type, options) =
+                if parts.len() >= 6 && parts[1] == "on" && parts[3] == "type" {
+                    (parts[0], parts[2], parts[4], parts[5].trim_start_matches('(').trim_end_matches(')'))
+                } else if parts.len() >= 4 {
+                    (parts[0], parts[1], parts[2], parts[3])
+                } else {
+                    return None;
+                };
+
+            let options: Vec<String> = options.split(',').map(|o| o.to_string()).collect();
+            let read_only = options.iter().any(|o| o == "ro");
+
+            Some(MountInfo {
+                device: unescape(device),
+                mount_point: unescape(mount_point),
+                fs_type: fs_type.to_string(),
+                options,
+                read_only,
+            })
+        })
+        .collect()
+}
+
+// List mounted filesystems on the Android device with their read-only/read-write state
+#[tauri::command]
+async fn get_mount_info(app: tauri::AppHandle, device_id: String) -> Result<Vec<MountInfo>, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    for mount_command in ["cat /proc/mounts", "mount"] {
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", mount_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to get mount info: {}", e))?;
+
+        let mounts = parse_mounts(&String::from_utf8_lossy(&output.stdout));
+        if !mounts.is_empty() {
+            return Ok(mounts);
+        }
+    }
+
+    Err("Failed to read mount information".to_string())
+}
+
+// Find the mount a path lives on (the longest matching mount point)
+fn mount_for_path<'a>(mounts: &'a [MountInfo], path: &str) -> Option<&'a MountInfo> {
+    mounts
+        .iter()
+        .filter(|m| {
+            let mount_point = m.mount_point.trim_end_matches('/');
+            path == mount_point || path.starts_with(&format!("{}/", mount_point)) || m.mount_point == "/"
+        })
+        .max_by_key(|m| m.mount_point.len())
+}
+
+// Disk usage of a device path in bytes (`du -sk` granularity)
+async fn device_used_bytes(app: &tauri::AppHandle, device_id: &str, path: &str) -> u64 {
+    let escaped_path = path.replace("'", "'\\''");
+    app.shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &format!("du -sk '{}' 2>/dev/null", escaped_path)])
+        .output()
+        .await
+        .ok()
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .next()
+                .and_then(|kb| kb.parse::<u64>().ok())
+        })
+        .map(|kb| kb * 1024)
+        .unwrap_or(0)
+}
+
+// md5 of every file under a device path, as sorted "hash  relative_path" lines (just the hash for a file)
+async fn device_checksum_listing(app: &tauri::AppHandle, device_id: &str, path: &str) -> Result<String, String> {
+    let escaped_path = path.replace("'", "'\\''");
+    let checksum_command = format!(
+        "if [ -d '{p}' ]; then cd '{p}' && find . -type f -exec md5sum {{}} \\; | sort; else md5sum '{p}' | cut -d' ' -f1; fi",
+        p = escaped_path
+    );
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &checksum_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to checksum {}: {}", path, e))?;
+
+    if !output.status.success() {
+        return Err(format!("Failed to checksum {}: {}", path, String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Where a move lands: like mv, an existing folder as destination receives the source under its own name
+fn move_target(source: &str, destination: &str, destination_is_dir: bool) -> String {
+    if !destination_is_dir {
+        return destination.to_string();
+    }
+    let name = source.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+    format!("{}/{}", destination.trim_end_matches('/'), name)
+}
+
+// Copy source to destination on the device with `transfer-progress` events, verify checksums,
+// and only then delete the source; a failed or mismatched copy is removed and the source kept.
+// A file that was already at the destination is overwritten but never removed.
+async fn copy_verify_delete(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    source: &str,
+    destination: &str,
+) -> Result<(), String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let escaped_source = source.replace("'", "'\\''");
+
+    let dir_check = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, "shell", &format!("test -d '{}' && echo dir", destination.replace("'", "'\\''"))])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check destination: {}", e))?;
+    let destination = move_target(source, destination, String::from_utf8_lossy(&dir_check.stdout).contains("dir"));
+    let escaped_destination = destination.replace("'", "'\\''");
+
+    let target_check = shell
+        .command(&adb_cmd)
+        .args([
+            "-s",
+            device_id,
+            "shell",
+            &format!("if [ -d '{0}' ]; then echo dir; elif [ -e '{0}' ]; then echo exists; fi", escaped_destination),
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check destination: {}", e))?;
+    let target_check = String::from_utf8_lossy(&target_check.stdout);
+    // cp -r would nest the source inside an existing folder, so refuse rather than merge
+    if target_check.contains("dir") {
+        return Err(format!("Destination already exists: {}", destination));
+    }
+    let target_existed = target_check.contains("exists");
+
+    // Copy, reporting progress by polling the size of the copy against the source
+    let total_bytes = device_used_bytes(app, device_id, source).await;
+    let started = std::time::Instant::now();
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let reporter = {
+        let app = app.clone();
+        let done = done.clone();
+        let device_id = device_id.to_string();
+        let source = source.to_string();
+        let destination = destination.clone();
+        tauri::async_runtime::spawn(async move {
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                let bytes = device_used_bytes(&app, &device_id, &destination).await;
+                let progress = compute_transfer_progress(&source, bytes, total_bytes, started.elapsed().as_secs_f64());
+                let _ = app.emit("transfer-progress", progress);
+            }
+        })
+    };
+
+    // Preserve only timestamps; ownership can't be set on FAT/exFAT SD cards and would fail `cp -p`
+    let copy_command = format!("cp -r --preserve=timestamps '{}' '{}'", escaped_source, escaped_destination);
+    let copy_output = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, "shell", &copy_command])
+        .output()
+        .await;
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    let _ = reporter.await;
+
+    let remove_copy = format!("rm -rf '{}'", escaped_destination);
+    let discard_copy = || async {
+        if !target_existed {
+            let _ = shell.command(&adb_cmd).args(["-s", device_id, "shell", &remove_copy]).output().await;
+        }
+    };
+    let copy_result = match copy_output {
+        Ok(o) if o.status.success() => Ok(()),
+        Ok(o) => Err(format!("Copy failed: {}", String::from_utf8_lossy(&o.stderr))),
+        Err(e) => Err(format!("Failed to copy: {}", e)),
+    };
+    if let Err(e) = copy_result {
+        discard_copy().await;
+        return Err(e);
+    }
+
+    // Only delete the source once the copy is proven identical
+    let source_sums = device_checksum_listing(app, device_id, source).await?;
+    let copy_sums = device_checksum_listing(app, device_id, &destination).await?;
+    if source_sums != copy_sums {
+        discard_copy().await;
+        return Err(format!("Verification failed moving {}; the source was kept", source));
+    }
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, "shell", &format!("rm -rf '{}'", escaped_source)])
+        .output()
+        .await
+        .map_err(|e| format!("Copied but failed to remove source: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Copied to {} but failed to remove source: {}",
+            destination,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+// Move a file or folder into `dest_dir`, copying safely when the destination is on another volume
+// Cross-volume moves copy with `transfer-progress` events, verify checksums, and only then delete the source
+// Returns the new path
+#[tauri::command]
+async fn move_across_volumes(
+    app: tauri::AppHandle,
+    device_id: String,
+    source: String,
+    dest_dir: String,
+) -> Result<String, String> {
+    let source = source.trim_end_matches('/').to_string();
+    let name = source
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| format!("Invalid source path: {}", source))?;
+    let destination = format!("{}/{}", dest_dir.trim_end_matches('/'), name);
+
+    if is_critical_system_path(&source) {
+        return Err(format!("Cannot move critical system path: {}", source));
+    }
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let escaped_source = source.replace("'", "'\\''");
+    let escaped_destination = destination.replace("'", "'\\''");
+
+    let exists = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("[ -e '{}' ] && echo exists", escaped_destination)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check destination: {}", e))?;
+    if String::from_utf8_lossy(&exists.stdout).contains("exists") {
+        return Err(format!("Destination already exists: {}", destination));
+    }
+
+    // Compare filesystems of the real paths, since /sdcard and friends are symlinks
+    let mounts = get_mount_info(app.clone(), device_id.clone()).await?;
+    let real_source = resolve_device_path(&app, &device_id, &source).await;
+    let real_dest_dir = resolve_device_path(&app, &device_id, &dest_dir).await;
+    let same_volume = match (mount_for_path(&mounts, &real_source), mount_for_path(&mounts, &real_dest_dir)) {
+        (Some(a), Some(b)) => a.mount_point == b.mount_point,
+        _ => false,
+    };
+
+    if same_volume {
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &format!("mv '{}' '{}'", escaped_source, escaped_destination)])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to move: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("Move failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+        return Ok(destination);
+    }
+
+    copy_verify_delete(&app, &device_id, &source, &destination).await?;
+    Ok(destination)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhotoBackupResult {
+    pub copied: Vec<String>,
+    pub newest_epoch: Option<u64>,
+    pub errors: HashMap<String, String>,
+}
+
+// Keep image/video entries modified at or after `since_epoch`
+// Also returns the newest modification time among them, for the next incremental run
+fn select_media_since(files: Vec<FileEntry>, since_epoch: u64) -> (Vec<FileEntry>, Option<u64>) {
+    let is_media = |f: &FileEntry| {
+        f.mime_type
+            .as_deref()
+            .is_some_and(|mime| mime.starts_with("image/") || mime.starts_with("video/"))
+    };
+
+    let selected: Vec<FileEntry> = files
+        .into_iter()
+        .filter(|f| !f.is_directory && is_media(f))
+        .filter(|f| f.modified_epoch.is_some_and(|epoch| epoch >= since_epoch))
+        .collect();
+    let newest = selected.iter().filter_map(|f| f.modified_epoch).max();
+
+    (selected, newest)
+}
+
+// Don't let the next incremental run start after a file that failed to copy
+fn hold_back_epoch(newest: Option<u64>, oldest_failed: Option<u64>) -> Option<u64> {
+    match (newest, oldest_failed) {
+        (Some(newest), Some(failed)) => Some(newest.min(failed)),
+        (newest, _) => newest,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FileStat {
+    pub path: String,
+    pub file_type: String,
+    pub size: u64,
+    pub blocks: u64,
+    pub device: String,
+    pub inode: u64,
+    pub links: u64,
+    pub permissions_octal: String,
+    pub permissions: String,
+    pub owner: String,
+    pub group: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub accessed_epoch: u64,
+    pub modified_epoch: u64,
+    pub changed_epoch: u64,
+}
+
+// Fields requested from `stat -c`; the default stat layout differs between toybox and coreutils,
+// so an explicit format keeps parsing identical on every device
+const FILE_STAT_FORMAT: &str = "%F|%s|%b|%D|%i|%h|%a|%A|%U|%G|%u|%g|%X|%Y|%Z";
+
+// Parse one line of `stat -c FILE_STAT_FORMAT` output
+fn parse_file_stat(path: &str, output: &str) -> Option<FileStat> {
+    let fields: Vec<&str> = output.trim().split('|').collect();
+    if fields.len() != 15 {
+        return None;
+    }
+
+    Some(FileStat {
+        path: path.to_string(),
+        file_type: fields[0].to_string(),
+        size: fields[1].parse().ok()?,
+        blocks: fields[2].parse().ok()?,
+        device: fields[3].to_string(),
+        inode: fields[4].parse().ok()?,
+        links: fields[5].parse().ok()?,
+        // Some toybox builds print the octal mode without padding
+        permissions_octal: format!("{:0>4}", fields[6]),
+        permissions: fields[7].to_string(),
+        owner: fields[8].to_string(),
+        group: fields[9].to_string(),
+        uid: fields[10].parse().ok()?,
+        gid: fields[11].parse().ok()?,
+        accessed_epoch: fields[12].parse().ok()?,
+        modified_epoch: fields[13].parse().ok()?,
+        changed_epoch: fields[14].parse().ok()?,
+    })
+}
+
+// Get detailed stat information for a file, for the "Properties" dialog
+#[tauri::command]
+async fn get_file_stat(app: tauri::AppHandle, device_id: String, file_path: String) -> Result<FileStat, String> {
+    let escaped_path = file_path.replace("'", "'\\''");
+    let stat_command = format!("stat -c '{}' '{}'", FILE_STAT_FORMAT, escaped_path);
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &stat_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to stat file: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No such file") || stdout.contains("No such file") {
+            return Err(format!("File not found: {}", file_path));
+        }
+        return Err(format!("stat failed: {}{}", stdout.trim(), stderr.trim()));
+    }
+
+    parse_file_stat(&file_path, &stdout)
+        .ok_or_else(|| format!("Unexpected stat output: {}", stdout.trim()))
+}
+
+// Transfers started with a transfer_id, mapped to whether cancel_transfer was called for them
+// Batch transfers stay registered between files, when there is no adb child in ACTIVE_JOBS to kill
+static ACTIVE_TRANSFERS: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Keeps a transfer_id cancellable through cancel_transfer until dropped
+struct TransferRegistration(String);
+
+impl TransferRegistration {
+    fn new(transfer_id: &str) -> Self {
+        if let Ok(mut transfers) = ACTIVE_TRANSFERS.lock() {
+            transfers.insert(transfer_id.to_string(), false);
+        }
+        TransferRegistration(transfer_id.to_string())
+    }
+
+    fn is_cancelled(&self) -> bool {
+        is_transfer_cancelled(&self.0)
+    }
+}
+
+impl Drop for TransferRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut transfers) = ACTIVE_TRANSFERS.lock() {
+            transfers.remove(&self.0);
+        }
+    }
+}
+
+fn is_transfer_cancelled(transfer_id: &str) -> bool {
+    ACTIVE_TRANSFERS
+        .lock()
+        .ok()
+        .and_then(|transfers| transfers.get(transfer_id).copied())
+        .unwrap_or(false)
+}
+
+// Run `adb pull`/`adb push` and return (succeeded, stderr)
+// With a transfer_id the process is tracked for cancel_transfer, and `transfer-progress` events are emitted by
+// polling the destination's size (the local file for pulls, `stat` on the device for pushes), since adb only prints
+// its percentages to a terminal. Returns Err(OPERATION_CANCELLED) if cancel_transfer stopped it.
+async fn run_adb_transfer(
+    app: &tauri::AppHandle,
+    args: [&str; 5],
+    transfer_id: Option<&str>,
+    total_bytes: u64,
+) -> Result<(bool, String), String> {
+    let command = app.shell().command(get_adb_command()).args(args);
+    let Some(transfer_id) = transfer_id else {
+        let output = command
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+        return Ok((output.status.success(), String::from_utf8_lossy(&output.stderr).to_string()));
+    };
+
+    if is_transfer_cancelled(transfer_id) {
+        return Err(OPERATION_CANCELLED.to_string());
+    }
+    let (mut rx, child) = command
+        .spawn()
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+    let job_id = register_job(Some(transfer_id), args[2], Some(args[1]), child);
+
+    let started = std::time::Instant::now();
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let reporter = {
+        let app = app.clone();
+        let done = done.clone();
+        let transfer_id = transfer_id.to_string();
+        let device_id = args[1].to_string();
+        let is_pull = args[2] == "pull";
+        let destination = args[4].to_string();
+        tauri::async_runtime::spawn(async move {
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                let bytes = if is_pull {
+                    fs::metadata(&destination).map(|m| m.len()).unwrap_or(0)
+                } else {
+                    get_remote_file_size(&app, &device_id, &destination).await.unwrap_or(0)
+                };
+                let progress = compute_transfer_progress(&transfer_id, bytes, total_bytes, started.elapsed().as_secs_f64());
+                let _ = app.emit("transfer-progress", progress);
+            }
+        })
+    };
+
+    let mut stderr = String::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stderr(line) => stderr.push_str(&String::from_utf8_lossy(&line)),
+            CommandEvent::Terminated(payload) => exit_code = payload.code,
+            _ => {}
+        }
+    }
+
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    let _ = reporter.await;
+
+    // cancel_transfer removes the entry before killing, so a missing entry means we were cancelled
+    if unregister_job(&job_id).is_none() || is_transfer_cancelled(transfer_id) {
+        return Err(OPERATION_CANCELLED.to_string());
+    }
+    Ok((exit_code == Some(0), stderr))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferComplete {
+    pub transfer_id: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferError {
+    pub transfer_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferCancelled {
+    pub transfer_id: String,
+}
+
+// End a pull_file/push_file event stream with `transfer-complete`, `transfer-cancelled` or `transfer-error`
+fn emit_transfer_outcome(app: &tauri::AppHandle, transfer_id: &str, result: &Result<u64, String>) {
+    let _ = match result {
+        Ok(bytes) => app.emit("transfer-complete", TransferComplete {
+            transfer_id: transfer_id.to_string(),
+            bytes: *bytes,
+        }),
+        Err(error) if error == OPERATION_CANCELLED => {
+            emit_transfer_cancelled(app, transfer_id);
+            Ok(())
+        }
+        Err(error) => app.emit("transfer-error", TransferError {
+            transfer_id: transfer_id.to_string(),
+            error: error.clone(),
+        }),
+    };
+}
+
+fn emit_transfer_cancelled(app: &tauri::AppHandle, transfer_id: &str) {
+    let _ = app.emit("transfer-cancelled", TransferCancelled {
+        transfer_id: transfer_id.to_string(),
+    });
+}
+
+// Remove what a cancelled pull left behind: a truncated file, or a half-filled folder it created
+fn remove_partial_download(path: &std::path::Path, existed: bool) {
+    if path.is_file() {
+        let _ = fs::remove_file(path);
+    } else if path.is_dir() && !existed {
+        let _ = fs::remove_dir_all(path);
+    }
+}
+
+// Stop a transfer started with this transfer_id (pull_file, push_file, pull_files, push_directory,
+// backup_device); returns false if it had already finished
+// The transfer itself removes its partial destination and emits `transfer-cancelled`
+#[tauri::command]
+fn cancel_transfer(transfer_id: String) -> Result<bool, String> {
+    let registered = ACTIVE_TRANSFERS
+        .lock()
+        .map(|mut transfers| transfers.get_mut(&transfer_id).map(|cancelled| *cancelled = true).is_some())
+        .unwrap_or(false);
+    Ok(kill_job(transfer_id)? || registered)
+}
+
+// Download a file from the Android device to the local filesystem
+#[tauri::command]
+async fn download_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    device_path: String,
+    local_path: String,
+    skip_existing: bool,
+) -> Result<String, String> {
+    download_file_inner(&app, &device_id, &device_path, &local_path, skip_existing, None).await
+}
+
+// download_file, optionally streaming `transfer-progress` events for transfer_id
+async fn download_file_inner(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    device_path: &str,
+    local_path: &str,
+    skip_existing: bool,
+    transfer_id: Option<&str>,
+) -> Result<String, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // First, get the file's modification time from the device
+    let escaped_path = device_path.replace("'", "'\\''");
+    let stat_command = format!("stat -c %Y '{}'", escaped_path);
+
+    let stat_output = shell
+        .command(&adb_cmd)
+        .args(["-s", device_id, "shell", &stat_command])
+        .output()
+        .await
+        .ok();
+
+    let mtime = stat_output
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<u64>()
+                .ok()
+        });
+
+    // Skip transfer when duplicate handling is enabled and destination already exists.
+    let local_file_path = std::path::Path::new(local_path);
+    if skip_existing && local_file_path.exists() {
+        return Ok("skipped".to_string());
+    }
+
+    // Use adb pull to download the file
+    let total_bytes = match transfer_id {
+        Some(_) => get_remote_file_size(app, device_id, device_path).await.unwrap_or(0),
+        None => 0,
+    };
+    let (success, stderr) = run_adb_transfer(app, ["-s", device_id, "pull", device_path, local_path], transfer_id, total_bytes)
+        .await?;
+
+    if !success {
+        if stderr.contains("does not exist") {
+            return Err(format!("File not found: {}", device_path));
+        } else if stderr.contains("Permission denied") {
+            return Err(format!("Permission denied: {}", device_path));
+        } else {
+            return Err(format!("Download failed: {}", stderr));
+        }
+    }
+
+    // Set the modification time on the downloaded file to match the source
+    if let Some(timestamp) = mtime {
+        if local_file_path.exists() {
+            let mtime_system = UNIX_EPOCH + Duration::from_secs(timestamp);
+            let file = fs::File::open(local_file_path)
+                .map_err(|e| format!("Failed to open downloaded file: {}", e))?;
+
+            file.set_modified(mtime_system)
+                .map_err(|e| format!("Failed to set modification time: {}", e))?;
+
+            // Set creation/birth time on macOS to match the source file
+            let _ = set_creation_time(local_file_path, timestamp);
+        }
+    }
+
+    record_transfer(
+        "download_file",
+        "download",
+        fs::metadata(local_file_path).map(|m| m.len()).unwrap_or(0),
+        1,
+    );
+    Ok("downloaded".to_string())
+}
+
+// Download a file to a chosen local path, creating parent folders; returns the bytes written
+// Fails if the destination exists unless overwrite is set. Progress is reported as `transfer-progress`
+// events for transfer_id, followed by `transfer-complete`, `transfer-cancelled` or `transfer-error`.
+#[tauri::command]
+async fn pull_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    dest_path: String,
+    overwrite: bool,
+    transfer_id: String,
+) -> Result<u64, String> {
+    let _registration = TransferRegistration::new(&transfer_id);
+    let result = pull_file_inner(&app, &device_id, &file_path, &dest_path, overwrite, &transfer_id).await;
+    emit_transfer_outcome(&app, &transfer_id, &result);
+    result
+}
+
+async fn pull_file_inner(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    file_path: &str,
+    dest_path: &str,
+    overwrite: bool,
+    transfer_id: &str,
+) -> Result<u64, String> {
+    // Like adb pull, an existing folder as dest_path receives the file under its own name
+    let dest = std::path::Path::new(dest_path);
+    let target = if dest.is_dir() {
+        dest.join(file_path.trim_end_matches('/').rsplit('/').next().unwrap_or_default())
+    } else {
+        dest.to_path_buf()
+    };
+    let existed = target.exists();
+    if existed && !overwrite {
+        return Err(format!("Destination already exists: {}", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create folder {}: {}", parent.display(), e))?;
+    }
+
+    let target_str = target.to_string_lossy().to_string();
+    if let Err(e) = download_file_inner(app, device_id, file_path, &target_str, false, Some(transfer_id)).await {
+        if e == OPERATION_CANCELLED {
+            remove_partial_download(&target, existed);
+        }
+        return Err(e);
+    }
+
+    fs::metadata(&target)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read downloaded file {}: {}", target.display(), e))
+}
+
+// Check the volume holding target_dir has room for `needed` bytes before a push starts
+// If df can't be read the push goes ahead and adb reports any failure itself
+async fn ensure_device_space(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    target_dir: &str,
+    needed: u64,
+) -> Result<(), AdbError> {
+    match get_storage_info(app.clone(), device_id.to_string(), target_dir.to_string()).await {
+        Ok(info) if info.free_bytes < needed => Err(AdbError::InsufficientSpace {
+            needed,
+            available: info.free_bytes,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Upload a file from the local filesystem to the Android device
+#[tauri::command]
+async fn upload_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    local_path: String,
+    device_path: String,
+    overwrite: bool,
+) -> Result<(), AdbError> {
+    upload_file_inner(&app, &device_id, &local_path, &device_path, overwrite, None).await
+}
+
+// upload_file, optionally streaming `transfer-progress` events for transfer_id
+async fn upload_file_inner(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    local_path: &str,
+    device_path: &str,
+    overwrite: bool,
+    transfer_id: Option<&str>,
+) -> Result<(), AdbError> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Verify local file exists
+    if !std::path::Path::new(local_path).exists() {
+        return Err(AdbError::NotFound(local_path.to_string()));
+    }
+
+    // adb push replaces existing files silently, so check first unless overwriting was requested
+    if !overwrite {
+        let escaped_path = device_path.replace("'", "'\\''");
+        let test_command = format!("test -e '{}' && echo exists", escaped_path);
+        let test_output = shell
+            .command(&adb_cmd)
+            .args(["-s", device_id, "shell", &test_command])
+            .output()
+            .await
+            .map_err(AdbError::from_spawn)?;
+
+        if String::from_utf8_lossy(&test_output.stdout).contains("exists") {
+            return Err(AdbError::AlreadyExists(device_path.to_string()));
+        }
+    }
+
+    let local_size = fs::metadata(local_path).map(|m| m.len()).unwrap_or(0);
+    let target_dir = match device_path.rsplit_once('/') {
+        Some((parent, _)) if !parent.is_empty() => parent,
+        _ => "/",
+    };
+    ensure_device_space(app, device_id, target_dir, local_size).await?;
+
+    // Use adb push to upload the file
+    let (success, stderr) = run_adb_transfer(app, ["-s", device_id, "push", local_path, device_path], transfer_id, local_size)
+        .await?;
+
+    if !success {
+        return Err(AdbError::from_stderr(&stderr, device_path));
+    }
+
+    // Preserve the local file's modification time on the device
+    let local_path_obj = std::path::Path::new(local_path);
+    if let Ok(metadata) = local_path_obj.metadata() {
+        if let Ok(modified) = metadata.modified() {
+            if let Ok(duration) = modified.duration_since(UNIX_EPOCH) {
+                let mtime = duration.as_secs();
+                let escaped_path = device_path.replace("'", "'\\''");
+                let touch_cmd = format!("touch -d @{} '{}'", mtime, escaped_path);
+                let _ = shell
+                    .command(&adb_cmd)
+                    .args(["-s", device_id, "shell", &touch_cmd])
+                    .output()
+                    .await;
+            }
+        }
+    }
+
+    record_transfer("upload_file", "upload", local_size, 1);
+    Ok(())
+}
+
+// Push a local file to the device and confirm it arrived, returning the bytes pushed
+// Refuses the critical system paths delete_file guards; permission and read-only errors come from upload_file.
+// Fails if the destination exists unless overwrite is set. Progress is reported like pull_file's.
+#[tauri::command]
+async fn push_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    source_path: String,
+    dest_path: String,
+    overwrite: bool,
+    transfer_id: String,
+) -> Result<u64, String> {
+    let _registration = TransferRegistration::new(&transfer_id);
+    let result = push_file_inner(&app, &device_id, &source_path, &dest_path, overwrite, &transfer_id).await;
+    emit_transfer_outcome(&app, &transfer_id, &result);
+    result
+}
+
+async fn push_file_inner(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    source_path: &str,
+    dest_path: &str,
+    overwrite: bool,
+    transfer_id: &str,
+) -> Result<u64, String> {
+    if is_critical_system_path(dest_path) {
+        return Err(format!("Cannot push into critical system path: {}", dest_path));
+    }
+    let size = match fs::metadata(source_path) {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        _ => return Err(format!("Local file not found: {}", source_path)),
+    };
+
+    // Like adb push, an existing folder as dest_path receives the file under its own name
+    let escaped_dest = dest_path.replace("'", "'\\''");
+    let dir_check = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &format!("test -d '{}' && echo dir", escaped_dest)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to check destination: {}", e))?;
+    let target = if String::from_utf8_lossy(&dir_check.stdout).contains("dir") {
+        let file_name = std::path::Path::new(source_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        format!("{}/{}", dest_path.trim_end_matches('/'), file_name)
+    } else {
+        dest_path.to_string()
+    };
+    let escaped_path = target.replace("'", "'\\''");
+
+    if let Err(e) = upload_file_inner(app, device_id, source_path, &target, overwrite, Some(transfer_id)).await {
+        let e = String::from(e);
+        if e == OPERATION_CANCELLED {
+            let _ = app
+                .shell()
+                .command(get_adb_command())
+                .args(["-s", device_id, "shell", &format!("rm -f '{}'", escaped_path)])
+                .output()
+                .await;
+        }
+        return Err(e);
+    }
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &format!("test -e '{}' && echo exists", escaped_path)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to verify pushed file: {}", e))?;
+
+    if !String::from_utf8_lossy(&output.stdout).contains("exists") {
+        return Err(format!("Push reported success but {} is missing on the device", target));
+    }
+    Ok(size)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PushDirectorySummary {
+    pub total_files: usize,
+    pub pushed: usize,
+    pub failed: Vec<String>,
+}
+
+// Device path for a file under a pushed folder; relative components are joined with '/' on every host OS
+fn remote_push_path(remote_dest: &str, relative: &std::path::Path) -> String {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    format!("{}/{}", remote_dest.trim_end_matches('/'), parts.join("/"))
+}
+
+// Push a local folder's contents into remote_dest (created with mkdir -p), emitting `push-progress` events
+// Files are pushed one `adb push` at a time so progress can be reported per file; adb only prints its own
+// progress lines to a terminal. cancel_transfer(transfer_id) stops it, keeping the files already pushed.
+#[tauri::command]
+async fn push_directory(
+    app: tauri::AppHandle,
+    device_id: String,
+    local_path: String,
+    remote_dest: String,
+    transfer_id: String,
+) -> Result<PushDirectorySummary, AdbError> {
+    let local_root = PathBuf::from(&local_path);
+    if !local_root.is_dir() {
+        return Err(AdbError::NotFound(local_path));
+    }
+
+    let files: Vec<(PathBuf, u64)> = walkdir::WalkDir::new(&local_root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| {
+            let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+            (e.into_path(), size)
+        })
+        .collect();
+    let total_files = files.len();
+    let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+
+    let escaped_dest = remote_dest.replace("'", "'\\''");
+    let mkdir_output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "shell", &format!("mkdir -p '{}' 2>&1", escaped_dest)])
+        .output()
+        .await
+        .map_err(AdbError::from_spawn)?;
+    let mkdir_stdout = String::from_utf8_lossy(&mkdir_output.stdout);
+
+    if mkdir_stdout.contains("Read-only file system") || mkdir_stdout.contains("Permission denied") {
+        return Err(AdbError::from_stderr(&mkdir_stdout, &remote_dest));
+    }
+    ensure_device_space(&app, &device_id, &remote_dest, total_bytes).await?;
+
+    let registration = TransferRegistration::new(&transfer_id);
+    let mut failed = Vec::new();
+    let mut pushed_bytes = 0;
+    for (index, (local_file, size)) in files.iter().enumerate() {
+        if registration.is_cancelled() {
+            emit_transfer_cancelled(&app, &transfer_id);
+            return Err(AdbError::Cancelled);
+        }
+
+        let relative = local_file.strip_prefix(&local_root).unwrap_or(local_file);
+        let remote_file = remote_push_path(&remote_dest, relative);
+        let _ = app.emit("push-progress", BatchProgress {
+            completed: index,
+            total: total_files,
+            current_file: remote_file.clone(),
+            overall_percent: index as f64 / total_files.max(1) as f64 * 100.0,
+        });
+
+        let local_str = local_file.to_string_lossy();
+        let outcome = run_adb_transfer(&app, ["-s", &device_id, "push", &local_str, &remote_file], Some(&transfer_id), *size).await;
+        let (success, stderr) = match outcome {
+            Err(e) if e == OPERATION_CANCELLED => {
+                let escaped_file = remote_file.replace("'", "'\\''");
+                let _ = app
+                    .shell()
+                    .command(get_adb_command())
+                    .args(["-s", &device_id, "shell", &format!("rm -f '{}'", escaped_file)])
+                    .output()
+                    .await;
+                emit_transfer_cancelled(&app, &transfer_id);
+                return Err(AdbError::Cancelled);
+            }
+            outcome => outcome?,
+        };
+        if success {
+            pushed_bytes += size;
+            continue;
+        }
+
+        // Every later file would fail the same way
+        if stderr.contains("Read-only file system") {
+            return Err(AdbError::ReadOnlyFileSystem(remote_dest));
+        }
+        if stderr.contains("No space left on device") {
+            return Err(AdbError::OutOfSpace(remote_dest));
+        }
+        failed.push(format!("{}: {}", remote_file, stderr.trim()));
+    }
+
+    let pushed = total_files - failed.len();
+    record_transfer("push_directory", "upload", pushed_bytes, pushed as u64);
+
+    let _ = app.emit("push-progress", BatchProgress {
+        completed: pushed,
+        total: total_files,
+        current_file: String::new(),
+        overall_percent: 100.0,
+    });
+
+    Ok(PushDirectorySummary {
+        total_files,
+        pushed,
+        failed,
+    })
+}
+
+// How long the pushed wallpaper image is kept so the on-device picker can read it
+const WALLPAPER_CLEANUP_DELAY: Duration = Duration::from_secs(120);
+
+// Push a local image and open the device's "Set as wallpaper" picker for it
+// Android has no shell command to set a wallpaper directly, so this sends an ATTACH_DATA intent and the
+// user confirms on the device. Devices without a handler for the intent get an "unsupported" error.
+#[tauri::command]
+async fn set_wallpaper(app: tauri::AppHandle, device_id: String, image_path: String) -> Result<(), String> {
+    let extension = std::path::Path::new(&image_path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !is_image_extension(&extension) {
+        return Err(format!("Not a supported image: {}", image_path));
+    }
+
+    // Shared storage, since the picker app can't read adb's private /data/local/tmp
+    let device_path = format!("/sdcard/Download/.droiddock_wallpaper_{}.{}", now_millis(), extension);
+    upload_file(app.clone(), device_id.clone(), image_path, device_path.clone(), true).await?;
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let remove_command = format!("rm -f '{}'", device_path);
+
+    let am_command = format!(
+        "am start -W -a android.intent.action.ATTACH_DATA -c android.intent.category.DEFAULT -d 'file://{}' -t 'image/*'",
+        device_path
+    );
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &am_command])
+        .output()
+        .await;
+
+    let started = match &output {
+        Ok(o) => {
+            let combined = format!(
+                "{}{}",
+                String::from_utf8_lossy(&o.stdout),
+                String::from_utf8_lossy(&o.stderr)
+            );
+            if combined.contains("unable to resolve Intent") || combined.contains("Error:") {
+                Err("Setting a wallpaper is not supported on this device's Android version".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        Err(e) => Err(format!("Failed to open wallpaper picker: {}", e)),
+    };
+
+    if started.is_err() {
+        let _ = shell.command(&adb_cmd).args(["-s", &device_id, "shell", &remove_command]).output().await;
+        return started;
+    }
+
+    // The system copies the image once the user confirms, so the pushed file can go afterwards
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WALLPAPER_CLEANUP_DELAY).await;
+        let _ = app
+            .shell()
+            .command(get_adb_command())
+            .args(["-s", &device_id, "shell", &remove_command])
+            .output()
+            .await;
+    });
+
+    Ok(())
+}
+
+// Compute a device path's location relative to a remote root
+// Returns None when the path is outside the root or would escape it via `..`
+fn relative_device_path(path: &str, remote_root: &str) -> Option<String> {
+    let root = remote_root.trim_end_matches('/');
+    let remainder = path.strip_prefix(root)?;
+    if !root.is_empty() && !remainder.starts_with('/') {
+        return None;
+    }
+    let relative = remainder.trim_start_matches('/');
+    if relative.is_empty() || relative.split('/').any(|part| part == "..") {
+        return None;
+    }
+    Some(relative.to_string())
+}
+
+// Incrementally back up photos and videos modified since the given time
+// Folder structure under `camera_path` is recreated in `local_dest` and mtimes are preserved
+// A failed file is recorded in `errors` and `newest_epoch` is held back so the next run retries it
+#[tauri::command]
+async fn pull_photos_since(
+    app: tauri::AppHandle,
+    device_id: String,
+    camera_path: String,
+    since_epoch: u64,
+    local_dest: String,
+) -> Result<PhotoBackupResult, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let escaped_path = camera_path.replace("'", "'\\''");
+    let find_command = format!("find '{}' -type f -exec ls -ld {{}} \\; 2>/dev/null", escaped_path);
+
+    let output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &find_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list photos: {}", e))?;
+
+    let files: Vec<FileEntry> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_ls_line)
+        .collect();
+    let (media, newest_epoch) = select_media_since(files, since_epoch);
+
+    let local_root = PathBuf::from(&local_dest);
+    fs::create_dir_all(&local_root)
+        .map_err(|e| format!("Failed to create local folder {}: {}", local_dest, e))?;
+
+    let mut copied = Vec::new();
+    let mut errors = HashMap::new();
+    let mut oldest_failed: Option<u64> = None;
+    for entry in media {
+        // find prints full paths, so the parsed name is the device path
+        let Some(relative) = relative_device_path(&entry.name, &camera_path) else {
+            continue;
+        };
+
+        let local_file = local_root.join(&relative);
+        let result = match local_file.parent().map(fs::create_dir_all) {
+            Some(Err(e)) => Err(format!("Failed to create folder: {}", e)),
+            _ => {
+                download_file(
+                    app.clone(),
+                    device_id.clone(),
+                    entry.name.clone(),
+                    local_file.to_string_lossy().to_string(),
+                    false,
+                )
+                .await
+            }
+        };
+
+        match result {
+            Ok(_) => copied.push(entry.name),
+            Err(e) => {
+                if let Some(epoch) = entry.modified_epoch {
+                    oldest_failed = Some(oldest_failed.map_or(epoch, |oldest| oldest.min(epoch)));
+                }
+                errors.insert(entry.name, e);
+            }
+        }
+    }
+
+    Ok(PhotoBackupResult {
+        copied,
+        newest_epoch: hold_back_epoch(newest_epoch, oldest_failed),
+        errors,
+    })
+}
+
+// Download files while recreating their folder structure relative to a remote root
+// Returns a map of device path -> "downloaded" or an error message
+#[tauri::command]
+async fn download_preserving_structure(
+    app: tauri::AppHandle,
+    device_id: String,
+    paths: Vec<String>,
+    remote_root: String,
+    local_root: String,
+) -> Result<HashMap<String, String>, String> {
+    let local_root_path = PathBuf::from(&local_root);
+    fs::create_dir_all(&local_root_path)
+        .map_err(|e| format!("Failed to create local folder {}: {}", local_root, e))?;
+
+    let mut results = HashMap::new();
+
+    for device_path in paths {
+        let relative = match relative_device_path(&device_path, &remote_root) {
+            Some(relative) => relative,
+            None => {
+                results.insert(device_path.clone(), format!("Not inside {}", remote_root));
+                continue;
+            }
+        };
+
+        let local_file = local_root_path.join(&relative);
+        if let Some(parent) = local_file.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                results.insert(device_path.clone(), format!("Failed to create folder: {}", e));
+                continue;
+            }
+        }
+
+        let result = download_file(
+            app.clone(),
+            device_id.clone(),
+            device_path.clone(),
+            local_file.to_string_lossy().to_string(),
+            false,
+        )
+        .await
+        .unwrap_or_else(|e| e);
+        results.insert(device_path, result);
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub current_file: String,
+    pub overall_percent: f64,
+}
+
+// First free local path for a file, appending " (1)", " (2)", ... before the extension
+fn available_local_path(path: &std::path::Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+// Download a list of files into one folder, emitting a single `batch-progress` stream
+// conflict: "skip", "overwrite" or "rename" (keep both). Returns device path -> result or error message
+// cancel_transfer(transfer_id) stops the batch, keeping the files already pulled
+#[tauri::command]
+async fn pull_files(
+    app: tauri::AppHandle,
+    device_id: String,
+    paths: Vec<String>,
+    local_dest: String,
+    conflict: String,
+    transfer_id: String,
+) -> Result<HashMap<String, String>, String> {
+    if !matches!(conflict.as_str(), "skip" | "overwrite" | "rename") {
+        return Err(format!("Unknown conflict policy: {}", conflict));
+    }
+
+    let dest_dir = PathBuf::from(&local_dest);
+    fs::create_dir_all(&dest_dir)
+        .map_err(|e| format!("Failed to create local folder {}: {}", local_dest, e))?;
+
+    let registration = TransferRegistration::new(&transfer_id);
+    let total = paths.len();
+    let mut results = HashMap::new();
+
+    for (index, device_path) in paths.into_iter().enumerate() {
+        if registration.is_cancelled() {
+            emit_transfer_cancelled(&app, &transfer_id);
+            return Err(OPERATION_CANCELLED.to_string());
+        }
+
+        let _ = app.emit("batch-progress", BatchProgress {
+            completed: index,
+            total,
+            current_file: device_path.clone(),
+            overall_percent: index as f64 / total as f64 * 100.0,
+        });
+
+        let file_name = device_path.trim_end_matches('/').rsplit('/').next().unwrap_or("file");
+        let mut local_file = dest_dir.join(file_name);
+        if local_file.exists() && conflict == "rename" {
+            local_file = available_local_path(&local_file);
+        }
+
+        let existed = local_file.exists();
+        let local_str = local_file.to_string_lossy().to_string();
+        let result = download_file_inner(&app, &device_id, &device_path, &local_str, conflict == "skip", Some(&transfer_id)).await;
+        if matches!(&result, Err(e) if e == OPERATION_CANCELLED) {
+            remove_partial_download(&local_file, existed);
+            emit_transfer_cancelled(&app, &transfer_id);
+            return Err(OPERATION_CANCELLED.to_string());
+        }
+        results.insert(device_path, result.unwrap_or_else(|e| e));
+    }
+
+    let _ = app.emit("batch-progress", BatchProgress {
+        completed: total,
+        total,
+        current_file: String::new(),
+        overall_percent: 100.0,
+    });
+
+    Ok(results)
+}
+
+// Format seconds since the epoch as a UTC "YYYY-MM-DD" date (inverse of the civil-day math in parse_ls_datetime)
+fn format_epoch_date(secs: u64) -> String {
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupManifest {
+    pub device_id: String,
+    pub model: String,
+    pub date: String,
+    pub folders: Vec<String>,
+    pub file_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupResult {
+    pub backup_dir: String,
+    pub manifest: BackupManifest,
+    pub errors: HashMap<String, String>,
+}
+
+// Local folder name for each backed-up device folder: its last component, with " (1)", " (2)", ...
+// appended when two folders share one (e.g. /sdcard/DCIM/Camera and /sdcard/Pictures/Camera).
+// Names are compared ignoring case, since the local disk may be case-insensitive.
+fn backup_folder_names(folders: &[String]) -> Vec<String> {
+    let mut used = HashSet::new();
+    folders
+        .iter()
+        .map(|folder| {
+            let base = folder.trim_end_matches('/').rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("root");
+            let name = std::iter::once(base.to_string())
+                .chain((1..).map(|n| format!("{} ({})", base, n)))
+                .find(|candidate| !used.contains(&candidate.to_lowercase()))
+                .unwrap();
+            used.insert(name.to_lowercase());
+            name
+        })
+        .collect()
+}
+
+// Back up device folders into a dated folder under local_root (e.g. DroidDock-Backup-2025-02-01)
+// Each folder keeps its structure and file mtimes; a manifest.json records the device, date and file count.
+// Emits `backup-progress` events with the same shape as `batch-progress`.
+// cancel_transfer(transfer_id) stops the backup; files already copied are kept but no manifest is written.
+#[tauri::command]
+async fn backup_device(
+    app: tauri::AppHandle,
+    device_id: String,
+    folders: Vec<String>,
+    local_root: String,
+    transfer_id: String,
+) -> Result<BackupResult, String> {
+    let registration = TransferRegistration::new(&transfer_id);
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let date = format_epoch_date(now_millis() / 1000);
+    let backup_dir = available_local_path(&PathBuf::from(&local_root).join(format!("DroidDock-Backup-{}", date)));
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backup folder {}: {}", backup_dir.display(), e))?;
+
+    let model_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", "getprop ro.product.model"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read device model: {}", e))?;
+    let model = String::from_utf8_lossy(&model_output.stdout).trim().to_string();
+
+    // Collect every file first so progress has a stable total
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for (folder, folder_name) in folders.iter().zip(backup_folder_names(&folders)) {
+        let folder = folder.trim_end_matches('/');
+        let escaped_folder = folder.replace("'", "'\\''");
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &format!("find '{}' -type f 2>/dev/null", escaped_folder)])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to list {}: {}", folder, e))?;
+
+        for device_path in String::from_utf8_lossy(&output.stdout).lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(relative) = relative_device_path(device_path, folder) {
+                files.push((device_path.to_string(), backup_dir.join(&folder_name).join(relative)));
+            }
+        }
+    }
+
+    let total = files.len();
+    let mut errors = HashMap::new();
+    let mut file_count = 0;
+
+    for (index, (device_path, local_file)) in files.into_iter().enumerate() {
+        if registration.is_cancelled() {
+            emit_transfer_cancelled(&app, &transfer_id);
+            return Err(OPERATION_CANCELLED.to_string());
+        }
+
+        let _ = app.emit("backup-progress", BatchProgress {
+            completed: index,
+            total,
+            current_file: device_path.clone(),
+            overall_percent: index as f64 / total as f64 * 100.0,
+        });
+
+        if let Some(parent) = local_file.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                errors.insert(device_path, format!("Failed to create folder: {}", e));
+                continue;
+            }
+        }
+
+        let local_str = local_file.to_string_lossy().to_string();
+        match download_file_inner(&app, &device_id, &device_path, &local_str, false, Some(&transfer_id)).await {
+            Ok(_) => file_count += 1,
+            Err(e) if e == OPERATION_CANCELLED => {
+                remove_partial_download(&local_file, false);
+                emit_transfer_cancelled(&app, &transfer_id);
+                return Err(e);
+            }
+            Err(e) => {
+                errors.insert(device_path, e);
+            }
+        }
+    }
+
+    let _ = app.emit("backup-progress", BatchProgress {
+        completed: total,
+        total,
+        current_file: String::new(),
+        overall_percent: 100.0,
+    });
+
+    let manifest = BackupManifest {
+        device_id,
+        model,
+        date,
+        folders,
+        file_count,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    fs::write(backup_dir.join("manifest.json"), manifest_json)
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+    Ok(BackupResult {
+        backup_dir: backup_dir.to_string_lossy().to_string(),
+        manifest,
+        errors,
+    })
+}
+
+// Largest file get_image_preview will read into memory
+const MAX_IMAGE_PREVIEW_INPUT: usize = 50 * 1024 * 1024;
+
+// Run `adb exec-out` and collect its raw stdout, giving up once it exceeds max_bytes
+// exec-out bypasses the pty, so binary data arrives unmodified
+async fn exec_out_bytes(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    shell_command: &str,
+    max_bytes: usize,
+) -> Result<Vec<u8>, String> {
+    let (mut rx, child) = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "exec-out", shell_command])
+        .set_raw_out(true)
+        .spawn()
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+    let job_id = register_job(None, "exec-out", Some(device_id), child);
+
+    let mut bytes = Vec::new();
+    let mut stderr = Vec::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(chunk) => {
+                if bytes.len() + chunk.len() > max_bytes {
+                    if let Some(child) = unregister_job(&job_id) {
+                        let _ = child.kill();
+                    }
+                    return Err(format!("File too large to load (over {} MB)", max_bytes / (1024 * 1024)));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            CommandEvent::Stderr(chunk) => stderr.extend_from_slice(&chunk),
+            CommandEvent::Terminated(payload) => exit_code = payload.code,
+            _ => {}
+        }
+    }
+
+    unregister_job(&job_id);
+
+    if exit_code != Some(0) {
+        return Err(format!("ADB exec-out failed: {}", String::from_utf8_lossy(&stderr).trim()));
+    }
+
+    Ok(bytes)
+}
+
+// Pull a file by streaming `adb exec-out cat` into a local file, emitting `transfer-progress` events
+// Useful for very large files and for paths adb pull struggles with; the final size is checked against stat
+#[tauri::command]
+async fn pull_via_exec_out(
+    app: tauri::AppHandle,
+    device_id: String,
+    device_path: String,
+    local_path: String,
+) -> Result<u64, String> {
+    use std::io::Write;
+
+    let expected_size = get_remote_file_size(&app, &device_id, &device_path)
+        .await
+        .ok_or_else(|| format!("File not found: {}", device_path))?;
+
+    let escaped_path = device_path.replace("'", "'\\''");
+    let (mut rx, child) = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", &device_id, "exec-out", &format!("cat '{}'", escaped_path)])
+        .set_raw_out(true)
+        .spawn()
+        .map_err(|e| format!("Failed to execute adb command: {}", e))?;
+    let job_id = register_job(None, "exec-out", Some(&device_id), child);
+
+    let mut file = match fs::File::create(&local_path) {
+        Ok(file) => std::io::BufWriter::new(file),
+        Err(e) => {
+            if let Some(child) = unregister_job(&job_id) {
+                let _ = child.kill();
+            }
+            return Err(format!("Failed to create {}: {}", local_path, e));
+        }
+    };
+
+    let started = std::time::Instant::now();
+    let mut last_report = started;
+    let mut written: u64 = 0;
+    let mut stderr = Vec::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(chunk) => {
+                if let Err(e) = file.write_all(&chunk) {
+                    if let Some(child) = unregister_job(&job_id) {
+                        let _ = child.kill();
+                    }
+                    let _ = fs::remove_file(&local_path);
+                    return Err(format!("Failed to write {}: {}", local_path, e));
+                }
+                written += chunk.len() as u64;
+
+                if last_report.elapsed() >= Duration::from_millis(500) {
+                    last_report = std::time::Instant::now();
+                    let elapsed = started.elapsed().as_secs_f64();
+                    let progress = compute_transfer_progress(&device_path, written, expected_size, elapsed);
+                    let _ = app.emit("transfer-progress", progress);
+                }
+            }
+            CommandEvent::Stderr(chunk) => stderr.extend_from_slice(&chunk),
+            CommandEvent::Terminated(payload) => exit_code = payload.code,
+            _ => {}
+        }
+    }
+
+    unregister_job(&job_id);
+    file.flush().map_err(|e| format!("Failed to write {}: {}", local_path, e))?;
+    drop(file);
+
+    if exit_code != Some(0) || written != expected_size {
+        let _ = fs::remove_file(&local_path);
+        if exit_code != Some(0) {
+            return Err(format!("ADB exec-out failed: {}", String::from_utf8_lossy(&stderr).trim()));
+        }
+        return Err(format!(
+            "Size mismatch for {}: expected {} bytes, received {}",
+            device_path, expected_size, written
+        ));
+    }
+
+    let _ = app.emit(
+        "transfer-progress",
+        compute_transfer_progress(&device_path, written, expected_size, started.elapsed().as_secs_f64()),
+    );
+    record_transfer("pull_via_exec_out", "download", written, 1);
+    Ok(written)
+}
+
+// Get a medium-size preview of an image without writing it to disk
+// The image is streamed into memory, downscaled to fit max_dimension, and returned as a JPEG data URL
+#[tauri::command]
+async fn get_image_preview(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    max_dimension: u32,
+) -> Result<String, String> {
+    let escaped_path = file_path.replace("'", "'\\''");
+    let cat_command = format!("cat '{}'", escaped_path);
+    let bytes = exec_out_bytes(&app, &device_id, &cat_command, MAX_IMAGE_PREVIEW_INPUT).await?;
+
+    if bytes.is_empty() {
+        return Err(format!("File not found or empty: {}", file_path));
+    }
+
+    let img = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to decode image ({} bytes): {}", bytes.len(), e))?;
+
+    let max_dimension = max_dimension.clamp(1, 4096);
+    let resized = if img.width() > max_dimension || img.height() > max_dimension {
+        img.thumbnail(max_dimension, max_dimension)
+    } else {
+        img
+    };
+
+    // JPEG keeps large previews far smaller than PNG; it has no alpha channel, so flatten to RGB
+    let mut jpeg_bytes = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_bytes, 85)
+        .encode_image(&resized.to_rgb8())
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+
+    let base64_string = general_purpose::STANDARD.encode(&jpeg_bytes);
+    Ok(format!("data:image/jpeg;base64,{}", base64_string))
+}
+
+// Response type for file preview
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilePreview {
+    pub file_type: String,  // "image", "text", or "unsupported"
+    pub content: String,    // base64 for images, text content for text files
+    pub size: u64,          // file size in bytes
+}
+
+// Preview a file from the Android device
+#[tauri::command]
+async fn preview_file(
+    app: tauri::AppHandle,
+    device_id: String,
+    device_path: String,
+    extension: Option<String>,
+) -> Result<FilePreview, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    // Define max file size for preview (10MB for text, 50MB for images)
+    const MAX_TEXT_SIZE: u64 = 10 * 1024 * 1024;
+    const MAX_IMAGE_SIZE: u64 = 50 * 1024 * 1024;
+
+    // Get file size first
+    // Escape single quotes in path and wrap in quotes to handle spaces
+    let escaped_path = device_path.replace("'", "'\\''");
+    let stat_command = format!("stat -c %s '{}'", escaped_path);
+
+    let stat_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &stat_command])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to get file size: {}", e))?;
+
+    if !stat_output.status.success() {
+        return Err("Failed to get file information".to_string());
+    }
+
+    let size_str = String::from_utf8_lossy(&stat_output.stdout).trim().to_string();
+    let file_size: u64 = size_str.parse()
+        .map_err(|_| "Failed to parse file size".to_string())?;
+
+    // Determine file type - handle extension with or without dot prefix
+    let ext = extension
+        .as_ref()
+        .map(|e| {
+            let trimmed = e.trim();
+            if trimmed.starts_with('.') {
+                &trimmed[1..]
+            } else {
+                trimmed
+            }
+        })
+        .unwrap_or("")
+        .to_lowercase();
+
+    // Add debug logging (using eprintln! for simplicity in debug builds)
+    // Note: For larger applications, consider using the 'log' crate
+    #[cfg(debug_assertions)]
+    {
+        eprintln!("Preview file debug - extension param: {:?}", extension);
+        eprintln!("Preview file debug - processed ext: {}", ext);
+    }
+
+    let is_image = is_image_extension(&ext);
+    let is_text = is_text_extension(&ext);
+
+    #[cfg(debug_assertions)]
+    {
+        eprintln!("Preview file debug - is_image: {}, is_text: {}", is_image, is_text);
+    }
+
+    if !is_image && !is_text {
+        return Ok(FilePreview {
+            file_type: "unsupported".to_string(),
+            content: String::new(),
+            size: file_size,
+        });
+    }
+
+    // Check file size limits
+    if is_text && file_size > MAX_TEXT_SIZE {
+        return Err(format!(
+            "Text file too large to preview ({} MB). Maximum size is 10 MB.",
+            file_size / (1024 * 1024)
+        ));
+    }
+
+    if is_image && file_size > MAX_IMAGE_SIZE {
+        return Err(format!(
+            "Image file too large to preview ({} MB). Maximum size is 50 MB.",
+            file_size / (1024 * 1024)
+        ));
+    }
+
+    // Create a temp file to store the pulled file
+    let temp_file = tempfile::NamedTempFile::new()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let temp_path = temp_file.path().to_str()
+        .ok_or("Failed to get temp file path")?;
+
+    // Pull the file from the device
+    let pull_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "pull", &device_path, temp_path])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to pull file: {}", e))?;
+
+    if !pull_output.status.success() {
+        let stderr = String::from_utf8_lossy(&pull_output.stderr);
+        return Err(format!("Failed to pull file: {}", stderr));
+    }
+
+    // Read and process the file based on type
+    if is_image {
+        // Read image file and convert to base64
+        let image_bytes = fs::read(temp_path)
+            .map_err(|e| format!("Failed to read image file: {}", e))?;
+        let base64_content = general_purpose::STANDARD.encode(&image_bytes);
+
+        Ok(FilePreview {
+            file_type: "image".to_string(),
+            content: base64_content,
+            size: file_size,
+        })
+    } else {
+        // Read text file
+        let text_content = fs::read_to_string(temp_path)
+            .map_err(|e| format!("Failed to read text file: {}", e))?;
+
+        // Limit text content to reasonable size for display
+        // Use char-based truncation to avoid panicking on multi-byte UTF-8 characters
+        let display_content = if text_content.len() > 500_000 {
+            let truncated: String = text_content.chars().take(500_000).collect();
+            format!(
+                "{}\n\n... (truncated, showing first 500KB of {} KB file)",
+                truncated,
+                text_content.len() / 1024
+            )
+        } else {
+            text_content
+        };
+
+        Ok(FilePreview {
+            file_type: "text".to_string(),
+            content: display_content,
+            size: file_size,
+        })
+    }
+}
+
+// Upper bound for pulled preview files kept in the cache; oldest are evicted beyond this
+const PREVIEW_CACHE_MAX_BYTES: u64 = 500 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewPath {
+    pub file_type: String,          // "image", "pdf", "text", or "needs-download"
+    pub local_path: Option<String>,
+}
+
+// Delete the least recently used files in dir until it holds at most max_bytes
+fn evict_oldest_files(dir: &std::path::Path, max_bytes: u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let metadata = e.metadata().ok().filter(|m| m.is_file())?;
+            Some((metadata.modified().unwrap_or(UNIX_EPOCH), metadata.len(), e.path()))
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(modified, _, _)| *modified);
+    for (_, size, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+// Pull a file into the preview cache and return its local path for the frontend to render
+// Images, PDFs and text are supported; anything else returns "needs-download" without pulling.
+// Entries are keyed like thumbnails (plus the remote mtime) and the cache is capped at PREVIEW_CACHE_MAX_BYTES.
+#[tauri::command]
+async fn get_preview_path(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    extension: String,
+) -> Result<PreviewPath, String> {
+    let ext = extension.trim().trim_start_matches('.').to_lowercase();
+    let file_type = if is_image_extension(&ext) {
+        "image"
+    } else if ext == "pdf" {
+        "pdf"
+    } else if is_text_extension(&ext) {
+        "text"
+    } else {
+        return Ok(PreviewPath {
+            file_type: "needs-download".to_string(),
+            local_path: None,
+        });
+    };
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    let escaped_path = file_path.replace("'", "'\\''");
+    let stat_output = shell
+        .command(&adb_cmd)
+        .args(["-s", &device_id, "shell", &format!("stat -c '%s %Y' '{}'", escaped_path)])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to get file information: {}", e))?;
+    let stat_stdout = String::from_utf8_lossy(&stat_output.stdout);
+    let (size, mtime) = stat_stdout
+        .trim()
+        .split_once(' ')
+        .and_then(|(size, mtime)| Some((size.parse::<u64>().ok()?, mtime.to_string())))
+        .ok_or_else(|| format!("File not found: {}", file_path))?;
+
+    // Files larger than the whole cache are better served by a real download
+    if size > PREVIEW_CACHE_MAX_BYTES {
+        return Ok(PreviewPath {
+            file_type: "needs-download".to_string(),
+            local_path: None,
+        });
+    }
+
+    let preview_dir = thumbnail_cache_dir().join("previews");
+    fs::create_dir_all(&preview_dir)
+        .map_err(|e| format!("Failed to create preview cache: {}", e))?;
+    let cache_key = thumbnail_cache_key(&device_id, &format!("{}@{}", file_path, mtime));
+    let cached_file = preview_dir.join(format!("preview_{}.{}", cache_key, ext));
+
+    if cached_file.exists() {
+        // Refresh the mtime so eviction treats it as recently used
+        if let Ok(file) = fs::File::options().append(true).open(&cached_file) {
+            let _ = file.set_modified(std::time::SystemTime::now());
+        }
+    } else {
+        let pull_output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "pull", &file_path, cached_file.to_str().unwrap()])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to pull file: {}", e))?;
+
+        if !pull_output.status.success() {
+            let _ = fs::remove_file(&cached_file);
+            return Err(format!("Failed to pull file: {}", String::from_utf8_lossy(&pull_output.stderr)));
+        }
+        evict_oldest_files(&preview_dir, PREVIEW_CACHE_MAX_BYTES);
+    }
+
+    Ok(PreviewPath {
+        file_type: file_type.to_string(),
+        local_path: Some(cached_file.to_string_lossy().to_string()),
+    })
+}
+
+// ========================
+// Folder Sync Types
+// ========================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum SyncDirection {
+    PhoneToComputer,
+    ComputerToPhone,
+    BothWays,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncOptions {
+    pub local_path: String,
+    pub device_path: String,
+    pub direction: SyncDirection,
+    pub recursive: bool,
+    pub delete_missing: bool,
+    pub match_mode: String,
+    #[serde(default)]
+    pub file_patterns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileMetadata {
+    pub relative_path: String,
+    pub size: u64,
+    pub modified_time: u64,
+    pub is_directory: bool,
+    pub md5_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncAction {
+    pub file_path: String,
+    pub action_type: String,
+    pub direction: String,
+    pub size: u64,
+    pub reason: String,
+    pub rename_from: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncPreview {
+    pub actions: Vec<SyncAction>,
+    pub total_transfer_bytes: u64,
+    pub copy_count: u32,
+    pub update_count: u32,
+    pub delete_count: u32,
+    pub skip_count: u32,
+    pub rename_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncProgress {
+    pub current_file: String,
+    pub completed_count: u32,
+    pub total_count: u32,
+    pub completed_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub success_count: u32,
+    pub skip_count: u32,
+    pub error_count: u32,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedSync {
+    pub id: String,
+    pub name: String,
+    pub options: SyncOptions,
+}
+
+fn saved_syncs_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(data_dir.join("saved_syncs.json"))
+}
+
+fn read_saved_syncs_file(app: &tauri::AppHandle) -> Result<Vec<SavedSync>, String> {
+    let path = saved_syncs_path(app)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read saved syncs: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse saved syncs: {}", e))
+}
+
+fn write_saved_syncs_file(app: &tauri::AppHandle, syncs: &[SavedSync]) -> Result<(), String> {
+    let path = saved_syncs_path(app)?;
+    let content = serde_json::to_string_pretty(syncs)
+        .map_err(|e| format!("Failed to serialize saved syncs: {}", e))?;
+    fs::write(&path, content)
+        .map_err(|e| format!("Failed to write saved syncs: {}", e))
+}
+
+#[tauri::command]
+async fn list_saved_syncs(app: tauri::AppHandle) -> Result<Vec<SavedSync>, String> {
+    read_saved_syncs_file(&app)
+}
+
+#[tauri::command]
+async fn save_sync_config(
+    app: tauri::AppHandle,
+    id: Option<String>,
+    name: String,
+    options: SyncOptions,
+) -> Result<SavedSync, String> {
+    let trimmed_name = name.trim().to_string();
+    if trimmed_name.is_empty() {
+        return Err("Sync name cannot be empty".to_string());
+    }
+    let mut syncs = read_saved_syncs_file(&app)?;
+    static ID_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let saved_id = id.unwrap_or_else(|| {
+        let ms = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let seq = ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        format!("{}-{}", ms, seq)
+    });
+    let saved = SavedSync {
+        id: saved_id.clone(),
+        name: trimmed_name,
+        options,
+    };
+    if let Some(pos) = syncs.iter().position(|s| s.id == saved_id) {
+        syncs[pos] = saved.clone();
+    } else {
+        syncs.push(saved.clone());
+    }
+    write_saved_syncs_file(&app, &syncs)?;
+    Ok(saved)
+}
+
+#[tauri::command]
+async fn delete_saved_sync(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let mut syncs = read_saved_syncs_file(&app)?;
+    syncs.retain(|s| s.id != id);
+    write_saved_syncs_file(&app, &syncs)
+}
+
+fn compute_local_md5(path: &std::path::Path) -> Option<String> {
+    use std::io::Read;
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut context = md5::Context::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let bytes_read = file.read(&mut buffer).ok()?;
+        if bytes_read == 0 {
+            break;
+        }
+        context.consume(&buffer[..bytes_read]);
+    }
+    Some(format!("{:x}", context.compute()))
+}
+
+// md5 of a file on the device, from `md5sum`
+async fn get_device_md5(app: &tauri::AppHandle, device_id: &str, device_path: &str) -> Option<String> {
+    let escaped_path = device_path.replace("'", "'\\''");
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &format!("md5sum '{}' 2>/dev/null", escaped_path)])
+        .output()
+        .await
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 32)
+        .map(|hash| hash.to_lowercase())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullVerification {
+    pub verified: bool,
+    pub local_hash: Option<String>,
+    pub device_hash: Option<String>,
+    pub retried: bool,
+}
+
+// Pull a file and confirm the local copy matches the device via md5
+// The device-side hash runs while the pull is in progress; on mismatch the pull is retried once if requested
+#[tauri::command]
+async fn pull_and_verify(
+    app: tauri::AppHandle,
+    device_id: String,
+    file_path: String,
+    local_dest: String,
+    retry_on_mismatch: bool,
+) -> Result<PullVerification, String> {
+    let device_hash_task = {
+        let app = app.clone();
+        let device_id = device_id.clone();
+        let file_path = file_path.clone();
+        tauri::async_runtime::spawn(async move { get_device_md5(&app, &device_id, &file_path).await })
+    };
+
+    download_file(app.clone(), device_id.clone(), file_path.clone(), local_dest.clone(), false).await?;
+    let mut local_hash = compute_local_md5(std::path::Path::new(&local_dest));
+
+    let device_hash = device_hash_task
+        .await
+        .map_err(|e| format!("Failed to hash file on device: {}", e))?;
+    if device_hash.is_none() {
+        return Err(format!("Could not compute md5 on device for {}", file_path));
+    }
+
+    let mut retried = false;
+    if local_hash != device_hash && retry_on_mismatch {
+        retried = true;
+        download_file(app.clone(), device_id.clone(), file_path.clone(), local_dest.clone(), false).await?;
+        local_hash = compute_local_md5(std::path::Path::new(&local_dest));
+    }
+
+    Ok(PullVerification {
+        verified: local_hash.is_some() && local_hash == device_hash,
+        local_hash,
+        device_hash,
+        retried,
+    })
+}
+
+/// Returns true if a file/directory should be excluded from sync operations.
+fn is_sync_excluded(name: &str) -> bool {
+    // macOS AppleDouble resource fork files (e.g. "._IMG001.jpg")
+    if name.starts_with("._") {
+        return true;
+    }
+    matches!(
+        name,
+        ".DS_Store"
+            | ".Spotlight-V100"
+            | ".Trashes"
+            | ".fseventsd"
+            | ".TemporaryItems"
+            | "Thumbs.db"
+            | "desktop.ini"
+            | ".thumbnails"
+            | ".thumbdata3"
+            | ".thumbdata4"
+            | ".nomedia"
+    )
+}
+
+/// Normalize file patterns: strip device_path prefix and append /**/* for bare directories.
+fn normalize_patterns(patterns: &[String], device_path: &str) -> Vec<String> {
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    let prefix = device_path.trim_end_matches('/');
+    patterns.iter().map(|p| {
+        // Strip device path prefix if present (user may paste absolute paths)
+        let stripped = if p.starts_with(prefix) {
+            p[prefix.len()..].trim_start_matches('/').to_string()
+        } else {
+            p.trim_start_matches('/').to_string()
+        };
+        // Strip trailing slash
+        let stripped = stripped.trim_end_matches('/').to_string();
+        // If pattern has no glob characters, treat as directory: append /**/*
+        if !stripped.contains('*') && !stripped.contains('?') && !stripped.contains('[') {
+            format!("{}/**/*", stripped)
+        } else {
+            stripped
+        }
+    }).collect()
+}
+
+fn matches_any_pattern(rel_path: &str, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return true;
+    }
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+    patterns.iter().any(|p| {
+        glob::Pattern::new(p)
+            .map(|pat| pat.matches_with(rel_path, options))
+            .unwrap_or(false)
+    })
+}
+
+// List files on the local Mac filesystem for sync
+#[tauri::command]
+fn list_local_files(path: String, recursive: bool, match_mode: String, file_patterns: Vec<String>) -> Result<Vec<FileMetadata>, String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err(format!("Local path does not exist: {}", path));
+    }
+
+    let mut result = Vec::new();
+
+    if recursive {
+        for entry in walkdir::WalkDir::new(&root)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| {
+                let name = e.file_name().to_str().unwrap_or("");
+                !is_sync_excluded(name)
+            })
+            .filter_map(|e| e.ok())
+        {
+            let rel_path = entry.path().strip_prefix(&root)
+                .map_err(|e| format!("Path error: {}", e))?
+                .to_string_lossy()
+                .to_string();
+
+            if rel_path.is_empty() {
+                continue;
+            }
+
+            let metadata = entry.metadata()
+                .map_err(|e| format!("Failed to read metadata for {}: {}", rel_path, e))?;
+
+            // Skip directories when filtering by patterns (only match files)
+            if !metadata.is_dir() && !matches_any_pattern(&rel_path, &file_patterns) {
+                continue;
+            }
+
+            let modified_time = metadata.modified()
+                .unwrap_or(UNIX_EPOCH)
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            let md5_hash = if match_mode == "content" && !metadata.is_dir() {
+                compute_local_md5(entry.path())
+            } else {
+                None
+            };
+
+            result.push(FileMetadata {
+                relative_path: rel_path,
+                size: metadata.len(),
+                modified_time,
+                is_directory: metadata.is_dir(),
+                md5_hash,
+            });
+        }
+    } else {
+        let entries = fs::read_dir(&root)
+            .map_err(|e| format!("Failed to read directory {}: {}", path, e))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            let metadata = entry.metadata()
+                .map_err(|e| format!("Failed to read metadata: {}", e))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if is_sync_excluded(&name) {
+                continue;
+            }
+
+            if !metadata.is_dir() && !matches_any_pattern(&name, &file_patterns) {
+                continue;
+            }
+
+            let modified_time = metadata.modified()
+                .unwrap_or(UNIX_EPOCH)
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            let md5_hash = if match_mode == "content" && !metadata.is_dir() {
+                compute_local_md5(&root.join(&name))
+            } else {
+                None
+            };
+
+            result.push(FileMetadata {
+                relative_path: name,
+                size: metadata.len(),
+                modified_time,
+                is_directory: metadata.is_dir(),
+                md5_hash,
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+// List files on the Android device for sync
+#[tauri::command]
+async fn list_device_files_for_sync(
+    app: tauri::AppHandle,
+    device_id: String,
+    path: String,
+    recursive: bool,
+    match_mode: String,
+    file_patterns: Vec<String>,
+) -> Result<Vec<FileMetadata>, String> {
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+    let escaped_path = path.replace("'", "'\\''");
+
+    let mut result = Vec::new();
+
+    if recursive {
+        // When patterns are provided, only scan targeted directories instead of the entire root
+        let scan_dirs: Vec<String> = if !file_patterns.is_empty() {
+            // Extract directory prefixes from patterns (everything before the first glob character)
+            let mut dirs: Vec<String> = file_patterns.iter().map(|p| {
+                // Find the directory portion before any glob characters
+                let glob_pos = p.find(|c: char| c == '*' || c == '?' || c == '[')
+                    .unwrap_or(p.len());
+                let dir_part = &p[..glob_pos];
+                // Trim to last slash to get the directory
+                let dir = if let Some(slash_pos) = dir_part.rfind('/') {
+                    &dir_part[..slash_pos]
+                } else {
+                    ""
+                };
+                if dir.is_empty() {
+                    path.clone()
+                } else {
+                    format!("{}/{}", path, dir)
+                }
+            }).collect();
+            dirs.sort();
+            dirs.dedup();
+            // Remove directories that are subdirectories of other entries
+            let mut filtered: Vec<String> = Vec::new();
+            for dir in &dirs {
+                if !filtered.iter().any(|parent| dir.starts_with(&format!("{}/", parent))) {
+                    filtered.push(dir.clone());
+                }
+            }
+            filtered
+        } else {
+            vec![path.clone()]
+        };
+
+        #[cfg(debug_assertions)]
+        {
+            eprintln!("[sync] scan_dirs: {:?}", scan_dirs);
+            eprintln!("[sync] file_patterns for filtering: {:?}", file_patterns);
+        }
+
+        // Use find + stat in one command per scan_dir. Each output line is self-contained
+        // using '|' as delimiter: size|mtime|type|full_path
+        // This avoids the fragile batch-stat approach where one failed stat misaligns all subsequent entries.
+        for scan_dir in &scan_dirs {
+            let escaped_scan_dir = scan_dir.replace("'", "'\\''");
+            let find_stat_command = format!(
+                "find '{}' -mindepth 1 \\( -type f -o -type d \\) -exec stat -c '%s|%Y|%F|%n' {{}} + 2>/dev/null",
+                escaped_scan_dir
+            );
+
+            let output = shell
+                .command(&adb_cmd)
+                .args(["-s", &device_id, "shell", &find_stat_command])
+                .output()
+                .await
+                .map_err(|e| format!("Failed to list device files: {}", e))?;
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+
+            #[cfg(debug_assertions)]
+            eprintln!("[sync] find+stat returned {} lines for {}", stdout.lines().count(), scan_dir);
+
+            for line in stdout.lines() {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+
+                // Parse: size|mtime|type|full_path
+                let parts: Vec<&str> = line.splitn(4, '|').collect();
+                if parts.len() < 4 {
+                    continue;
+                }
+
+                let size = parts[0].parse::<u64>().unwrap_or(0);
+                let mtime = parts[1].parse::<u64>().unwrap_or(0);
+                let is_dir = parts[2].contains("directory");
+                let file_path = parts[3];
+
+                // Compute relative path
+                let rel_path = if file_path.starts_with(&path) {
+                    file_path[path.len()..].trim_start_matches('/').to_string()
+                } else {
+                    file_path.to_string()
+                };
+
+                if rel_path.is_empty() {
+                    continue;
+                }
+
+                // Skip if any path component is excluded
+                if rel_path.split('/').any(|part| is_sync_excluded(part)) {
+                    continue;
+                }
+
+                // Apply glob pattern filtering (skip directories from filtering)
+                if !is_dir && !matches_any_pattern(&rel_path, &file_patterns) {
+                    continue;
+                }
+
+                let md5_hash = if match_mode == "content" && !is_dir {
+                    let escaped_file = file_path.replace("'", "'\\''");
+                    let md5_cmd = format!("md5sum '{}' 2>/dev/null", escaped_file);
+                    let md5_output = shell
+                        .command(&adb_cmd)
+                        .args(["-s", &device_id, "shell", &md5_cmd])
+                        .output()
+                        .await
+                        .ok();
+                    md5_output.and_then(|o| {
+                        let out = String::from_utf8_lossy(&o.stdout).trim().to_string();
+                        out.split_whitespace().next().map(|s| s.to_string())
+                    })
+                } else {
+                    None
+                };
+
+                result.push(FileMetadata {
+                    relative_path: rel_path,
+                    size,
+                    modified_time: mtime,
+                    is_directory: is_dir,
+                    md5_hash,
+                });
+            }
+        }
+        // Deduplicate results from overlapping scan_dirs
+        result.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        result.dedup_by(|a, b| a.relative_path == b.relative_path);
+    } else {
+        // Flat listing: use ls -la and stat for mtime
+        let ls_command = format!("ls -la '{}'", escaped_path);
+
+        let output = shell
+            .command(&adb_cmd)
+            .args(["-s", &device_id, "shell", &ls_command])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to list device files: {}", e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let entries: Vec<FileEntry> = stdout
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter(|line| !line.starts_with("total"))
+            .filter_map(|line| parse_ls_line(line))
+            .collect();
+
+        for entry in &entries {
+            if is_sync_excluded(&entry.name) {
+                continue;
+            }
+
+            if !entry.is_directory && !matches_any_pattern(&entry.name, &file_patterns) {
+                continue;
+            }
+
+            let file_full_path = format!("{}/{}", path, entry.name);
+            let escaped_file = file_full_path.replace("'", "'\\''");
+            let stat_cmd = format!("stat -c '%s %Y' '{}' 2>/dev/null", escaped_file);
+
+            let stat_output = shell
+                .command(&adb_cmd)
+                .args(["-s", &device_id, "shell", &stat_cmd])
+                .output()
+                .await
+                .ok();
+
+            let (size, mtime) = if let Some(stat_out) = stat_output {
+                let stat_str = String::from_utf8_lossy(&stat_out.stdout).trim().to_string();
+                let parts: Vec<&str> = stat_str.split(' ').collect();
+                if parts.len() >= 2 {
+                    (
+                        parts[0].parse::<u64>().unwrap_or(0),
+                        parts[1].parse::<u64>().unwrap_or(0),
+                    )
+                } else {
+                    (entry.size.parse::<u64>().unwrap_or(0), 0)
+                }
+            } else {
+                (entry.size.parse::<u64>().unwrap_or(0), 0)
+            };
+
+            let md5_hash = if match_mode == "content" && !entry.is_directory {
+                let md5_cmd = format!("md5sum '{}' 2>/dev/null", escaped_file);
+                let md5_output = shell
+                    .command(&adb_cmd)
+                    .args(["-s", &device_id, "shell", &md5_cmd])
+                    .output()
+                    .await
+                    .ok();
+                md5_output.and_then(|o| {
+                    let out = String::from_utf8_lossy(&o.stdout).trim().to_string();
+                    out.split_whitespace().next().map(|s| s.to_string())
+                })
+            } else {
+                None
+            };
+
+            result.push(FileMetadata {
+                relative_path: entry.name.clone(),
+                size,
+                modified_time: mtime,
+                is_directory: entry.is_directory,
+                md5_hash,
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+// Compute sync actions from two file lists
+fn compute_sync_actions(
+    local_files: &[FileMetadata],
+    device_files: &[FileMetadata],
+    direction: &SyncDirection,
+    delete_missing: bool,
+    match_mode: &str,
+) -> Vec<SyncAction> {
+    if match_mode == "content" {
+        return compute_sync_actions_by_content(local_files, device_files, direction, delete_missing);
+    }
+
+    let local_map: HashMap<&str, &FileMetadata> = local_files
+        .iter()
+        .filter(|f| !f.is_directory)
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+
+    let device_map: HashMap<&str, &FileMetadata> = device_files
+        .iter()
+        .filter(|f| !f.is_directory)
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+
+    let mut actions = Vec::new();
+
+    match direction {
+        SyncDirection::PhoneToComputer => {
+            // Source = device, Dest = local
+            for (rel_path, dev_file) in &device_map {
+                if let Some(local_file) = local_map.get(rel_path) {
+                    if dev_file.size != local_file.size || dev_file.modified_time > local_file.modified_time {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "update".to_string(),
+                            direction: "\u{2192} Computer".to_string(),
+                            size: dev_file.size,
+                            reason: "Device file is newer or different size".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                } else {
+                    actions.push(SyncAction {
+                        file_path: rel_path.to_string(),
+                        action_type: "copy".to_string(),
+                        direction: "\u{2192} Computer".to_string(),
+                        size: dev_file.size,
+                        reason: "File only exists on device".to_string(),
+                        rename_from: None,
+                    });
+                }
+            }
+            if delete_missing {
+                for (rel_path, local_file) in &local_map {
+                    if !device_map.contains_key(rel_path) {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "delete".to_string(),
+                            direction: "\u{2716} Computer".to_string(),
+                            size: local_file.size,
+                            reason: "File not on device, will be deleted locally".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                }
+            }
+        }
+        SyncDirection::ComputerToPhone => {
+            // Source = local, Dest = device
+            for (rel_path, local_file) in &local_map {
+                if let Some(dev_file) = device_map.get(rel_path) {
+                    if local_file.size != dev_file.size || local_file.modified_time > dev_file.modified_time {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "update".to_string(),
+                            direction: "\u{2192} Phone".to_string(),
+                            size: local_file.size,
+                            reason: "Local file is newer or different size".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                } else {
+                    actions.push(SyncAction {
+                        file_path: rel_path.to_string(),
+                        action_type: "copy".to_string(),
+                        direction: "\u{2192} Phone".to_string(),
+                        size: local_file.size,
+                        reason: "File only exists locally".to_string(),
+                        rename_from: None,
+                    });
+                }
+            }
+            if delete_missing {
+                for (rel_path, dev_file) in &device_map {
+                    if !local_map.contains_key(rel_path) {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "delete".to_string(),
+                            direction: "\u{2716} Phone".to_string(),
+                            size: dev_file.size,
+                            reason: "File not on computer, will be deleted from device".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                }
+            }
+        }
+        SyncDirection::BothWays => {
+            // Merge: files unique to one side get copied, conflicts resolved by mtime
+            let mut all_paths: std::collections::HashSet<&str> = std::collections::HashSet::new();
+            all_paths.extend(local_map.keys());
+            all_paths.extend(device_map.keys());
+
+            for rel_path in all_paths {
+                match (local_map.get(rel_path), device_map.get(rel_path)) {
+                    (Some(_local_file), None) => {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "copy".to_string(),
+                            direction: "\u{2192} Phone".to_string(),
+                            size: _local_file.size,
+                            reason: "File only exists locally".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    (None, Some(_dev_file)) => {
+                        actions.push(SyncAction {
+                            file_path: rel_path.to_string(),
+                            action_type: "copy".to_string(),
+                            direction: "\u{2192} Computer".to_string(),
+                            size: _dev_file.size,
+                            reason: "File only exists on device".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    (Some(local_file), Some(dev_file)) => {
+                        if local_file.size != dev_file.size || local_file.modified_time != dev_file.modified_time {
+                            if local_file.modified_time >= dev_file.modified_time {
+                                actions.push(SyncAction {
+                                    file_path: rel_path.to_string(),
+                                    action_type: "update".to_string(),
+                                    direction: "\u{2192} Phone".to_string(),
+                                    size: local_file.size,
+                                    reason: "Local file is newer".to_string(),
+                                    rename_from: None,
+                                });
+                            } else {
+                                actions.push(SyncAction {
+                                    file_path: rel_path.to_string(),
+                                    action_type: "update".to_string(),
+                                    direction: "\u{2192} Computer".to_string(),
+                                    size: dev_file.size,
+                                    reason: "Device file is newer".to_string(),
+                                    rename_from: None,
+                                });
+                            }
+                        }
+                        // Same size and mtime → skip (no action needed)
+                    }
+                    (None, None) => {} // shouldn't happen
+                }
+            }
+        }
+    }
+
+    actions.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    actions
+}
+
+// Content-based (MD5) sync action computation
+fn compute_sync_actions_by_content(
+    local_files: &[FileMetadata],
+    device_files: &[FileMetadata],
+    direction: &SyncDirection,
+    delete_missing: bool,
+) -> Vec<SyncAction> {
+    use std::collections::HashSet;
+
+    let mut actions = Vec::new();
+
+    // Build hash -> files maps (only files with hashes)
+    let mut local_by_hash: HashMap<&str, Vec<&FileMetadata>> = HashMap::new();
+    for f in local_files.iter().filter(|f| !f.is_directory && f.md5_hash.is_some()) {
+        local_by_hash.entry(f.md5_hash.as_ref().unwrap().as_str())
+            .or_default().push(f);
+    }
+    let mut device_by_hash: HashMap<&str, Vec<&FileMetadata>> = HashMap::new();
+    for f in device_files.iter().filter(|f| !f.is_directory && f.md5_hash.is_some()) {
+        device_by_hash.entry(f.md5_hash.as_ref().unwrap().as_str())
+            .or_default().push(f);
+    }
+
+    // Also build path maps for update detection
+    let local_by_path: HashMap<&str, &FileMetadata> = local_files.iter()
+        .filter(|f| !f.is_directory && f.md5_hash.is_some())
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+    let device_by_path: HashMap<&str, &FileMetadata> = device_files.iter()
+        .filter(|f| !f.is_directory && f.md5_hash.is_some())
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+
+    let mut handled_local: HashSet<String> = HashSet::new();
+    let mut handled_device: HashSet<String> = HashSet::new();
+
+    // Collect all unique hashes
+    let mut all_hashes: HashSet<&str> = HashSet::new();
+    all_hashes.extend(local_by_hash.keys());
+    all_hashes.extend(device_by_hash.keys());
+
+    for hash in &all_hashes {
+        let local_for_hash = local_by_hash.get(hash).cloned().unwrap_or_default();
+        let device_for_hash = device_by_hash.get(hash).cloned().unwrap_or_default();
+
+        if !local_for_hash.is_empty() && !device_for_hash.is_empty() {
+            // Hash exists on both sides
+            let lf = local_for_hash[0];
+            let df = device_for_hash[0];
+            handled_local.insert(lf.relative_path.clone());
+            handled_device.insert(df.relative_path.clone());
+
+            if lf.relative_path != df.relative_path {
+                // Same content, different path → rename
+                match direction {
+                    SyncDirection::PhoneToComputer => {
+                        // Source is device, so rename local to match device name
+                        actions.push(SyncAction {
+                            file_path: df.relative_path.clone(),
+                            action_type: "rename".to_string(),
+                            direction: "\u{2192} Computer".to_string(),
+                            size: df.size,
+                            reason: format!("Renamed: {} \u{2192} {}", lf.relative_path, df.relative_path),
+                            rename_from: Some(lf.relative_path.clone()),
+                        });
+                    }
+                    SyncDirection::ComputerToPhone => {
+                        // Source is local, so rename device to match local name
+                        actions.push(SyncAction {
+                            file_path: lf.relative_path.clone(),
+                            action_type: "rename".to_string(),
+                            direction: "\u{2192} Phone".to_string(),
+                            size: lf.size,
+                            reason: format!("Renamed: {} \u{2192} {}", df.relative_path, lf.relative_path),
+                            rename_from: Some(df.relative_path.clone()),
+                        });
+                    }
+                    SyncDirection::BothWays => {
+                        // Rename the older side to match the newer
+                        if lf.modified_time >= df.modified_time {
+                            actions.push(SyncAction {
+                                file_path: lf.relative_path.clone(),
+                                action_type: "rename".to_string(),
+                                direction: "\u{2192} Phone".to_string(),
+                                size: lf.size,
+                                reason: format!("Renamed: {} \u{2192} {}", df.relative_path, lf.relative_path),
+                                rename_from: Some(df.relative_path.clone()),
+                            });
+                        } else {
+                            actions.push(SyncAction {
+                                file_path: df.relative_path.clone(),
+                                action_type: "rename".to_string(),
+                                direction: "\u{2192} Computer".to_string(),
+                                size: df.size,
+                                reason: format!("Renamed: {} \u{2192} {}", lf.relative_path, df.relative_path),
+                                rename_from: Some(lf.relative_path.clone()),
+                            });
+                        }
+                    }
+                }
+            }
+            // Same hash + same path → skip (identical, no action)
+
+            // Mark additional files with same hash as handled
+            for f in &local_for_hash { handled_local.insert(f.relative_path.clone()); }
+            for f in &device_for_hash { handled_device.insert(f.relative_path.clone()); }
+        } else if !local_for_hash.is_empty() {
+            // Hash only on local side
+            for lf in &local_for_hash {
+                handled_local.insert(lf.relative_path.clone());
+                match direction {
+                    SyncDirection::ComputerToPhone | SyncDirection::BothWays => {
+                        actions.push(SyncAction {
+                            file_path: lf.relative_path.clone(),
+                            action_type: "copy".to_string(),
+                            direction: "\u{2192} Phone".to_string(),
+                            size: lf.size,
+                            reason: "File only exists locally".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    SyncDirection::PhoneToComputer => {
+                        // Source is device, local-only file: delete if enabled
+                        if delete_missing {
+                            actions.push(SyncAction {
+                                file_path: lf.relative_path.clone(),
+                                action_type: "delete".to_string(),
+                                direction: "\u{2716} Computer".to_string(),
+                                size: lf.size,
+                                reason: "File not on device, will be deleted locally".to_string(),
+                                rename_from: None,
+                            });
+                        }
+                    }
+                }
+            }
+        } else {
+            // Hash only on device side
+            for df in &device_for_hash {
+                handled_device.insert(df.relative_path.clone());
+                match direction {
+                    SyncDirection::PhoneToComputer | SyncDirection::BothWays => {
+                        actions.push(SyncAction {
+                            file_path: df.relative_path.clone(),
+                            action_type: "copy".to_string(),
+                            direction: "\u{2192} Computer".to_string(),
+                            size: df.size,
+                            reason: "File only exists on device".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    SyncDirection::ComputerToPhone => {
+                        if delete_missing {
+                            actions.push(SyncAction {
+                                file_path: df.relative_path.clone(),
+                                action_type: "delete".to_string(),
+                                direction: "\u{2716} Phone".to_string(),
+                                size: df.size,
+                                reason: "File not on computer, will be deleted from device".to_string(),
+                                rename_from: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Handle files with same path but different hash (updates) that weren't matched above
+    for (path, lf) in &local_by_path {
+        if handled_local.contains(*path) { continue; }
+        if let Some(df) = device_by_path.get(path) {
+            if lf.md5_hash != df.md5_hash {
+                handled_local.insert(path.to_string());
+                handled_device.insert(path.to_string());
+                match direction {
+                    SyncDirection::PhoneToComputer => {
+                        actions.push(SyncAction {
+                            file_path: path.to_string(),
+                            action_type: "update".to_string(),
+                            direction: "\u{2192} Computer".to_string(),
+                            size: df.size,
+                            reason: "Device file has different content".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    SyncDirection::ComputerToPhone => {
+                        actions.push(SyncAction {
+                            file_path: path.to_string(),
+                            action_type: "update".to_string(),
+                            direction: "\u{2192} Phone".to_string(),
+                            size: lf.size,
+                            reason: "Local file has different content".to_string(),
+                            rename_from: None,
+                        });
+                    }
+                    SyncDirection::BothWays => {
+                        if lf.modified_time >= df.modified_time {
+                            actions.push(SyncAction {
+                                file_path: path.to_string(),
+                                action_type: "update".to_string(),
+                                direction: "\u{2192} Phone".to_string(),
+                                size: lf.size,
+                                reason: "Local file is newer".to_string(),
+                                rename_from: None,
+                            });
+                        } else {
+                            actions.push(SyncAction {
+                                file_path: path.to_string(),
+                                action_type: "update".to_string(),
+                                direction: "\u{2192} Computer".to_string(),
+                                size: df.size,
+                                reason: "Device file is newer".to_string(),
+                                rename_from: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    actions.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    actions
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileConflict {
+    pub relative_path: String,
+    pub local: FileMetadata,
+    pub device: FileMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectoryComparison {
+    pub device_only: Vec<FileMetadata>,
+    pub local_only: Vec<FileMetadata>,
+    pub conflicts: Vec<FileConflict>,
+    pub identical_count: u32,
+}
+
+// Diff two file lists by relative path; files on both sides with differing size/mtime are conflicts
+fn compare_file_lists(local_files: &[FileMetadata], device_files: &[FileMetadata]) -> DirectoryComparison {
+    let local_map: HashMap<&str, &FileMetadata> = local_files
+        .iter()
+        .filter(|f| !f.is_directory)
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+
+    let device_map: HashMap<&str, &FileMetadata> = device_files
+        .iter()
+        .filter(|f| !f.is_directory)
+        .map(|f| (f.relative_path.as_str(), f))
+        .collect();
+
+    let mut device_only = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut identical_count: u32 = 0;
+
+    for (rel_path, dev_file) in &device_map {
+        match local_map.get(rel_path) {
+            Some(local_file) => {
+                if local_file.size != dev_file.size || local_file.modified_time != dev_file.modified_time {
+                    conflicts.push(FileConflict {
+                        relative_path: rel_path.to_string(),
+                        local: (*local_file).clone(),
+                        device: (*dev_file).clone(),
+                    });
+                } else {
+                    identical_count += 1;
+                }
+            }
+            None => device_only.push((*dev_file).clone()),
+        }
+    }
+
+    let mut local_only: Vec<FileMetadata> = local_map
+        .iter()
+        .filter(|(rel_path, _)| !device_map.contains_key(*rel_path))
+        .map(|(_, f)| (*f).clone())
+        .collect();
+
+    device_only.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    local_only.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    conflicts.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    DirectoryComparison {
+        device_only,
+        local_only,
+        conflicts,
+        identical_count,
+    }
+}
+
+// Compare a device folder with a local folder without changing anything
+#[tauri::command]
+async fn compare_directories(
+    app: tauri::AppHandle,
+    device_id: String,
+    remote_path: String,
+    local_path: String,
+) -> Result<DirectoryComparison, String> {
+    let local_files = list_local_files(local_path, true, "filename".to_string(), Vec::new())?;
+    let device_files = list_device_files_for_sync(
+        app,
+        device_id,
+        remote_path,
+        true,
+        "filename".to_string(),
+        Vec::new(),
+    ).await?;
+
+    Ok(compare_file_lists(&local_files, &device_files))
+}
+
+// Preview sync: compute what would happen without executing
+#[tauri::command]
+async fn preview_sync(
+    app: tauri::AppHandle,
+    device_id: String,
+    options: SyncOptions,
+) -> Result<SyncPreview, String> {
+    let patterns = normalize_patterns(&options.file_patterns, &options.device_path);
+    // Force recursive when patterns contain path separators
+    let recursive = options.recursive || patterns.iter().any(|p| p.contains('/'));
+
+    #[cfg(debug_assertions)]
+    {
+        eprintln!("[sync] raw file_patterns: {:?}", options.file_patterns);
+        eprintln!("[sync] normalized patterns: {:?}", patterns);
+        eprintln!("[sync] recursive: {}", recursive);
+    }
+
+    let local_files = list_local_files(options.local_path.clone(), recursive, options.match_mode.clone(), patterns.clone())?;
+    let device_files = list_device_files_for_sync(
+        app,
+        device_id,
+        options.device_path.clone(),
+        recursive,
+        options.match_mode.clone(),
+        patterns,
+    ).await?;
+
+    #[cfg(debug_assertions)]
+    {
+        eprintln!("[sync] local_files count: {}", local_files.len());
+        eprintln!("[sync] device_files count: {}", device_files.len());
+        if !device_files.is_empty() {
+            eprintln!("[sync] first device file: {:?}", device_files[0].relative_path);
+        }
+        if !local_files.is_empty() {
+            eprintln!("[sync] first local file: {:?}", local_files[0].relative_path);
+        }
+    }
+
+    let actions = compute_sync_actions(&local_files, &device_files, &options.direction, options.delete_missing, &options.match_mode);
+
+    let mut total_transfer_bytes: u64 = 0;
+    let mut copy_count: u32 = 0;
+    let mut update_count: u32 = 0;
+    let mut delete_count: u32 = 0;
+    let mut skip_count: u32 = 0;
+    let mut rename_count: u32 = 0;
+
+    for action in &actions {
+        match action.action_type.as_str() {
+            "copy" => {
+                copy_count += 1;
+                total_transfer_bytes += action.size;
+            }
+            "update" => {
+                update_count += 1;
+                total_transfer_bytes += action.size;
+            }
+            "delete" => {
+                delete_count += 1;
+            }
+            "rename" => {
+                rename_count += 1;
+            }
+            _ => {
+                skip_count += 1;
+            }
+        }
+    }
+
+    Ok(SyncPreview {
+        actions,
+        total_transfer_bytes,
+        copy_count,
+        update_count,
+        delete_count,
+        skip_count,
+        rename_count,
+    })
+}
+
+// Execute sync: perform the actual file transfers
+#[tauri::command]
+async fn execute_sync(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    device_id: String,
+    options: SyncOptions,
+) -> Result<SyncResult, String> {
+    if is_critical_system_path(&options.device_path) {
+        return Err(format!("Cannot sync to critical system path: {}", options.device_path));
+    }
+
+    let patterns = normalize_patterns(&options.file_patterns, &options.device_path);
+    // Force recursive when patterns contain path separators
+    let recursive = options.recursive || patterns.iter().any(|p| p.contains('/'));
+
+    let local_files = list_local_files(options.local_path.clone(), recursive, options.match_mode.clone(), patterns.clone())?;
+    let device_files = list_device_files_for_sync(
+        app.clone(),
+        device_id.clone(),
+        options.device_path.clone(),
+        recursive,
+        options.match_mode.clone(),
+        patterns,
+    ).await?;
+
+    let actions = compute_sync_actions(&local_files, &device_files, &options.direction, options.delete_missing, &options.match_mode);
+
+    // Build timestamp lookup maps for preserving file modification times
+    let device_mtime_map: HashMap<String, u64> = device_files.iter()
+        .map(|f| (f.relative_path.clone(), f.modified_time))
+        .collect();
+    let local_mtime_map: HashMap<String, u64> = local_files.iter()
+        .map(|f| (f.relative_path.clone(), f.modified_time))
+        .collect();
+
+    let total_count = actions.len() as u32;
+    let total_bytes: u64 = actions
+        .iter()
+        .filter(|a| a.action_type == "copy" || a.action_type == "update")
+        .map(|a| a.size)
+        .sum();
+
+    let mut success_count: u32 = 0;
+    let mut skip_count: u32 = 0;
+    let mut error_count: u32 = 0;
+    let mut errors: Vec<String> = Vec::new();
+    let mut completed_bytes: u64 = 0;
+
+    let shell = app.shell();
+    let adb_cmd = get_adb_command();
+
+    for (i, action) in actions.iter().enumerate() {
+        // Emit progress
+        let _ = window.emit("sync-progress", SyncProgress {
+            current_file: action.file_path.clone(),
+            completed_count: i as u32,
+            total_count,
+            completed_bytes,
+            total_bytes,
+        });
+
+        let result = match action.action_type.as_str() {
+            "copy" | "update" => {
+                if action.direction.contains("Computer") {
+                    // Pull from device to local
+                    let device_file = format!("{}/{}", options.device_path, action.file_path);
+                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
+
+                    // Create parent directories
+                    if let Some(parent) = local_file.parent() {
+                        let _ = fs::create_dir_all(parent);
+                    }
+
+                    let output = shell
+                        .command(&adb_cmd)
+                        .args(["-s", &device_id, "pull", &device_file, local_file.to_str().unwrap_or("")])
+                        .output()
+                        .await;
+
+                    match output {
+                        Ok(o) if o.status.success() => {
+                            // Restore the source file's modification time
+                            if let Some(&mtime) = device_mtime_map.get(&action.file_path) {
+                                let mtime_system = UNIX_EPOCH + Duration::from_secs(mtime);
+                                if let Ok(file) = fs::File::open(&local_file) {
+                                    let _ = file.set_modified(mtime_system);
+                                }
+                                // Set creation/birth time on macOS to match the source file
+                                let _ = set_creation_time(&std::path::PathBuf::from(&local_file), mtime);
+                            }
+                            record_transfer(
+                                "sync",
+                                "download",
+                                fs::metadata(&local_file).map(|m| m.len()).unwrap_or(0),
+                                1,
+                            );
+                            Ok(())
+                        }
+                        Ok(o) => Err(format!("Pull failed: {}", String::from_utf8_lossy(&o.stderr))),
+                        Err(e) => Err(format!("Pull error: {}", e)),
+                    }
+                } else if action.direction.contains("Phone") {
+                    // Push from local to device
+                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
+                    let device_file = format!("{}/{}", options.device_path, action.file_path);
+
+                    // Create parent directory on device if needed
+                    if let Some(parent) = PathBuf::from(&action.file_path).parent() {
+                        let parent_str = parent.to_string_lossy();
+                        if !parent_str.is_empty() {
+                            let device_parent = format!("{}/{}", options.device_path, parent_str);
+                            let escaped = device_parent.replace("'", "'\\''");
+                            let mkdir_cmd = format!("mkdir -p '{}'", escaped);
+                            let _ = shell
+                                .command(&adb_cmd)
+                                .args(["-s", &device_id, "shell", &mkdir_cmd])
+                                .output()
+                                .await;
+                        }
+                    }
+
+                    let output = shell
+                        .command(&adb_cmd)
+                        .args(["-s", &device_id, "push", local_file.to_str().unwrap_or(""), &device_file])
+                        .output()
+                        .await;
+
+                    match output {
+                        Ok(o) if o.status.success() => {
+                            // Restore the source file's modification time on the device
+                            if let Some(&mtime) = local_mtime_map.get(&action.file_path) {
+                                let escaped_path = device_file.replace("'", "'\\''");
+                                let touch_cmd = format!("touch -d @{} '{}'", mtime, escaped_path);
+                                let _ = shell
+                                    .command(&adb_cmd)
+                                    .args(["-s", &device_id, "shell", &touch_cmd])
+                                    .output()
+                                    .await;
+                            }
+                            record_transfer(
+                                "sync",
+                                "upload",
+                                fs::metadata(&local_file).map(|m| m.len()).unwrap_or(0),
+                                1,
+                            );
+                            Ok(())
+                        }
+                        Ok(o) => Err(format!("Push failed: {}", String::from_utf8_lossy(&o.stderr))),
+                        Err(e) => Err(format!("Push error: {}", e)),
+                    }
+                } else {
+                    Ok(()) // skip
+                }
+            }
+            "delete" => {
+                if action.direction.contains("Computer") {
+                    // Delete local file
+                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
+                    fs::remove_file(&local_file)
+                        .map_err(|e| format!("Failed to delete local file: {}", e))
+                } else if action.direction.contains("Phone") {
+                    // Delete device file
+                    let device_file = format!("{}/{}", options.device_path, action.file_path);
+                    let escaped = device_file.replace("'", "'\\''");
+                    let rm_cmd = format!("rm '{}'", escaped);
+
+                    let output = shell
+                        .command(&adb_cmd)
+                        .args(["-s", &device_id, "shell", &rm_cmd])
+                        .output()
+                        .await;
+
+                    match output {
+                        Ok(o) if o.status.success() => Ok(()),
+                        Ok(o) => Err(format!("Delete failed: {}", String::from_utf8_lossy(&o.stderr))),
+                        Err(e) => Err(format!("Delete error: {}", e)),
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+            "rename" => {
+                if let Some(ref old_rel_path) = action.rename_from {
+                    if action.direction.contains("Computer") {
+                        // Rename local file
+                        let old_local = PathBuf::from(&options.local_path).join(old_rel_path);
+                        let new_local = PathBuf::from(&options.local_path).join(&action.file_path);
+                        if let Some(parent) = new_local.parent() {
+                            let _ = fs::create_dir_all(parent);
+                        }
+                        fs::rename(&old_local, &new_local)
+                            .map_err(|e| format!("Rename failed: {}", e))
+                    } else if action.direction.contains("Phone") {
+                        // Rename on device
+                        let old_device = format!("{}/{}", options.device_path, old_rel_path);
+                        let new_device = format!("{}/{}", options.device_path, action.file_path);
+                        let escaped_old = old_device.replace("'", "'\\''");
+                        let escaped_new = new_device.replace("'", "'\\''");
+                        // Create parent dir on device if needed
+                        if let Some(parent) = PathBuf::from(&action.file_path).parent() {
+                            let parent_str = parent.to_string_lossy();
+                            if !parent_str.is_empty() {
+                                let device_parent = format!("{}/{}", options.device_path, parent_str);
+                                let escaped = device_parent.replace("'", "'\\''");
+                                let mkdir_cmd = format!("mkdir -p '{}'", escaped);
+                                let _ = shell
+                                    .command(&adb_cmd)
+                                    .args(["-s", &device_id, "shell", &mkdir_cmd])
+                                    .output()
+                                    .await;
+                            }
+                        }
+                        let mv_cmd = format!("mv '{}' '{}'", escaped_old, escaped_new);
+                        let output = shell
+                            .command(&adb_cmd)
+                            .args(["-s", &device_id, "shell", &mv_cmd])
+                            .output()
+                            .await;
+                        match output {
+                            Ok(o) if o.status.success() => Ok(()),
+                            Ok(o) => Err(format!("Rename failed: {}", String::from_utf8_lossy(&o.stderr))),
+                            Err(e) => Err(format!("Rename error: {}", e)),
+                        }
+                    } else {
+                        Ok(())
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+            _ => {
+                skip_count += 1;
+                continue;
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                success_count += 1;
+                if action.action_type == "copy" || action.action_type == "update" {
+                    completed_bytes += action.size;
+                }
+            }
+            Err(e) => {
+                error_count += 1;
+                errors.push(format!("{}: {}", action.file_path, e));
+            }
+        }
+    }
+
+    // Emit final progress
+    let _ = window.emit("sync-progress", SyncProgress {
+        current_file: "Done".to_string(),
+        completed_count: total_count,
+        total_count,
+        completed_bytes,
+        total_bytes,
+    });
+
+    Ok(SyncResult {
+        success_count,
+        skip_count,
+        error_count,
+        errors,
+    })
+}
+
+// ========================
+// Device Settings
+// ========================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DeviceSettings {
+    pub last_path: Option<String>,
+    pub download_dir: Option<String>,
+    pub nickname: Option<String>,
+    pub wireless_address: Option<String>,
+    // Set when a wireless device is disconnected; cleared when it shows up again
+    pub stale: bool,
+}
+
+fn device_settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(data_dir.join("device_settings.json"))
+}
+
+fn read_device_settings_file(app: &tauri::AppHandle) -> Result<HashMap<String, DeviceSettings>, String> {
+    let path = device_settings_path(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read device settings: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse device settings: {}", e))
+}
+
+fn write_device_settings_file(
+    app: &tauri::AppHandle,
+    settings: &HashMap<String, DeviceSettings>,
+) -> Result<(), String> {
+    let path = device_settings_path(app)?;
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize device settings: {}", e))?;
+    fs::write(&path, content)
+        .map_err(|e| format!("Failed to write device settings: {}", e))
+}
+
+// Get the stored settings for a device (defaults if none were saved)
+#[tauri::command]
+async fn get_device_settings(app: tauri::AppHandle, device_id: String) -> Result<DeviceSettings, String> {
+    let all_settings = read_device_settings_file(&app)?;
+    Ok(all_settings.get(&device_id).cloned().unwrap_or_default())
+}
+
+// Set (or clear, with an empty alias) the nickname shown for a device
+#[tauri::command]
+async fn set_device_alias(app: tauri::AppHandle, device_id: String, alias: String) -> Result<(), String> {
+    let mut all_settings = read_device_settings_file(&app)?;
+    let trimmed = alias.trim();
+    all_settings.entry(device_id).or_default().nickname = if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    };
+    write_device_settings_file(&app, &all_settings)
+}
+
+// Copy the fields set in `update` over `existing`, leaving the rest alone
+// `stale` is managed by device tracking, so it is never taken from the caller
+fn merge_device_settings(existing: &mut DeviceSettings, update: DeviceSettings) {
+    if update.last_path.is_some() {
+        existing.last_path = update.last_path;
+    }
+    if update.download_dir.is_some() {
+        existing.download_dir = update.download_dir;
+    }
+    if update.nickname.is_some() {
+        existing.nickname = update.nickname;
+    }
+    if update.wireless_address.is_some() {
+        existing.wireless_address = update.wireless_address;
+    }
+}
+
+// Update the stored settings for a device, patching only the fields provided
+// e.g. `{ last_path }` keeps the nickname set by set_device_alias; returns the merged settings
+#[tauri::command]
+async fn update_device_settings(
+    app: tauri::AppHandle,
+    device_id: String,
+    settings: DeviceSettings,
+) -> Result<DeviceSettings, String> {
+    let mut all_settings = read_device_settings_file(&app)?;
+    let entry = all_settings.entry(device_id).or_default();
+    merge_device_settings(entry, settings);
+    let merged = entry.clone();
+    write_device_settings_file(&app, &all_settings)?;
+    Ok(merged)
+}
+
+// Revalidate a device after it reconnects (e.g. unplugged and plugged back in)
+// Returns the last visited path if it still exists, otherwise the storage root, and stores it as last_path
+#[tauri::command]
+async fn refresh_device(app: tauri::AppHandle, device_id: String) -> Result<String, String> {
+    // A reconnect may be a different OS build (e.g. after an update), so probe again on next use
+    if let Ok(mut cache) = DEVICE_CAPABILITIES.lock() {
+        cache.remove(&device_id);
+    }
+
+    let mut all_settings = read_device_settings_file(&app)?;
+    let last_path = all_settings.get(&device_id).and_then(|s| s.last_path.clone());
+
+    let still_exists = match &last_path {
+        Some(path) => {
+            let escaped_path = path.replace("'", "'\\''");
+            app.shell()
+                .command(get_adb_command())
+                .args(["-s", &device_id, "shell", &format!("test -d '{}' && echo exists", escaped_path)])
+                .output()
+                .await
+                .map(|o| String::from_utf8_lossy(&o.stdout).contains("exists"))
+                .unwrap_or(false)
+        }
+        None => false,
+    };
+
+    let valid_path = match last_path {
+        Some(path) if still_exists => path,
+        _ => detect_storage_path(app.clone(), device_id.clone()).await?,
+    };
+
+    let settings = all_settings.entry(device_id).or_default();
+    if settings.last_path.as_deref() != Some(valid_path.as_str()) || settings.stale {
+        settings.last_path = Some(valid_path.clone());
+        settings.stale = false;
+        write_device_settings_file(&app, &all_settings)?;
+    }
+
+    Ok(valid_path)
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PairResult {
+    pub success: bool,
+    pub message: String,
+}
+
+// Interpret `adb pair` output: "Successfully paired to 192.168.1.5:37123 [guid=...]" or "Failed: ..."
+fn parse_pair_output(output: &str) -> PairResult {
+    let message = output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("Successfully paired") || line.starts_with("Failed") || line.contains("error"))
+        .or_else(|| output.lines().map(str::trim).find(|line| !line.is_empty()))
+        .unwrap_or("No output from adb pair")
+        .to_string();
+
+    PairResult {
+        success: message.starts_with("Successfully paired"),
+        message,
+    }
+}
+
+// Pair with a device over Wireless debugging (Android 11+) using the ip:port and code from its pairing dialog
+// Pairing is a one-time step; the device can then be reached with adb connect
+#[tauri::command]
+async fn pair_device(app: tauri::AppHandle, address: String, code: String) -> Result<PairResult, String> {
+    if !matches!(parse_transport(&address), (ref kind, Some(_), Some(_)) if kind == "tcp") {
+        return Err(format!("Invalid pairing address (expected ip:port): {}", address));
+    }
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Err("Pairing code must be 6 digits".to_string());
+    }
+
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["pair", &address, &code])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute adb pair: {}", e))?;
+
+    let combined = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(parse_pair_output(&combined))
+}
+
+// Disconnect a single adb-over-network device
+// Its saved settings are kept but marked stale; emits `devices-changed` with the remaining devices
+#[tauri::command]
+async fn disconnect_wireless_device(app: tauri::AppHandle, device_id: String) -> Result<Vec<AdbDevice>, String> {
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["disconnect", &device_id])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to disconnect device: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("error") {
+        return Err(format!(
+            "Failed to disconnect {}: {}{}",
+            device_id,
+            stdout.trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut all_settings = read_device_settings_file(&app)?;
+    if let Some(settings) = all_settings.get_mut(&device_id) {
+        settings.stale = true;
+        write_device_settings_file(&app, &all_settings)?;
+    }
+
+    let remaining = get_devices(app.clone()).await?;
+    let _ = app.emit("devices-changed", &remaining);
+    Ok(remaining)
+}
+
+// ========================
+// Transfer Queue
+// ========================
+
+// Maximum number of queued transfers that run at the same time
+const MAX_CONCURRENT_TRANSFERS: usize = 2;
+
+// Number of completed jobs kept in the persisted history
+const MAX_TRANSFER_HISTORY: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferJob {
+    pub id: String,
+    pub kind: String,        // "pull" or "push"
+    pub device_id: String,
+    pub source: String,
+    pub destination: String,
+    pub status: String,      // "queued", "running", "completed", or "failed"
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    pub queued_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct TransferQueueState {
+    pending: std::collections::VecDeque<TransferJob>,
+    running: Vec<TransferJob>,
+    // While paused no new jobs are started; running ones finish normally
+    paused: bool,
+}
+
+static TRANSFER_QUEUE: LazyLock<Mutex<TransferQueueState>> =
+    LazyLock::new(|| Mutex::new(TransferQueueState::default()));
+
+// Serializes read-modify-write of transfer_history.json, since up to MAX_CONCURRENT_TRANSFERS jobs can finish together
+static TRANSFER_HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn transfer_history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(data_dir.join("transfer_history.json"))
+}
+
+fn read_transfer_history_file(app: &tauri::AppHandle) -> Result<Vec<TransferJob>, String> {
+    let path = transfer_history_path(app)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read transfer history: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse transfer history: {}", e))
+}
+
+fn append_transfer_history(app: &tauri::AppHandle, job: &TransferJob) -> Result<(), String> {
+    let _guard = TRANSFER_HISTORY_LOCK
+        .lock()
+        .map_err(|_| "Transfer history lock is poisoned".to_string())?;
+    let mut history = read_transfer_history_file(app)?;
+    history.push(job.clone());
+    if history.len() > MAX_TRANSFER_HISTORY {
+        let excess = history.len() - MAX_TRANSFER_HISTORY;
+        history.drain(..excess);
+    }
+    let path = transfer_history_path(app)?;
+    let content = serde_json::to_string_pretty(&history)
+        .map_err(|e| format!("Failed to serialize transfer history: {}", e))?;
+
+    // Write to a temp file and rename, so a crash mid-write never leaves truncated JSON behind
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("Failed to write transfer history: {}", e))?;
+    fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to write transfer history: {}", e))
+}
+
+// Start queued jobs until the concurrency limit is reached
+fn process_transfer_queue(app: &tauri::AppHandle) {
+    let mut to_start = Vec::new();
+    if let Ok(mut queue) = TRANSFER_QUEUE.lock() {
+        while !queue.paused && queue.running.len() < MAX_CONCURRENT_TRANSFERS {
+            let Some(mut job) = queue.pending.pop_front() else {
+                break;
+            };
+            job.status = "running".to_string();
+            queue.running.push(job.clone());
+            to_start.push(job);
+        }
+    }
+
+    for job in to_start {
+        let _ = app.emit("transfer-job-status", job.clone());
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run_transfer_job(app, job).await;
+        });
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferProgress {
+    pub transfer_id: String,
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub percentage: f64,
+    pub bytes_per_second: f64,
+    pub eta_seconds: Option<u64>,
+}
+
+// Build a progress snapshot with the average speed since the transfer started
+fn compute_transfer_progress(
+    transfer_id: &str,
+    bytes_transferred: u64,
+    total_bytes: u64,
+    elapsed_secs: f64,
+) -> TransferProgress {
+    let bytes_per_second = if elapsed_secs > 0.0 {
+        bytes_transferred as f64 / elapsed_secs
+    } else {
+        0.0
+    };
+    let eta_seconds = if bytes_per_second > 0.0 && total_bytes >= bytes_transferred {
+        Some(((total_bytes - bytes_transferred) as f64 / bytes_per_second).ceil() as u64)
+    } else {
+        None
+    };
+    let percentage = if total_bytes > 0 {
+        (bytes_transferred as f64 / total_bytes as f64 * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+
+    TransferProgress {
+        transfer_id: transfer_id.to_string(),
+        bytes_transferred,
+        total_bytes,
+        percentage,
+        bytes_per_second,
+        eta_seconds,
+    }
+}
+
+// Get the size of a file on the device in bytes
+async fn get_remote_file_size(app: &tauri::AppHandle, device_id: &str, device_path: &str) -> Option<u64> {
+    let escaped_path = device_path.replace("'", "'\\''");
+    let stat_command = format!("stat -c %s '{}'", escaped_path);
+    let output = app
+        .shell()
+        .command(get_adb_command())
+        .args(["-s", device_id, "shell", &stat_command])
+        .output()
+        .await
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().ok()
+}
+
+// Pull a queued job's file while emitting `transfer-progress` events
+// adb writes straight to the destination, so progress is measured from the growing local file
+async fn pull_with_progress(app: &tauri::AppHandle, job: &TransferJob) -> Result<u64, String> {
+    let started = std::time::Instant::now();
+    download_file_inner(app, &job.device_id, &job.source, &job.destination, false, Some(&job.id)).await?;
+
+    let bytes = fs::metadata(&job.destination).map(|m| m.len()).unwrap_or(0);
+    let _ = app.emit(
+        "transfer-progress",
+        compute_transfer_progress(&job.id, bytes, bytes, started.elapsed().as_secs_f64()),
+    );
+    Ok(bytes)
+}
+
+async fn run_transfer_job(app: tauri::AppHandle, mut job: TransferJob) {
+    let started = std::time::Instant::now();
+
+    let result = match job.kind.as_str() {
+        "pull" => pull_with_progress(&app, &job).await,
+        "push" => {
+            let size = fs::metadata(&job.source).map(|m| m.len()).unwrap_or(0);
+            upload_file(
+                app.clone(),
+                job.device_id.clone(),
+                job.source.clone(),
+                job.destination.clone(),
+                true,
+            )
+            .await
+            .map(|_| size)
+            .map_err(String::from)
+        }
+        other => Err(format!("Unknown transfer kind: {}", other)),
+    };
+
+    job.duration_ms = started.elapsed().as_millis() as u64;
+    job.finished_at = Some(now_millis());
+    match result {
+        Ok(bytes) => {
+            job.status = "completed".to_string();
+            job.bytes = bytes;
+        }
+        Err(e) => {
+            job.status = "failed".to_string();
+            job.error = Some(e);
+        }
+    }
+
+    if let Ok(mut queue) = TRANSFER_QUEUE.lock() {
+        queue.running.retain(|running| running.id != job.id);
+    }
+    let _ = append_transfer_history(&app, &job);
+    let _ = app.emit("transfer-job-status", job);
+
+    process_transfer_queue(&app);
+}
+
+// Add a pull or push job to the transfer queue
+#[tauri::command]
+async fn enqueue_transfer(
+    app: tauri::AppHandle,
+    kind: String,
+    device_id: String,
+    source: String,
+    destination: String,
+) -> Result<TransferJob, String> {
+    if kind != "pull" && kind != "push" {
+        return Err(format!("Invalid transfer kind: {}", kind));
+    }
+
+    static JOB_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let queued_at = now_millis();
+    let seq = JOB_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let job = TransferJob {
+        id: format!("{}-{}", queued_at, seq),
+        kind,
+        device_id,
+        source,
+        destination,
+        status: "queued".to_string(),
+        bytes: 0,
+        duration_ms: 0,
+        error: None,
+        queued_at,
+        finished_at: None,
+    };
+
+    TRANSFER_QUEUE
+        .lock()
+        .map_err(|_| "Failed to access transfer queue".to_string())?
+        .pending
+        .push_back(job.clone());
+    let _ = app.emit("transfer-job-status", job.clone());
+
+    process_transfer_queue(&app);
+    Ok(job)
+}
+
+// List jobs that are running or waiting in the transfer queue
+#[tauri::command]
+fn get_transfer_queue() -> Result<Vec<TransferJob>, String> {
+    let queue = TRANSFER_QUEUE
+        .lock()
+        .map_err(|_| "Failed to access transfer queue".to_string())?;
+    Ok(queue.running.iter().chain(queue.pending.iter()).cloned().collect())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferQueueStatus {
+    pub paused: bool,
+    pub running: usize,
+    pub pending: usize,
+}
+
+// Pause or resume the transfer queue and emit `queue-state-changed`
+fn set_transfer_queue_paused(app: &tauri::AppHandle, paused: bool) -> Result<TransferQueueStatus, String> {
+    let status = {
+        let mut queue = TRANSFER_QUEUE
+            .lock()
+            .map_err(|_| "Failed to access transfer queue".to_string())?;
+        queue.paused = paused;
+        TransferQueueStatus {
+            paused,
+            running: queue.running.len(),
+            pending: queue.pending.len(),
+        }
+    };
+    let _ = app.emit("queue-state-changed", status.clone());
+    Ok(status)
+}
+
+// Stop starting queued transfers; in-flight transfers are allowed to finish
+#[tauri::command]
+fn pause_transfer_queue(app: tauri::AppHandle) -> Result<TransferQueueStatus, String> {
+    set_transfer_queue_paused(&app, true)
+}
+
+// Resume starting queued transfers
+#[tauri::command]
+fn resume_transfer_queue(app: tauri::AppHandle) -> Result<TransferQueueStatus, String> {
+    let status = set_transfer_queue_paused(&app, false)?;
+    process_transfer_queue(&app);
+    Ok(status)
+}
+
+// List finished transfers, oldest first
+#[tauri::command]
+async fn get_transfer_history(app: tauri::AppHandle) -> Result<Vec<TransferJob>, String> {
+    read_transfer_history_file(&app)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct OperationStats {
+    pub direction: String, // "download" or "upload"
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionStats {
+    pub downloaded_bytes: u64,
+    pub downloaded_files: u64,
+    pub uploaded_bytes: u64,
+    pub uploaded_files: u64,
+    pub operations: HashMap<String, OperationStats>,
+}
+
+// Totals for successful transfers since the app was launched
+static SESSION_STATS: LazyLock<Mutex<SessionStats>> = LazyLock::new(|| Mutex::new(SessionStats::default()));
+
+fn add_to_session_stats(stats: &mut SessionStats, operation: &str, direction: &str, bytes: u64, files: u64) {
+    if direction == "upload" {
+        stats.uploaded_bytes += bytes;
+        stats.uploaded_files += files;
+    } else {
+        stats.downloaded_bytes += bytes;
+        stats.downloaded_files += files;
+    }
+
+    let entry = stats.operations.entry(operation.to_string()).or_insert_with(|| OperationStats {
+        direction: direction.to_string(),
+        ..Default::default()
+    });
+    entry.bytes += bytes;
+    entry.files += files;
+}
+
+// Count a successful pull ("download") or push ("upload") towards the session totals
+fn record_transfer(operation: &str, direction: &str, bytes: u64, files: u64) {
+    if let Ok(mut stats) = SESSION_STATS.lock() {
+        add_to_session_stats(&mut stats, operation, direction, bytes, files);
+    }
+}
+
+// Bytes and files moved this session, in total and per operation
+#[tauri::command]
+fn get_session_stats() -> SessionStats {
+    SESSION_STATS.lock().map(|stats| stats.clone()).unwrap_or_default()
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .invoke_handler(tauri::generate_handler![
+            get_devices,
+            start_health_check,
+            stop_health_check,
+            await_device_ready,
+            diagnose_authorization,
+            restart_device_watcher,
+            list_files,
+            list_files_streamed,
+            watch_directory,
+            stop_watching,
+            list_files_resolved,
+            open_directory,
+            resolve_real_path,
+            resolve_content_uri,
+            detect_storage_path,
+            get_standard_folders,
+            list_root_entries,
+            list_storage_volumes,
+            is_path_writable,
+            check_adb,
+            set_adb_path,
+            get_current_adb_path,
+            get_diagnostics,
+            cancel_operation,
+            list_active_jobs,
+            kill_job,
+            cancel_transfer,
+            adb_root,
+            adb_unroot,
+            list_forwards,
+            add_forward,
+            remove_forward,
+            list_reverses,
+            add_reverse,
+            remove_reverse,
+            list_processes,
+            kill_process,
+            get_logcat_snapshot,
+            get_device_locale,
+            get_device_identity,
+            get_device_info,
+            get_capabilities,
+            get_device_clipboard,
+            set_device_clipboard,
+            list_input_methods,
+            get_default_input_method,
+            set_input_method,
+            get_thumbnail,
+            regenerate_thumbnail,
+            migrate_thumbnail_cache,
+            get_thumbnail_debug,
+            get_thumbnail_or_placeholder,
+            request_thumbnail,
+            get_app_icon,
+            list_packages_by_install_time,
+            get_app_meminfo,
+            get_cached_thumbnail,
+            check_temp_dir,
+            set_thumbnail_cache_dir,
+            prefetch_thumbnails,
+            delete_file,
+            delete_directory_with_progress,
+            rename_file,
+            create_directory,
+            copy_file,
+            move_file,
+            batch_rename,
+            search_files,
+            advanced_search,
+            find_recent_files,
+            find_broken_symlinks,
+            quick_hash,
+            find_duplicates,
+            count_files,
+            get_directory_tree,
+            export_listing_csv,
+            generate_checksum_manifest,
+            get_storage_info,
+            get_all_storage_usage,
+            analyze_storage_by_type,
+            check_local_free_space,
+            get_mount_info,
+            move_across_volumes,
+            get_file_stat,
+            download_file,
+            pull_file,
+            pull_via_exec_out,
+            pull_and_verify,
+            archive_and_pull,
+            download_preserving_structure,
+            pull_files,
+            backup_device,
+            pull_photos_since,
+            upload_file,
+            push_file,
+            push_directory,
+            set_wallpaper,
+            preview_file,
+            get_preview_path,
+            get_image_preview,
+            list_local_files,
+            list_device_files_for_sync,
+            preview_sync,
+            execute_sync,
+            list_saved_syncs,
+            save_sync_config,
+            delete_saved_sync,
+            get_device_settings,
+            update_device_settings,
+            refresh_device,
+            pair_device,
+            disconnect_wireless_device,
+            set_device_alias,
+            enqueue_transfer,
+            get_transfer_queue,
+            pause_transfer_queue,
+            resume_transfer_queue,
+            get_transfer_history,
+            get_session_stats,
+            compare_directories
+        ])
+        .setup(|app| {
+            // Restore the thumbnail cache location chosen in a previous session
+            if let Ok(settings) = read_app_settings(app.handle()) {
+                if let Ok(mut guard) = THUMBNAIL_CACHE_DIR.lock() {
+                    *guard = settings.thumbnail_cache_dir.map(PathBuf::from);
                 }
             }
-        } else {
-            // Hash only on device side
-            for df in &device_for_hash {
-                handled_device.insert(df.relative_path.clone());
-                match direction {
-                    SyncDirection::PhoneToComputer | SyncDirection::BothWays => {
-                        actions.push(SyncAction {
-                            file_path: df.relative_path.clone(),
-                            action_type: "copy".to_string(),
-                            direction: "\u{2192} Computer".to_string(),
-                            size: df.size,
-                            reason: "File only exists on device".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    SyncDirection::ComputerToPhone => {
-                        if delete_missing {
-                            actions.push(SyncAction {
-                                file_path: df.relative_path.clone(),
-                                action_type: "delete".to_string(),
-                                direction: "\u{2716} Phone".to_string(),
-                                size: df.size,
-                                reason: "File not on computer, will be deleted from device".to_string(),
-                                rename_from: None,
-                            });
-                        }
-                    }
-                }
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            // Don't leave orphaned adb children (track-devices, long pulls, ...) behind when the app quits
+            if let tauri::RunEvent::Exit = event {
+                kill_all_jobs();
+                stop_watching();
             }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_any_pattern_with_spaces() {
+        // Pattern with space in directory name
+        let patterns = vec![
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*".to_string(),
+        ];
+
+        // Should match a file directly in that directory
+        assert!(matches_any_pattern(
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/IMG_001.jpg",
+            &patterns,
+        ));
+
+        // Should NOT match a file in a different directory
+        assert!(!matches_any_pattern(
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Video/Sent/VID_001.mp4",
+            &patterns,
+        ));
+    }
+
+    #[test]
+    fn test_normalize_patterns_with_spaces() {
+        // Bare directory with spaces → should get /**/* appended
+        let patterns = vec![
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent".to_string(),
+        ];
+        let normalized = normalize_patterns(&patterns, "/sdcard");
+        assert_eq!(
+            normalized,
+            vec!["Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/**/*"],
+        );
+
+        // Pattern with glob stays as-is
+        let patterns2 = vec![
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*".to_string(),
+        ];
+        let normalized2 = normalize_patterns(&patterns2, "/sdcard");
+        assert_eq!(
+            normalized2,
+            vec!["Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*"],
+        );
+    }
+
+    #[test]
+    fn test_matches_any_pattern_with_double_star_and_spaces() {
+        // Normalized pattern from bare directory input
+        let patterns = vec![
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/**/*".to_string(),
+        ];
+
+        // Should match a file in the directory
+        assert!(matches_any_pattern(
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/IMG_001.jpg",
+            &patterns,
+        ));
+
+        // Should match a file in a subdirectory
+        assert!(matches_any_pattern(
+            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/2024/IMG_001.jpg",
+            &patterns,
+        ));
+    }
+
+    #[test]
+    fn test_matches_empty_patterns_matches_all() {
+        assert!(matches_any_pattern("any/path/file.txt", &vec![]));
+    }
+
+    // Helper to create a FileMetadata for testing
+    fn make_file(path: &str, size: u64, mtime: u64, md5: Option<&str>) -> FileMetadata {
+        FileMetadata {
+            relative_path: path.to_string(),
+            size,
+            modified_time: mtime,
+            is_directory: false,
+            md5_hash: md5.map(|s| s.to_string()),
         }
     }
 
-    // Handle files with same path but different hash (updates) that weren't matched above
-    for (path, lf) in &local_by_path {
-        if handled_local.contains(*path) { continue; }
-        if let Some(df) = device_by_path.get(path) {
-            if lf.md5_hash != df.md5_hash {
-                handled_local.insert(path.to_string());
-                handled_device.insert(path.to_string());
-                match direction {
-                    SyncDirection::PhoneToComputer => {
-                        actions.push(SyncAction {
-                            file_path: path.to_string(),
-                            action_type: "update".to_string(),
-                            direction: "\u{2192} Computer".to_string(),
-                            size: df.size,
-                            reason: "Device file has different content".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    SyncDirection::ComputerToPhone => {
-                        actions.push(SyncAction {
-                            file_path: path.to_string(),
-                            action_type: "update".to_string(),
-                            direction: "\u{2192} Phone".to_string(),
-                            size: lf.size,
-                            reason: "Local file has different content".to_string(),
-                            rename_from: None,
-                        });
-                    }
-                    SyncDirection::BothWays => {
-                        if lf.modified_time >= df.modified_time {
-                            actions.push(SyncAction {
-                                file_path: path.to_string(),
-                                action_type: "update".to_string(),
-                                direction: "\u{2192} Phone".to_string(),
-                                size: lf.size,
-                                reason: "Local file is newer".to_string(),
-                                rename_from: None,
-                            });
-                        } else {
-                            actions.push(SyncAction {
-                                file_path: path.to_string(),
-                                action_type: "update".to_string(),
-                                direction: "\u{2192} Computer".to_string(),
-                                size: df.size,
-                                reason: "Device file is newer".to_string(),
-                                rename_from: None,
-                            });
-                        }
-                    }
-                }
-            }
-        }
+    // ========================
+    // compute_sync_actions tests (filename-based)
+    // ========================
+
+    #[test]
+    fn test_sync_phone_to_computer_copy_new_file() {
+        let local = vec![];
+        let device = vec![make_file("photo.jpg", 1000, 100, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "copy");
+        assert_eq!(actions[0].file_path, "photo.jpg");
+        assert!(actions[0].direction.contains("Computer"));
+    }
+
+    #[test]
+    fn test_sync_computer_to_phone_copy_new_file() {
+        let local = vec![make_file("doc.pdf", 2000, 200, None)];
+        let device = vec![];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::ComputerToPhone, false, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "copy");
+        assert_eq!(actions[0].file_path, "doc.pdf");
+        assert!(actions[0].direction.contains("Phone"));
+    }
+
+    #[test]
+    fn test_sync_update_newer_file() {
+        let local = vec![make_file("file.txt", 100, 50, None)];
+        let device = vec![make_file("file.txt", 200, 100, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "update");
+    }
+
+    #[test]
+    fn test_sync_no_action_when_identical() {
+        let local = vec![make_file("file.txt", 100, 100, None)];
+        let device = vec![make_file("file.txt", 100, 100, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
+        assert_eq!(actions.len(), 0);
+    }
+
+    #[test]
+    fn test_sync_delete_missing_phone_to_computer() {
+        let local = vec![make_file("old.txt", 100, 50, None)];
+        let device = vec![];
+        // delete_missing=true: local file not on device → delete locally
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, true, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "delete");
+        assert!(actions[0].direction.contains("Computer"));
+    }
+
+    #[test]
+    fn test_sync_no_delete_when_flag_off() {
+        let local = vec![make_file("old.txt", 100, 50, None)];
+        let device = vec![];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
+        assert_eq!(actions.len(), 0);
+    }
+
+    #[test]
+    fn test_sync_delete_missing_computer_to_phone() {
+        let local = vec![];
+        let device = vec![make_file("orphan.txt", 100, 50, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::ComputerToPhone, true, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "delete");
+        assert!(actions[0].direction.contains("Phone"));
+    }
+
+    #[test]
+    fn test_sync_both_ways_copies_unique_files() {
+        let local = vec![make_file("local_only.txt", 100, 50, None)];
+        let device = vec![make_file("device_only.txt", 200, 60, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::BothWays, false, "filename");
+        assert_eq!(actions.len(), 2);
+        let types: Vec<&str> = actions.iter().map(|a| a.action_type.as_str()).collect();
+        assert!(types.contains(&"copy"));
+    }
+
+    #[test]
+    fn test_sync_both_ways_conflict_newer_wins() {
+        let local = vec![make_file("file.txt", 100, 200, None)];
+        let device = vec![make_file("file.txt", 200, 100, None)];
+        let actions = compute_sync_actions(&local, &device, &SyncDirection::BothWays, false, "filename");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "update");
+        // Local is newer (mtime 200 > 100), should push to phone
+        assert!(actions[0].direction.contains("Phone"));
+    }
+
+    // ========================
+    // compute_sync_actions_by_content tests (MD5-based)
+    // ========================
+
+    #[test]
+    fn test_content_sync_identical_files_no_action() {
+        let local = vec![make_file("file.txt", 100, 50, Some("abc123"))];
+        let device = vec![make_file("file.txt", 100, 50, Some("abc123"))];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
+        assert_eq!(actions.len(), 0);
+    }
+
+    #[test]
+    fn test_content_sync_rename_detection() {
+        // Same hash, different path → rename
+        let local = vec![make_file("old_name.txt", 100, 50, Some("abc123"))];
+        let device = vec![make_file("new_name.txt", 100, 60, Some("abc123"))];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "rename");
+        assert!(actions[0].rename_from.is_some());
+    }
+
+    #[test]
+    fn test_content_sync_copy_unique_file() {
+        let local = vec![];
+        let device = vec![make_file("new.txt", 100, 50, Some("abc123"))];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "copy");
+        assert!(actions[0].direction.contains("Computer"));
+    }
+
+    #[test]
+    fn test_content_sync_delete_missing() {
+        let local = vec![make_file("orphan.txt", 100, 50, Some("abc123"))];
+        let device = vec![];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, true);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "delete");
+    }
+
+    #[test]
+    fn test_content_sync_no_delete_when_flag_off() {
+        let local = vec![make_file("orphan.txt", 100, 50, Some("abc123"))];
+        let device = vec![];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
+        assert_eq!(actions.len(), 0);
+    }
+
+    #[test]
+    fn test_content_sync_both_ways_rename_uses_newer() {
+        let local = vec![make_file("a.txt", 100, 200, Some("abc123"))];
+        let device = vec![make_file("b.txt", 100, 100, Some("abc123"))];
+        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::BothWays, false);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "rename");
+        // Local is newer, so device should be renamed to match local
+        assert!(actions[0].direction.contains("Phone"));
+        assert_eq!(actions[0].file_path, "a.txt");
     }
 
-    actions.sort_by(|a, b| a.file_path.cmp(&b.file_path));
-    actions
-}
+    #[test]
+    fn test_parse_adb_root_output() {
+        assert_eq!(parse_adb_root_output("restarting adbd as root\n", true).unwrap(), "restarted");
+        assert_eq!(parse_adb_root_output("adbd is already running as root\n", true).unwrap(), "unchanged");
+        assert!(parse_adb_root_output("adbd cannot run as root in production builds\n", true).is_err());
+        assert_eq!(parse_adb_root_output("restarting adbd as non root\n", false).unwrap(), "restarted");
+        assert_eq!(parse_adb_root_output("adbd not running as root\n", false).unwrap(), "unchanged");
+    }
 
-// Preview sync: compute what would happen without executing
-#[tauri::command]
-async fn preview_sync(
-    app: tauri::AppHandle,
-    device_id: String,
-    options: SyncOptions,
-) -> Result<SyncPreview, String> {
-    let patterns = normalize_patterns(&options.file_patterns, &options.device_path);
-    // Force recursive when patterns contain path separators
-    let recursive = options.recursive || patterns.iter().any(|p| p.contains('/'));
+    #[test]
+    fn test_parse_ps_output_toybox() {
+        let output = "USER           PID  PPID     VSZ    RSS WCHAN            ADDR S NAME\n\
+                      root             1     0 10782796 11744 0                   0 S init\n\
+                      u0_a123      12345   678 14000000 90000 0                   0 S com.example.app\n";
+        let processes = parse_ps_output(output);
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[1].pid, 12345);
+        assert_eq!(processes[1].user, "u0_a123");
+        assert_eq!(processes[1].name, "com.example.app");
+    }
 
-    #[cfg(debug_assertions)]
-    {
-        eprintln!("[sync] raw file_patterns: {:?}", options.file_patterns);
-        eprintln!("[sync] normalized patterns: {:?}", patterns);
-        eprintln!("[sync] recursive: {}", recursive);
+    #[test]
+    fn test_parse_ps_output_toolbox() {
+        // Older ps has an unlabelled state column before NAME
+        let output = "USER     PID   PPID  VSIZE  RSS     WCHAN    PC        NAME\n\
+                      root      1     0     8904   788   ffffffff 00000000 S /init\n";
+        let processes = parse_ps_output(output);
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid, 1);
+        assert_eq!(processes[0].user, "root");
+        assert_eq!(processes[0].name, "/init");
     }
 
-    let local_files = list_local_files(options.local_path.clone(), recursive, options.match_mode.clone(), patterns.clone())?;
-    let device_files = list_device_files_for_sync(
-        app,
-        device_id,
-        options.device_path.clone(),
-        recursive,
-        options.match_mode.clone(),
-        patterns,
-    ).await?;
+    #[test]
+    fn test_parse_broadcast_data() {
+        let output = "Broadcasting: Intent { act=clipper.get flg=0x400000 }\n\
+                      Broadcast completed: result=-1, data=\"hello \"world\"\"\n";
+        assert_eq!(parse_broadcast_data(output), Some("hello \"world\"".to_string()));
+        assert_eq!(parse_broadcast_data("Broadcast completed: result=0\n"), None);
+    }
 
-    #[cfg(debug_assertions)]
-    {
-        eprintln!("[sync] local_files count: {}", local_files.len());
-        eprintln!("[sync] device_files count: {}", device_files.len());
-        if !device_files.is_empty() {
-            eprintln!("[sync] first device file: {:?}", device_files[0].relative_path);
-        }
-        if !local_files.is_empty() {
-            eprintln!("[sync] first local file: {:?}", local_files[0].relative_path);
-        }
+    #[test]
+    fn test_relative_device_path() {
+        assert_eq!(
+            relative_device_path("/sdcard/DCIM/Camera/IMG_001.jpg", "/sdcard/DCIM/"),
+            Some("Camera/IMG_001.jpg".to_string())
+        );
+        assert_eq!(relative_device_path("/sdcard/DCIMX/a.jpg", "/sdcard/DCIM"), None);
+        assert_eq!(relative_device_path("/sdcard/DCIM/../secret", "/sdcard/DCIM"), None);
+        assert_eq!(relative_device_path("/sdcard/DCIM", "/sdcard/DCIM"), None);
     }
 
-    let actions = compute_sync_actions(&local_files, &device_files, &options.direction, options.delete_missing, &options.match_mode);
+    #[test]
+    fn test_compare_file_lists() {
+        let local = vec![
+            make_file("both_same.txt", 100, 50, None),
+            make_file("both_diff.txt", 100, 50, None),
+            make_file("local_only.txt", 10, 5, None),
+        ];
+        let device = vec![
+            make_file("both_same.txt", 100, 50, None),
+            make_file("both_diff.txt", 120, 50, None),
+            make_file("device_only.txt", 20, 6, None),
+        ];
+        let report = compare_file_lists(&local, &device);
+        assert_eq!(report.identical_count, 1);
+        assert_eq!(report.local_only.len(), 1);
+        assert_eq!(report.local_only[0].relative_path, "local_only.txt");
+        assert_eq!(report.device_only.len(), 1);
+        assert_eq!(report.device_only[0].relative_path, "device_only.txt");
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].relative_path, "both_diff.txt");
+    }
 
-    let mut total_transfer_bytes: u64 = 0;
-    let mut copy_count: u32 = 0;
-    let mut update_count: u32 = 0;
-    let mut delete_count: u32 = 0;
-    let mut skip_count: u32 = 0;
-    let mut rename_count: u32 = 0;
+    #[test]
+    fn test_parse_ls_line_modified_epoch() {
+        let entry = parse_ls_line("-rw-rw---- root sdcard_rw 1024 2025-02-01 06:31 photo.jpg").unwrap();
+        assert_eq!(entry.modified_epoch, Some(1738391460));
+        assert_eq!(parse_ls_datetime("1970-01-01", "00:00"), Some(0));
+        assert_eq!(parse_ls_datetime("Feb", "06:31"), None);
+    }
 
-    for action in &actions {
-        match action.action_type.as_str() {
-            "copy" => {
-                copy_count += 1;
-                total_transfer_bytes += action.size;
-            }
-            "update" => {
-                update_count += 1;
-                total_transfer_bytes += action.size;
-            }
-            "delete" => {
-                delete_count += 1;
-            }
-            "rename" => {
-                rename_count += 1;
-            }
-            _ => {
-                skip_count += 1;
-            }
-        }
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem            1K-blocks     Used Available Use% Mounted on\n\
+                      /dev/root               5992272  5940256     35632 100% /\n\
+                      tmpfs                   3789412     1216   3788196   1% /dev\n\
+                      /dev/block/platform/soc/1d84000.ufshc/by-name/userdata\n\
+                      \x20                     114248172 47213928  66903908  42% /data\n\
+                      /dev/fuse             114248172 47213928  66903908  42% /storage/emulated\n";
+        let volumes = parse_df_output(output);
+        let mounts: Vec<&str> = volumes.iter().map(|v| v.mount_point.as_str()).collect();
+        assert_eq!(mounts, vec!["/", "/data", "/storage/emulated"]);
+        assert_eq!(volumes[2].total_bytes, 114248172 * 1024);
+        assert_eq!(volumes[2].available_bytes, 66903908 * 1024);
     }
 
-    Ok(SyncPreview {
-        actions,
-        total_transfer_bytes,
-        copy_count,
-        update_count,
-        delete_count,
-        skip_count,
-        rename_count,
-    })
-}
+    #[test]
+    fn test_compute_transfer_progress() {
+        let progress = compute_transfer_progress("t1", 50_000_000, 200_000_000, 5.0);
+        assert_eq!(progress.bytes_per_second, 10_000_000.0);
+        assert_eq!(progress.eta_seconds, Some(15));
+        assert_eq!(progress.percentage, 25.0);
+
+        let starting = compute_transfer_progress("t1", 0, 200_000_000, 0.0);
+        assert_eq!(starting.eta_seconds, None);
+    }
 
-// Execute sync: perform the actual file transfers
-#[tauri::command]
-async fn execute_sync(
-    app: tauri::AppHandle,
-    window: tauri::Window,
-    device_id: String,
-    options: SyncOptions,
-) -> Result<SyncResult, String> {
-    if is_critical_system_path(&options.device_path) {
-        return Err(format!("Cannot sync to critical system path: {}", options.device_path));
+    #[test]
+    fn test_local_available_space_for_missing_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not").join("created").join("yet");
+        assert!(local_available_space(&missing).unwrap() > 0);
     }
 
-    let patterns = normalize_patterns(&options.file_patterns, &options.device_path);
-    // Force recursive when patterns contain path separators
-    let recursive = options.recursive || patterns.iter().any(|p| p.contains('/'));
+    #[test]
+    fn test_resolve_device_alias() {
+        assert_eq!(resolve_device_alias(Some("Work phone"), "Google Pixel 8", "ABC123"), "Work phone");
+        assert_eq!(resolve_device_alias(Some("  "), "Google Pixel 8", "ABC123"), "Google Pixel 8");
+        assert_eq!(resolve_device_alias(None, "", "ABC123"), "ABC123");
+    }
 
-    let local_files = list_local_files(options.local_path.clone(), recursive, options.match_mode.clone(), patterns.clone())?;
-    let device_files = list_device_files_for_sync(
-        app.clone(),
-        device_id.clone(),
-        options.device_path.clone(),
-        recursive,
-        options.match_mode.clone(),
-        patterns,
-    ).await?;
+    #[test]
+    fn test_parse_ls_line_mime_type() {
+        let image = parse_ls_line("-rw-rw---- root sdcard_rw 1024 2025-02-01 06:31 IMG_001.JPG").unwrap();
+        assert_eq!(image.mime_type.as_deref(), Some("image/jpeg"));
+        let dir = parse_ls_line("drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 DCIM").unwrap();
+        assert_eq!(dir.mime_type, None);
+    }
 
-    let actions = compute_sync_actions(&local_files, &device_files, &options.direction, options.delete_missing, &options.match_mode);
+    #[test]
+    fn test_parse_get_state() {
+        assert_eq!(parse_get_state("device\n", ""), "device");
+        assert_eq!(parse_get_state("", "error: device unauthorized.\n"), "unauthorized");
+        assert_eq!(parse_get_state("", "error: device 'ABC123' not found\n"), "disconnected");
+    }
 
-    // Build timestamp lookup maps for preserving file modification times
-    let device_mtime_map: HashMap<String, u64> = device_files.iter()
-        .map(|f| (f.relative_path.clone(), f.modified_time))
-        .collect();
-    let local_mtime_map: HashMap<String, u64> = local_files.iter()
-        .map(|f| (f.relative_path.clone(), f.modified_time))
+    #[test]
+    fn test_parse_mounts_both_formats() {
+        let proc_mounts = "/dev/block/dm-0 /system ext4 ro,seclabel,relatime 0 0\n\
+                           /dev/fuse /storage/emulated fuse rw,nosuid,nodev 0 0\n\
+                           /dev/block/vold/public:179,1 /mnt/media_rw/My\\040Card vfat rw,dirsync 0 0\n";
+        let mounts = parse_mounts(proc_mounts);
+        assert_eq!(mounts.len(), 3);
+        assert!(mounts[0].read_only);
+        assert!(!mounts[1].read_only);
+        assert_eq!(mounts[2].mount_point, "/mnt/media_rw/My Card");
+
+        let mount_cmd = "/dev/block/dm-0 on /system type ext4 (ro,seclabel,relatime)\n";
+        let mounts = parse_mounts(mount_cmd);
+        assert_eq!(mounts[0].mount_point, "/system");
+        assert_eq!(mounts[0].fs_type, "ext4");
+        assert!(mounts[0].read_only);
+    }
+
+    #[test]
+    fn test_compare_by_type_directories_first() {
+        let mut files: Vec<FileEntry> = [
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 notes.txt",
+            "drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 zeta",
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 b.jpg",
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 README",
+            "drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 Alpha",
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 a.JPG",
+        ]
+        .iter()
+        .filter_map(|line| parse_ls_line(line))
         .collect();
 
-    let total_count = actions.len() as u32;
-    let total_bytes: u64 = actions
+        files.sort_by(compare_by_type);
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Alpha", "zeta", "README", "a.JPG", "b.jpg", "notes.txt"]);
+
+        let first_file = files.iter().position(|f| !f.is_directory).unwrap();
+        assert!(files[first_file..].iter().all(|f| !f.is_directory));
+    }
+
+    #[test]
+    fn test_select_media_since() {
+        let files: Vec<FileEntry> = [
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 /sdcard/DCIM/Camera/old.jpg",
+            "-rw-rw---- root sdcard_rw 10 2025-03-01 08:00 /sdcard/DCIM/Camera/new.jpg",
+            "-rw-rw---- root sdcard_rw 10 2025-03-02 09:15 /sdcard/DCIM/Camera/clip.mp4",
+            "-rw-rw---- root sdcard_rw 10 2025-03-03 10:00 /sdcard/DCIM/Camera/.nomedia.txt",
+        ]
         .iter()
-        .filter(|a| a.action_type == "copy" || a.action_type == "update")
-        .map(|a| a.size)
-        .sum();
+        .filter_map(|line| parse_ls_line(line))
+        .collect();
 
-    let mut success_count: u32 = 0;
-    let mut skip_count: u32 = 0;
-    let mut error_count: u32 = 0;
-    let mut errors: Vec<String> = Vec::new();
-    let mut completed_bytes: u64 = 0;
+        let since = parse_ls_datetime("2025-03-01", "00:00").unwrap();
+        let (selected, newest) = select_media_since(files, since);
+        let names: Vec<&str> = selected.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["/sdcard/DCIM/Camera/new.jpg", "/sdcard/DCIM/Camera/clip.mp4"]);
+        assert_eq!(newest, parse_ls_datetime("2025-03-02", "09:15"));
 
-    let shell = app.shell();
-    let adb_cmd = get_adb_command();
+        assert_eq!(hold_back_epoch(Some(200), None), Some(200));
+        assert_eq!(hold_back_epoch(Some(200), Some(150)), Some(150));
+        assert_eq!(hold_back_epoch(None, None), None);
+    }
 
-    for (i, action) in actions.iter().enumerate() {
-        // Emit progress
-        let _ = window.emit("sync-progress", SyncProgress {
-            current_file: action.file_path.clone(),
-            completed_count: i as u32,
-            total_count,
-            completed_bytes,
-            total_bytes,
-        });
+    #[test]
+    fn test_parse_file_stat() {
+        let stat = parse_file_stat(
+            "/sdcard/a.txt",
+            "regular file|1234|8|fd0eh/64782d|5678|1|660|-rw-rw----|u0_a123|media_rw|10123|1023|1700000000|1700000100|1700000200\n",
+        )
+        .unwrap();
+        assert_eq!(stat.file_type, "regular file");
+        assert_eq!(stat.size, 1234);
+        assert_eq!(stat.inode, 5678);
+        assert_eq!(stat.permissions_octal, "0660");
+        assert_eq!(stat.permissions, "-rw-rw----");
+        assert_eq!(stat.group, "media_rw");
+        assert_eq!(stat.modified_epoch, 1700000100);
+
+        assert!(parse_file_stat("/sdcard/a.txt", "stat: /sdcard/a.txt: No such file or directory").is_none());
+    }
+
+    #[test]
+    fn test_compute_batch_rename() {
+        let paths = vec!["/sdcard/DCIM/IMG_1.jpg".to_string(), "/sdcard/DCIM/photo.JPG".to_string()];
+        let targets = compute_batch_rename(&paths, "Trip_{index}").unwrap();
+        assert_eq!(targets[0].1, "/sdcard/DCIM/Trip_1.jpg");
+        assert_eq!(targets[1].1, "/sdcard/DCIM/Trip_2.JPG");
+
+        let targets = compute_batch_rename(&paths, "{name}_edited.{ext}").unwrap();
+        assert_eq!(targets[0].1, "/sdcard/DCIM/IMG_1_edited.jpg");
+
+        // Same extension and no {index} collapses every file to one name
+        let same_ext = vec!["/sdcard/a.jpg".to_string(), "/sdcard/b.jpg".to_string()];
+        assert!(compute_batch_rename(&same_ext, "photo").is_err());
+        assert!(compute_batch_rename(&same_ext, "../{name}").is_err());
+        // Renaming a -> b would overwrite b before it is moved
+        assert!(compute_batch_rename(&same_ext, "b").is_err());
+    }
+
+    #[test]
+    fn test_is_qemu_prop_set() {
+        assert!(is_qemu_prop_set("1\n\n"));
+        assert!(is_qemu_prop_set("\n1\n"));
+        assert!(!is_qemu_prop_set("\n\n"));
+        assert!(!is_qemu_prop_set("0\n"));
+    }
+
+    #[test]
+    fn test_parse_standard_folders() {
+        let output = "DCIM|3\nDownload|12\nWhatsApp/Media|7\nbogus|1\n";
+        let folders = parse_standard_folders(output, "/storage/emulated/0/");
+        assert_eq!(folders.len(), 3);
+        assert_eq!(
+            folders[0],
+            StandardFolder {
+                label: "Camera".to_string(),
+                path: "/storage/emulated/0/DCIM".to_string(),
+                item_count: 3,
+            }
+        );
+        assert_eq!(folders[2].path, "/storage/emulated/0/WhatsApp/Media");
+        assert_eq!(folders[2].item_count, 7);
+    }
+
+    #[test]
+    fn test_placeholder_for_extension() {
+        assert_eq!(placeholder_for_extension("zip"), "placeholder:archive");
+        assert_eq!(placeholder_for_extension("pdf"), "placeholder:document");
+        assert_eq!(placeholder_for_extension("rs"), "placeholder:code");
+        assert_eq!(placeholder_for_extension("mp3"), "placeholder:audio");
+        assert_eq!(placeholder_for_extension("log"), "placeholder:text");
+        assert_eq!(placeholder_for_extension("bin"), "placeholder:file");
+    }
+
+    #[test]
+    fn test_build_prune_clause() {
+        let defaults: Vec<String> = DEFAULT_SEARCH_EXCLUSIONS.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            build_prune_clause(&defaults).unwrap(),
+            r#"\( -path '*/Android/data' -o -path '*/Android/obb' \) -prune -o"#
+        );
+        assert_eq!(
+            build_prune_clause(&["/sdcard/Movies/".to_string()]).unwrap(),
+            r#"\( -path '/sdcard/Movies' \) -prune -o"#
+        );
+        assert_eq!(
+            build_prune_clause(&["it's".to_string()]).unwrap(),
+            r#"\( -path '*/it'\''s' \) -prune -o"#
+        );
+        assert_eq!(build_prune_clause(&[]).unwrap(), build_prune_clause(&defaults).unwrap());
+        assert!(build_prune_clause(&["  ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_build_search_command_case_sensitivity() {
+        let insensitive = build_search_command("/sdcard", "IMG", false, "", false, false);
+        assert!(insensitive.contains(" -iname '*IMG*'"));
+
+        let sensitive = build_search_command("/sdcard", "IMG", false, "", true, false);
+        assert!(sensitive.contains(" -name '*IMG*'"));
+        assert!(!sensitive.contains("-iname"));
+    }
 
-        let result = match action.action_type.as_str() {
-            "copy" | "update" => {
-                if action.direction.contains("Computer") {
-                    // Pull from device to local
-                    let device_file = format!("{}/{}", options.device_path, action.file_path);
-                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
+    #[test]
+    fn test_parse_logcat_filter() {
+        assert_eq!(
+            parse_logcat_filter("ActivityManager:I  *:S").unwrap(),
+            vec!["ActivityManager:I".to_string(), "*:S".to_string()]
+        );
+        assert!(parse_logcat_filter("ActivityManager").is_err());
+        assert!(parse_logcat_filter("Tag:X").is_err());
+        assert!(parse_logcat_filter("Tag;rm:E").is_err());
+    }
 
-                    // Create parent directories
-                    if let Some(parent) = local_file.parent() {
-                        let _ = fs::create_dir_all(parent);
-                    }
+    #[test]
+    fn test_build_search_command_follow_symlinks() {
+        let plain = build_search_command("/sdcard", "a", true, "", false, false);
+        assert!(plain.starts_with("find '/sdcard'"));
 
-                    let output = shell
-                        .command(&adb_cmd)
-                        .args(["-s", &device_id, "pull", &device_file, local_file.to_str().unwrap_or("")])
-                        .output()
-                        .await;
+        let follow = build_search_command("/sdcard", "a", true, "", false, true);
+        assert!(follow.starts_with("find -L '/sdcard'"));
+    }
 
-                    match output {
-                        Ok(o) if o.status.success() => {
-                            // Restore the source file's modification time
-                            if let Some(&mtime) = device_mtime_map.get(&action.file_path) {
-                                let mtime_system = UNIX_EPOCH + Duration::from_secs(mtime);
-                                if let Ok(file) = fs::File::open(&local_file) {
-                                    let _ = file.set_modified(mtime_system);
-                                }
-                                // Set creation/birth time on macOS to match the source file
-                                let _ = set_creation_time(&std::path::PathBuf::from(&local_file), mtime);
-                            }
-                            Ok(())
-                        }
-                        Ok(o) => Err(format!("Pull failed: {}", String::from_utf8_lossy(&o.stderr))),
-                        Err(e) => Err(format!("Pull error: {}", e)),
-                    }
-                } else if action.direction.contains("Phone") {
-                    // Push from local to device
-                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
-                    let device_file = format!("{}/{}", options.device_path, action.file_path);
+    #[test]
+    fn test_build_directory_tree() {
+        let entries: Vec<FileEntry> = [
+            "drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 /sdcard/DCIM",
+            "drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 /sdcard/DCIM/Camera",
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 /sdcard/DCIM/Camera/a.jpg",
+            "-rw-rw---- root sdcard_rw 10 2025-02-01 06:31 /sdcard/notes.txt",
+        ]
+        .iter()
+        .filter_map(|line| parse_ls_line(line))
+        .collect();
 
-                    // Create parent directory on device if needed
-                    if let Some(parent) = PathBuf::from(&action.file_path).parent() {
-                        let parent_str = parent.to_string_lossy();
-                        if !parent_str.is_empty() {
-                            let device_parent = format!("{}/{}", options.device_path, parent_str);
-                            let escaped = device_parent.replace("'", "'\\''");
-                            let mkdir_cmd = format!("mkdir -p '{}'", escaped);
-                            let _ = shell
-                                .command(&adb_cmd)
-                                .args(["-s", &device_id, "shell", &mkdir_cmd])
-                                .output()
-                                .await;
-                        }
-                    }
+        let tree = build_directory_tree("/sdcard/", entries);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].entry.name, "DCIM");
+        assert_eq!(tree[0].children[0].path, "/sdcard/DCIM/Camera");
+        assert_eq!(tree[0].children[0].children[0].entry.name, "a.jpg");
+        assert_eq!(tree[1].entry.name, "notes.txt");
+        assert!(tree[1].children.is_empty());
+    }
 
-                    let output = shell
-                        .command(&adb_cmd)
-                        .args(["-s", &device_id, "push", local_file.to_str().unwrap_or(""), &device_file])
-                        .output()
-                        .await;
+    #[test]
+    fn test_listing_to_csv() {
+        let entries: Vec<(String, FileEntry)> = [
+            "-rw-rw---- root sdcard_rw 1024 2025-02-01 06:31 a, b.txt",
+            "drwxrwx--- root sdcard_rw 4096 2025-02-01 06:31 DCIM",
+        ]
+        .iter()
+        .filter_map(|line| parse_ls_line(line))
+        .map(|entry| (format!("/sdcard/{}", entry.name), entry))
+        .collect();
 
-                    match output {
-                        Ok(o) if o.status.success() => {
-                            // Restore the source file's modification time on the device
-                            if let Some(&mtime) = local_mtime_map.get(&action.file_path) {
-                                let escaped_path = device_file.replace("'", "'\\''");
-                                let touch_cmd = format!("touch -d @{} '{}'", mtime, escaped_path);
-                                let _ = shell
-                                    .command(&adb_cmd)
-                                    .args(["-s", &device_id, "shell", &touch_cmd])
-                                    .output()
-                                    .await;
-                            }
-                            Ok(())
-                        }
-                        Ok(o) => Err(format!("Push failed: {}", String::from_utf8_lossy(&o.stderr))),
-                        Err(e) => Err(format!("Push error: {}", e)),
-                    }
-                } else {
-                    Ok(()) // skip
-                }
-            }
-            "delete" => {
-                if action.direction.contains("Computer") {
-                    // Delete local file
-                    let local_file = PathBuf::from(&options.local_path).join(&action.file_path);
-                    fs::remove_file(&local_file)
-                        .map_err(|e| format!("Failed to delete local file: {}", e))
-                } else if action.direction.contains("Phone") {
-                    // Delete device file
-                    let device_file = format!("{}/{}", options.device_path, action.file_path);
-                    let escaped = device_file.replace("'", "'\\''");
-                    let rm_cmd = format!("rm '{}'", escaped);
+        let csv = listing_to_csv(&entries);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,full_path,size_bytes,modified,type,permissions");
+        assert_eq!(lines[1], "\"a, b.txt\",\"/sdcard/a, b.txt\",1024,2025-02-01 06:31,file,-rw-rw----");
+        assert_eq!(lines[2], "DCIM,/sdcard/DCIM,4096,2025-02-01 06:31,directory,drwxrwx---");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
 
-                    let output = shell
-                        .command(&adb_cmd)
-                        .args(["-s", &device_id, "shell", &rm_cmd])
-                        .output()
-                        .await;
+    #[test]
+    fn test_mount_for_path() {
+        let mounts = parse_mounts(
+            "/dev/root / ext4 ro 0 0\n\
+             /dev/block/dm-5 /data f2fs rw 0 0\n\
+             /dev/fuse /storage/emulated fuse rw 0 0\n\
+             /dev/fuse /storage/1234-ABCD fuse rw 0 0\n",
+        );
+        assert_eq!(mount_for_path(&mounts, "/storage/emulated/0/DCIM").unwrap().mount_point, "/storage/emulated");
+        assert_eq!(mount_for_path(&mounts, "/storage/1234-ABCD/Music").unwrap().mount_point, "/storage/1234-ABCD");
+        assert_eq!(mount_for_path(&mounts, "/data/local/tmp").unwrap().mount_point, "/data");
+        assert_eq!(mount_for_path(&mounts, "/database").unwrap().mount_point, "/");
+    }
 
-                    match output {
-                        Ok(o) if o.status.success() => Ok(()),
-                        Ok(o) => Err(format!("Delete failed: {}", String::from_utf8_lossy(&o.stderr))),
-                        Err(e) => Err(format!("Delete error: {}", e)),
-                    }
-                } else {
-                    Ok(())
-                }
-            }
-            "rename" => {
-                if let Some(ref old_rel_path) = action.rename_from {
-                    if action.direction.contains("Computer") {
-                        // Rename local file
-                        let old_local = PathBuf::from(&options.local_path).join(old_rel_path);
-                        let new_local = PathBuf::from(&options.local_path).join(&action.file_path);
-                        if let Some(parent) = new_local.parent() {
-                            let _ = fs::create_dir_all(parent);
-                        }
-                        fs::rename(&old_local, &new_local)
-                            .map_err(|e| format!("Rename failed: {}", e))
-                    } else if action.direction.contains("Phone") {
-                        // Rename on device
-                        let old_device = format!("{}/{}", options.device_path, old_rel_path);
-                        let new_device = format!("{}/{}", options.device_path, action.file_path);
-                        let escaped_old = old_device.replace("'", "'\\''");
-                        let escaped_new = new_device.replace("'", "'\\''");
-                        // Create parent dir on device if needed
-                        if let Some(parent) = PathBuf::from(&action.file_path).parent() {
-                            let parent_str = parent.to_string_lossy();
-                            if !parent_str.is_empty() {
-                                let device_parent = format!("{}/{}", options.device_path, parent_str);
-                                let escaped = device_parent.replace("'", "'\\''");
-                                let mkdir_cmd = format!("mkdir -p '{}'", escaped);
-                                let _ = shell
-                                    .command(&adb_cmd)
-                                    .args(["-s", &device_id, "shell", &mkdir_cmd])
-                                    .output()
-                                    .await;
-                            }
-                        }
-                        let mv_cmd = format!("mv '{}' '{}'", escaped_old, escaped_new);
-                        let output = shell
-                            .command(&adb_cmd)
-                            .args(["-s", &device_id, "shell", &mv_cmd])
-                            .output()
-                            .await;
-                        match output {
-                            Ok(o) if o.status.success() => Ok(()),
-                            Ok(o) => Err(format!("Rename failed: {}", String::from_utf8_lossy(&o.stderr))),
-                            Err(e) => Err(format!("Rename error: {}", e)),
-                        }
-                    } else {
-                        Ok(())
-                    }
-                } else {
-                    Ok(())
-                }
-            }
-            _ => {
-                skip_count += 1;
-                continue;
-            }
-        };
+    #[test]
+    fn test_split_symlink_name() {
+        assert_eq!(
+            split_symlink_name("/sdcard/link to file -> /data/missing file"),
+            ("/sdcard/link to file".to_string(), Some("/data/missing file".to_string()))
+        );
+        assert_eq!(split_symlink_name("plain.txt"), ("plain.txt".to_string(), None));
+    }
 
-        match result {
-            Ok(()) => {
-                success_count += 1;
-                if action.action_type == "copy" || action.action_type == "update" {
-                    completed_bytes += action.size;
-                }
-            }
-            Err(e) => {
-                error_count += 1;
-                errors.push(format!("{}: {}", action.file_path, e));
-            }
-        }
+    #[test]
+    fn test_permissions_to_octal() {
+        assert_eq!(permissions_to_octal("drwxr-xr-x").as_deref(), Some("755"));
+        assert_eq!(permissions_to_octal("-rw-rw----").as_deref(), Some("660"));
+        assert_eq!(permissions_to_octal("-rwsr-xr-x").as_deref(), Some("4755"));
+        assert_eq!(permissions_to_octal("drwxr-sr-x").as_deref(), Some("2755"));
+        assert_eq!(permissions_to_octal("drwxrwxrwt").as_deref(), Some("1777"));
+        assert_eq!(permissions_to_octal("drwxrwx--T").as_deref(), Some("1770"));
+        assert_eq!(permissions_to_octal("-rwSr-Sr-T").as_deref(), Some("7644"));
+        assert_eq!(permissions_to_octal("lrwxrwxrwx").as_deref(), Some("777"));
+        assert_eq!(permissions_to_octal("drwxr-xr-x.").as_deref(), Some("755"));
+        assert_eq!(permissions_to_octal("total"), None);
     }
 
-    // Emit final progress
-    let _ = window.emit("sync-progress", SyncProgress {
-        current_file: "Done".to_string(),
-        completed_count: total_count,
-        total_count,
-        completed_bytes,
-        total_bytes,
-    });
+    #[test]
+    fn test_parse_device_locale() {
+        assert_eq!(
+            parse_device_locale("de-DE\nen-US\nEurope/Berlin\n"),
+            DeviceLocale { locale: Some("de-DE".to_string()), timezone: Some("Europe/Berlin".to_string()) }
+        );
+        assert_eq!(
+            parse_device_locale("\nen-US\n\n"),
+            DeviceLocale { locale: Some("en-US".to_string()), timezone: None }
+        );
+    }
 
-    Ok(SyncResult {
-        success_count,
-        skip_count,
-        error_count,
-        errors,
-    })
-}
+    #[test]
+    fn test_parse_port_mappings() {
+        let forwards = "emulator-5554 tcp:8080 tcp:80\nABC123 tcp:9222 localabstract:chrome_devtools_remote\n";
+        assert_eq!(
+            parse_port_mappings(forwards, Some("ABC123")),
+            vec![PortMapping {
+                local: "tcp:9222".to_string(),
+                remote: "localabstract:chrome_devtools_remote".to_string(),
+            }]
+        );
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![
-            get_devices,
-            list_files,
-            detect_storage_path,
-            check_adb,
-            set_adb_path,
-            get_current_adb_path,
-            get_thumbnail,
-            delete_file,
-            rename_file,
-            search_files,
-            get_storage_info,
-            download_file,
-            upload_file,
-            preview_file,
-            list_local_files,
-            list_device_files_for_sync,
-            preview_sync,
-            execute_sync,
-            list_saved_syncs,
-            save_sync_config,
-            delete_saved_sync
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+        let reverses = "UsbFfs tcp:8081 tcp:8081\n";
+        assert_eq!(parse_port_mappings(reverses, None).len(), 1);
+        assert!(parse_port_mappings("", None).is_empty());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_parse_manifest_line() {
+        assert_eq!(
+            parse_manifest_line("1024 D41D8CD98F00B204E9800998ECF8427E  ./DCIM/My Photo.jpg"),
+            Some(ManifestEntry {
+                relative_path: "DCIM/My Photo.jpg".to_string(),
+                hash: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+                size: 1024,
+            })
+        );
+        assert_eq!(parse_manifest_line("md5sum: ./locked: Permission denied"), None);
+        assert!(checksum_tool("SHA256").is_ok());
+        assert!(checksum_tool("crc32").is_err());
+    }
+
+    #[test]
+    fn test_ls_fallback_helpers() {
+        assert!(is_invalid_option_error("ls: invalid option -- 'a'\nBusyBox v1.22 usage: ls [-1Cl] [FILE]..."));
+        assert!(!is_invalid_option_error("ls: /sdcard/missing: No such file or directory"));
+
+        let file = name_only_file_entry("IMG_001.JPG").unwrap();
+        assert!(!file.is_directory);
+        assert_eq!(file.extension.as_deref(), Some("jpg"));
+        assert_eq!(file.modified_epoch, None);
+
+        let dir = name_only_file_entry("DCIM/").unwrap();
+        assert!(dir.is_directory);
+        assert_eq!(dir.name, "DCIM");
+        assert!(name_only_file_entry("..").is_none());
+    }
 
     #[test]
-    fn test_matches_any_pattern_with_spaces() {
-        // Pattern with space in directory name
-        let patterns = vec![
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*".to_string(),
-        ];
+    fn test_build_advanced_find_args() {
+        let args: Vec<String> = ["-type", "f", "!", "-name", "*.tmp", "-size", "+100M", "-mtime", "-7"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            build_advanced_find_args(&args).unwrap(),
+            "'-type' 'f' '!' '-name' '*.tmp' '-size' '+100M' '-mtime' '-7'"
+        );
 
-        // Should match a file directly in that directory
-        assert!(matches_any_pattern(
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/IMG_001.jpg",
-            &patterns,
-        ));
+        let quote = vec!["-name".to_string(), "it's".to_string()];
+        assert_eq!(build_advanced_find_args(&quote).unwrap(), r"'-name' 'it'\''s'");
 
-        // Should NOT match a file in a different directory
-        assert!(!matches_any_pattern(
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Video/Sent/VID_001.mp4",
-            &patterns,
-        ));
+        let reject = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            build_advanced_find_args(&args).is_err()
+        };
+        assert!(reject(&["-delete"]));
+        assert!(reject(&["-exec", "rm", "{}", ";"]));
+        assert!(reject(&["-size", "1;reboot"]));
+        assert!(reject(&["-type", "x"]));
+        assert!(reject(&["-name"]));
+
+        let either: Vec<String> = ["-name", "*.jpg", "-o", "-name", "*.png"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            build_advanced_find_command("/sdcard", &either).unwrap(),
+            r"find '/sdcard' \( '-name' '*.jpg' '-o' '-name' '*.png' \) -exec ls -ld {} \; 2>/dev/null"
+        );
+        assert_eq!(
+            build_advanced_find_command("/sdcard", &[]).unwrap(),
+            r"find '/sdcard' -exec ls -ld {} \; 2>/dev/null"
+        );
     }
 
     #[test]
-    fn test_normalize_patterns_with_spaces() {
-        // Bare directory with spaces → should get /**/* appended
-        let patterns = vec![
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent".to_string(),
-        ];
-        let normalized = normalize_patterns(&patterns, "/sdcard");
+    fn test_pick_launcher_icon() {
+        let names: Vec<String> = [
+            "AndroidManifest.xml",
+            "res/mipmap-anydpi-v26/ic_launcher.xml",
+            "res/mipmap-hdpi-v4/ic_launcher.png",
+            "res/mipmap-xxxhdpi-v4/ic_launcher_round.png",
+            "res/mipmap-xxhdpi-v4/ic_launcher.webp",
+            "res/drawable/background.png",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(pick_launcher_icon(&names).as_deref(), Some("res/mipmap-xxhdpi-v4/ic_launcher.webp"));
+
+        let obfuscated = vec!["res/a1.png".to_string(), "res/xY.webp".to_string()];
+        assert_eq!(pick_launcher_icon(&obfuscated), None);
+    }
+
+    #[test]
+    fn test_parse_transport() {
+        assert_eq!(parse_transport("emulator-5554"), ("emulator".to_string(), None, None));
         assert_eq!(
-            normalized,
-            vec!["Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/**/*"],
+            parse_transport("192.168.1.20:5555"),
+            ("tcp".to_string(), Some("192.168.1.20".to_string()), Some(5555))
         );
+        assert_eq!(parse_transport("[fe80::1]:5555"), ("tcp".to_string(), Some("fe80::1".to_string()), Some(5555)));
+        assert_eq!(parse_transport("adb-R58M123-abc._adb-tls-connect._tcp").0, "tcp");
+        assert_eq!(parse_transport("R58M123ABC"), ("usb".to_string(), None, None));
+    }
 
-        // Pattern with glob stays as-is
-        let patterns2 = vec![
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*".to_string(),
-        ];
-        let normalized2 = normalize_patterns(&patterns2, "/sdcard");
+    #[test]
+    fn test_parse_storage_volumes() {
+        let mounts = parse_mounts(
+            "/dev/block/vold/public:179,65 /mnt/media_rw/1234-ABCD vfat rw 0 0\n\
+             /dev/block/vold/public:8,1 /mnt/media_rw/5678-EF01 exfat rw 0 0\n",
+        );
+        let output = "ok|/storage/1234-ABCD\nok|/storage/5678-EF01\ndenied|/storage/9999-0000\nok|/storage/emulated\n\
+                      denied|/mnt/media_rw/1234-ABCD\ndenied|/mnt/media_rw/5678-EF01\n";
+        let volumes = parse_storage_volumes(output, &mounts);
+
+        assert_eq!(volumes.len(), 3);
+        assert_eq!(volumes[0].path, "/storage/1234-ABCD");
+        assert!(!volumes[0].is_otg);
+        assert!(volumes[0].accessible);
+        assert!(volumes[1].is_otg);
+        assert_eq!(volumes[2].name, "9999-0000");
+        assert!(!volumes[2].accessible);
+    }
+
+    #[test]
+    fn test_available_local_path() {
+        let dir = std::env::temp_dir().join(format!("droiddock_avail_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("photo.jpg");
+        assert_eq!(available_local_path(&file), file);
+
+        fs::write(&file, b"x").unwrap();
+        fs::write(dir.join("photo (1).jpg"), b"x").unwrap();
+        assert_eq!(available_local_path(&file), dir.join("photo (2).jpg"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_capability_probes() {
+        let caps = parse_capability_probes("cmd=1\r\ntime_style=0\nstat_format=1\n", true);
         assert_eq!(
-            normalized2,
-            vec!["Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/*"],
+            caps,
+            DeviceCapabilities {
+                supports_exec_out: true,
+                supports_cmd: true,
+                supports_time_style: false,
+                supports_stat_format: true,
+            }
         );
+        assert_eq!(parse_capability_probes("", false), DeviceCapabilities::default());
     }
 
     #[test]
-    fn test_matches_any_pattern_with_double_star_and_spaces() {
-        // Normalized pattern from bare directory input
-        let patterns = vec![
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/**/*".to_string(),
+    fn test_format_epoch_date() {
+        assert_eq!(format_epoch_date(0), "1970-01-01");
+        assert_eq!(format_epoch_date(1_738_368_000), "2025-02-01");
+        assert_eq!(format_epoch_date(951_825_600), "2000-02-29");
+        let epoch = parse_ls_datetime("2024-12-31", "23:59").unwrap();
+        assert_eq!(format_epoch_date(epoch), "2024-12-31");
+    }
+
+    #[test]
+    fn test_parse_adb_version() {
+        let output = "Android Debug Bridge version 1.0.41\nVersion 35.0.2-12147458\nInstalled as /usr/bin/adb\n";
+        assert_eq!(parse_adb_version(output), Some("35.0.2-12147458".to_string()));
+        assert_eq!(parse_adb_version("Android Debug Bridge version 1.0.39\n"), Some("1.0.39".to_string()));
+        assert_eq!(parse_adb_version("command not found"), None);
+    }
+
+    #[test]
+    fn test_remote_push_path() {
+        let relative = std::path::Path::new("photos").join("2024").join("a b.jpg");
+        assert_eq!(remote_push_path("/sdcard/Backup/", &relative), "/sdcard/Backup/photos/2024/a b.jpg");
+        assert_eq!(remote_push_path("/sdcard", std::path::Path::new("x.txt")), "/sdcard/x.txt");
+    }
+
+    #[test]
+    fn test_parse_package_install_times() {
+        let output = "Packages:\n\
+          Package [com.example.old] (1a2b3c):\n\
+            versionName=1.0\n\
+            firstInstallTime=2023-05-01 09:00:00\n\
+            lastUpdateTime=2023-05-01 09:00:00\n\
+          Package [com.example.new] (4d5e6f):\n\
+            versionName=2.3.1\n\
+            firstInstallTime=2024-01-05 10:12:33\n\
+            lastUpdateTime=2024-02-01 08:00:01\n\
+            User 0: ceDataInode=1 installed=true\n\
+              firstInstallTime=2024-01-06 11:00:00\n";
+        let packages = parse_package_install_times(output);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].package_name, "com.example.old");
+        assert_eq!(packages[1].version_name.as_deref(), Some("2.3.1"));
+        assert_eq!(packages[1].first_install_time.as_deref(), Some("2024-01-05 10:12:33"));
+        assert!(packages[1].last_update_epoch > packages[0].last_update_epoch);
+    }
+
+    #[test]
+    fn test_collision_groups() {
+        let items = vec![
+            ("100".to_string(), "/sdcard/b.mp4".to_string()),
+            ("200".to_string(), "/sdcard/c.mp4".to_string()),
+            ("100".to_string(), "/sdcard/a.mp4".to_string()),
         ];
+        assert_eq!(collision_groups(items), vec![vec!["/sdcard/a.mp4".to_string(), "/sdcard/b.mp4".to_string()]]);
+        assert!(quick_hash_command("/sdcard/it's.mp4", 4096).contains("head -c 4096 \"$f\""));
+    }
 
-        // Should match a file in the directory
-        assert!(matches_any_pattern(
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/IMG_001.jpg",
-            &patterns,
-        ));
+    #[test]
+    fn test_thumbnail_cache_key_uses_stable_serial() {
+        let usb_key = thumbnail_cache_key("SERIAL123", "/sdcard/a.jpg");
+        DEVICE_SERIALS
+            .lock()
+            .unwrap()
+            .insert("192.168.1.20:5555".to_string(), "SERIAL123".to_string());
+        assert_eq!(thumbnail_cache_key("192.168.1.20:5555", "/sdcard/a.jpg"), usb_key);
+        assert_ne!(thumbnail_cache_key("10.0.0.9:5555", "/sdcard/a.jpg"), usb_key);
+    }
 
-        // Should match a file in a subdirectory
-        assert!(matches_any_pattern(
-            "Android/media/com.whatsapp/WhatsApp/Media/WhatsApp Images/Sent/2024/IMG_001.jpg",
-            &patterns,
-        ));
+    #[test]
+    fn test_parse_meminfo() {
+        let output = "Applications Memory Usage (in Kilobytes):\n\
+            ** MEMINFO in pid 1234 [com.example.app] **\n\
+                               Pss  Private  Private  SwapPss      Rss     Heap     Heap     Heap\n\
+                             Total    Dirty    Clean    Dirty    Total     Size    Alloc     Free\n\
+              Native Heap    10040    10000        0       18    12000    20480    14000     6480\n\
+              Dalvik Heap     3000     2900        0        0     5000     8000     4000     4000\n\
+                    TOTAL    54321    40000     1200       20    90000    28480    18000    10480\n\
+             App Summary\n\
+                   Java Heap:     4100                          9000\n\
+                 Native Heap:    10000                         12000\n\
+                   TOTAL PSS:    54321            TOTAL RSS:    90000\n";
+        let info = parse_meminfo("com.example.app", output);
+
+        assert_eq!(info.total_pss_kb, Some(54321));
+        assert_eq!(info.private_dirty_kb, Some(40000));
+        assert_eq!(info.java_heap_kb, Some(4100));
+        assert_eq!(info.native_heap_kb, Some(10000));
+        assert_eq!(info.native_heap_alloc_kb, Some(14000));
+        assert_eq!(info.dalvik_heap_alloc_kb, Some(4000));
     }
 
     #[test]
-    fn test_matches_empty_patterns_matches_all() {
-        assert!(matches_any_pattern("any/path/file.txt", &vec![]));
+    fn test_parse_content_query_data() {
+        assert_eq!(
+            parse_content_query_data("Row: 0 _data=/storage/emulated/0/DCIM/Camera/IMG 1.jpg\n"),
+            Some("/storage/emulated/0/DCIM/Camera/IMG 1.jpg".to_string())
+        );
+        assert_eq!(parse_content_query_data("Row: 0 _data=NULL\n"), None);
+        assert_eq!(parse_content_query_data("No result found.\n"), None);
     }
 
-    // Helper to create a FileMetadata for testing
-    fn make_file(path: &str, size: u64, mtime: u64, md5: Option<&str>) -> FileMetadata {
-        FileMetadata {
-            relative_path: path.to_string(),
-            size,
-            modified_time: mtime,
-            is_directory: false,
-            md5_hash: md5.map(|s| s.to_string()),
+    #[test]
+    fn test_evict_oldest_files() {
+        let dir = std::env::temp_dir().join(format!("droiddock_evict_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (i, name) in ["old.bin", "mid.bin", "new.bin"].iter().enumerate() {
+            let path = dir.join(name);
+            fs::write(&path, vec![0u8; 100]).unwrap();
+            let file = fs::File::options().append(true).open(&path).unwrap();
+            file.set_modified(UNIX_EPOCH + Duration::from_secs(1_000 + i as u64)).unwrap();
         }
+
+        evict_oldest_files(&dir, 200);
+        assert!(!dir.join("old.bin").exists());
+        assert!(dir.join("mid.bin").exists());
+        assert!(dir.join("new.bin").exists());
+
+        let _ = fs::remove_dir_all(&dir);
     }
 
-    // ========================
-    // compute_sync_actions tests (filename-based)
-    // ========================
+    #[test]
+    fn test_aggregate_storage_by_type() {
+        let output = "4000|/sdcard/DCIM/a.JPG\n1000|/sdcard/DCIM/b.heic\n9000|/sdcard/Movies/c.mp4\n\
+                      300|/sdcard/Download/app.apk\n50|/sdcard/notes.txt\n20|/sdcard/README\n";
+        let breakdown = aggregate_storage_by_type(output.lines());
+
+        assert_eq!(breakdown[0], StorageCategory { category: "videos".to_string(), total_bytes: 9000, file_count: 1 });
+        assert_eq!(breakdown[1], StorageCategory { category: "images".to_string(), total_bytes: 5000, file_count: 2 });
+        assert_eq!(breakdown[2].category, "apks");
+        assert_eq!(breakdown[3].category, "documents");
+        assert_eq!(breakdown[4].category, "other");
+    }
 
     #[test]
-    fn test_sync_phone_to_computer_copy_new_file() {
-        let local = vec![];
-        let device = vec![make_file("photo.jpg", 1000, 100, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "copy");
-        assert_eq!(actions[0].file_path, "photo.jpg");
-        assert!(actions[0].direction.contains("Computer"));
+    fn test_is_case_only_change() {
+        assert!(is_case_only_change("photo.JPG", "photo.jpg"));
+        assert!(!is_case_only_change("photo.jpg", "photo.jpg"));
+        assert!(!is_case_only_change("photo.jpg", "image.jpg"));
+
+        // A case-only batch target like IMG_1.jpg over img_1.jpg compares inodes instead of existence
+        let check = rename_conflict_check("/sdcard/img_1.jpg", "/sdcard/IMG_1.jpg", true, "/sdcard/IMG_1.jpg");
+        assert!(check.contains("stat -c %i '/sdcard/img_1.jpg'"));
+        assert!(!check.contains("[ -e"));
+        assert_eq!(
+            rename_conflict_check("/sdcard/a.jpg", "/sdcard/b.jpg", false, "exists"),
+            "[ -e '/sdcard/b.jpg' ] && echo 'exists'"
+        );
     }
 
     #[test]
-    fn test_sync_computer_to_phone_copy_new_file() {
-        let local = vec![make_file("doc.pdf", 2000, 200, None)];
-        let device = vec![];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::ComputerToPhone, false, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "copy");
-        assert_eq!(actions[0].file_path, "doc.pdf");
-        assert!(actions[0].direction.contains("Phone"));
+    fn test_parse_device_identity() {
+        let identity = parse_device_identity("R58M123ABC\n\nLab Pixel 7\r\nnull\n");
+        assert_eq!(identity.serial.as_deref(), Some("R58M123ABC"));
+        assert_eq!(identity.boot_serial, None);
+        assert_eq!(identity.bluetooth_name.as_deref(), Some("Lab Pixel 7"));
+        assert_eq!(identity.hostname, None);
     }
 
     #[test]
-    fn test_sync_update_newer_file() {
-        let local = vec![make_file("file.txt", 100, 50, None)];
-        let device = vec![make_file("file.txt", 200, 100, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "update");
+    fn test_authorization_guidance() {
+        let (steps, restart) = authorization_guidance("unauthorized");
+        assert!(restart);
+        assert!(steps.iter().any(|s| s.contains("Always allow")));
+
+        let (steps, restart) = authorization_guidance("device");
+        assert!(steps.is_empty());
+        assert!(!restart);
     }
 
     #[test]
-    fn test_sync_no_action_when_identical() {
-        let local = vec![make_file("file.txt", 100, 100, None)];
-        let device = vec![make_file("file.txt", 100, 100, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
-        assert_eq!(actions.len(), 0);
+    fn test_diff_listings() {
+        let entry = |name: &str, size: &str| {
+            let mut e = parse_ls_line(&format!("-rw-rw---- 1 root sdcard_rw {} 2024-01-15 10:30 {}", size, name)).unwrap();
+            e.name = name.to_string();
+            e
+        };
+        let previous = vec![entry("a.jpg", "100"), entry("b.jpg", "200")];
+        let current = vec![entry("a.jpg", "150"), entry("c.jpg", "300")];
+        let (added, removed, modified) = diff_listings(&previous, &current);
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].name, "c.jpg");
+        assert_eq!(removed, vec!["b.jpg".to_string()]);
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].name, "a.jpg");
     }
 
     #[test]
-    fn test_sync_delete_missing_phone_to_computer() {
-        let local = vec![make_file("old.txt", 100, 50, None)];
-        let device = vec![];
-        // delete_missing=true: local file not on device → delete locally
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, true, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "delete");
-        assert!(actions[0].direction.contains("Computer"));
+    fn test_is_valid_ime_id() {
+        assert!(is_valid_ime_id("com.android.inputmethod.latin/.LatinIME"));
+        assert!(is_valid_ime_id("com.google.android.inputmethod.latin/com.android.inputmethod.latin.LatinIME"));
+        assert!(!is_valid_ime_id("com.example; reboot"));
+        assert!(!is_valid_ime_id("no-slash"));
     }
 
     #[test]
-    fn test_sync_no_delete_when_flag_off() {
-        let local = vec![make_file("old.txt", 100, 50, None)];
-        let device = vec![];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::PhoneToComputer, false, "filename");
-        assert_eq!(actions.len(), 0);
+    fn test_add_to_session_stats() {
+        let mut stats = SessionStats::default();
+        add_to_session_stats(&mut stats, "download_file", "download", 1000, 1);
+        add_to_session_stats(&mut stats, "download_file", "download", 500, 1);
+        add_to_session_stats(&mut stats, "push_directory", "upload", 2000, 4);
+
+        assert_eq!(stats.downloaded_bytes, 1500);
+        assert_eq!(stats.downloaded_files, 2);
+        assert_eq!(stats.uploaded_files, 4);
+        assert_eq!(
+            stats.operations["download_file"],
+            OperationStats { direction: "download".to_string(), bytes: 1500, files: 2 }
+        );
     }
 
     #[test]
-    fn test_sync_delete_missing_computer_to_phone() {
-        let local = vec![];
-        let device = vec![make_file("orphan.txt", 100, 50, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::ComputerToPhone, true, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "delete");
-        assert!(actions[0].direction.contains("Phone"));
+    fn test_is_cross_device_error() {
+        assert!(is_cross_device_error("mv: bad '/sdcard/a' to '/storage/1234-ABCD/a': Invalid cross-device link"));
+        assert!(is_cross_device_error("mv: rename failed: Cross-device link"));
+        assert!(!is_cross_device_error("mv: /sdcard/a: No such file or directory"));
     }
 
     #[test]
-    fn test_sync_both_ways_copies_unique_files() {
-        let local = vec![make_file("local_only.txt", 100, 50, None)];
-        let device = vec![make_file("device_only.txt", 200, 60, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::BothWays, false, "filename");
-        assert_eq!(actions.len(), 2);
-        let types: Vec<&str> = actions.iter().map(|a| a.action_type.as_str()).collect();
-        assert!(types.contains(&"copy"));
+    fn test_parse_pair_output() {
+        let ok = parse_pair_output("Successfully paired to 192.168.1.5:37123 [guid=adb-123-abc]\n");
+        assert!(ok.success);
+
+        let failed = parse_pair_output("Enter pairing code: \nFailed: Unable to start pairing client.\n");
+        assert!(!failed.success);
+        assert_eq!(failed.message, "Failed: Unable to start pairing client.");
     }
 
     #[test]
-    fn test_sync_both_ways_conflict_newer_wins() {
-        let local = vec![make_file("file.txt", 100, 200, None)];
-        let device = vec![make_file("file.txt", 200, 100, None)];
-        let actions = compute_sync_actions(&local, &device, &SyncDirection::BothWays, false, "filename");
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "update");
-        // Local is newer (mtime 200 > 100), should push to phone
-        assert!(actions[0].direction.contains("Phone"));
+    fn test_parse_getprop() {
+        let output = "[ro.build.version.release]: [14]\n[ro.build.version.sdk]: [34]\n\
+                      [ro.product.model]: [Pixel 7]\n[ro.empty]: []\nnot a property line\n";
+        let props = parse_getprop(output);
+        assert_eq!(props.get("ro.product.model").map(String::as_str), Some("Pixel 7"));
+        assert_eq!(props.get("ro.build.version.sdk").map(String::as_str), Some("34"));
+        assert_eq!(props.get("ro.empty").map(String::as_str), Some(""));
+        assert_eq!(props.len(), 4);
     }
 
-    // ========================
-    // compute_sync_actions_by_content tests (MD5-based)
-    // ========================
+    #[test]
+    fn test_parse_adb_devices_output() {
+        let output = "List of devices attached\n\
+                      1A2B3C4D       device usb:1-1 product:panther model:Pixel_7 device:panther transport_id:2\n\
+                      192.168.1.20:5555 unauthorized transport_id:3\n\n";
+        let devices = parse_adb_devices_output(output);
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].model, "Pixel 7");
+        assert_eq!(devices[0].product.as_deref(), Some("panther"));
+        assert_eq!(devices[0].transport_id.as_deref(), Some("2"));
+        assert_eq!(devices[1].status, "unauthorized");
+        assert_eq!(devices[1].model, "");
+        assert_eq!(devices[1].product, None);
+        assert_eq!(devices[1].transport_type, "tcp");
+    }
 
     #[test]
-    fn test_content_sync_identical_files_no_action() {
-        let local = vec![make_file("file.txt", 100, 50, Some("abc123"))];
-        let device = vec![make_file("file.txt", 100, 50, Some("abc123"))];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
-        assert_eq!(actions.len(), 0);
+    fn test_adb_error_from_stderr() {
+        let path = "/sdcard/a.txt";
+        assert_eq!(
+            AdbError::from_stderr("rm: /sdcard/a.txt: Permission denied", path),
+            AdbError::PermissionDenied(path.to_string())
+        );
+        assert_eq!(
+            AdbError::from_stderr("rm: /sdcard/a.txt: No such file or directory", path),
+            AdbError::NotFound(path.to_string())
+        );
+        assert_eq!(
+            AdbError::from_stderr("error: device unauthorized.", path),
+            AdbError::DeviceUnauthorized
+        );
+        assert_eq!(
+            AdbError::from_stderr("rm: /sdcard/a: Directory not empty\n", path),
+            AdbError::DirectoryNotEmpty(path.to_string())
+        );
+        assert_eq!(
+            AdbError::from_stderr("cp: /sdcard/b.mp4: No space left on device", path),
+            AdbError::OutOfSpace(path.to_string())
+        );
+        assert_eq!(
+            AdbError::from_stderr("mv: bad option\n", path),
+            AdbError::CommandFailed { stderr: "mv: bad option".to_string() }
+        );
+        assert_eq!(AdbError::from(OPERATION_CANCELLED.to_string()), AdbError::Cancelled);
+
+        let json = serde_json::to_value(AdbError::NotFound(path.to_string())).unwrap();
+        assert_eq!(json["kind"], "not_found");
+        assert_eq!(json["message"], "File not found: /sdcard/a.txt");
+
+        let json = serde_json::to_value(AdbError::InsufficientSpace { needed: 10, available: 4 }).unwrap();
+        assert_eq!(json["kind"], "insufficient_space");
     }
 
     #[test]
-    fn test_content_sync_rename_detection() {
-        // Same hash, different path → rename
-        let local = vec![make_file("old_name.txt", 100, 50, Some("abc123"))];
-        let device = vec![make_file("new_name.txt", 100, 60, Some("abc123"))];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "rename");
-        assert!(actions[0].rename_from.is_some());
+    fn test_parse_ls_line_size_columns() {
+        // Android toolbox: owner group size, and no size column for directories
+        let file = parse_ls_line("-rw-rw---- root sdcard_rw 1024 2025-02-01 06:31 photo.jpg").unwrap();
+        assert_eq!(file.size, "1024");
+        assert_eq!(file.name, "photo.jpg");
+        let dir = parse_ls_line("drwxrwx--- root sdcard_rw 2025-02-01 06:31 .NightPearl").unwrap();
+        assert_eq!(dir.size, "0");
+        assert_eq!(dir.name, ".NightPearl");
+        assert_eq!(dir.date, "2025-02-01 06:31");
+
+        // toybox/busybox: links owner group size
+        let dir = parse_ls_line("drwxr-xr-x 2 root root 4096 2024-01-02 10:11 Music").unwrap();
+        assert_eq!(dir.size, "4096");
+        assert_eq!(dir.name, "Music");
+        let file = parse_ls_line("-rw-rw---- 1 u0_a123 media_rw 2048 2024-01-02 10:11 My Song.mp3").unwrap();
+        assert_eq!(file.size, "2048");
+        assert_eq!(file.name, "My Song.mp3");
+
+        // Link count without a size column must not report the group as the size
+        let dir = parse_ls_line("drwxr-xr-x 2 root sdcard_rw 2024-01-02 10:11 Music").unwrap();
+        assert_eq!(dir.size, "0");
+        assert_eq!(dir.name, "Music");
     }
 
     #[test]
-    fn test_content_sync_copy_unique_file() {
-        let local = vec![];
-        let device = vec![make_file("new.txt", 100, 50, Some("abc123"))];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "copy");
-        assert!(actions[0].direction.contains("Computer"));
+    fn test_parse_ls_line_symlink() {
+        let link = parse_ls_line("lrwxrwxrwx 1 root root 21 2024-01-02 10:11 sdcard -> /storage/self/primary").unwrap();
+        assert_eq!(link.name, "sdcard");
+        assert!(link.is_symlink);
+        assert_eq!(link.symlink_target.as_deref(), Some("/storage/self/primary"));
+
+        let link = parse_ls_line("lrwxrwxrwx root root 2024-01-02 10:11 My Photo.jpg -> /sdcard/DCIM/a b.jpg").unwrap();
+        assert_eq!(link.name, "My Photo.jpg");
+        assert_eq!(link.extension.as_deref(), Some("jpg"));
+        assert_eq!(link.symlink_target.as_deref(), Some("/sdcard/DCIM/a b.jpg"));
+
+        // " -> " in a regular file's name is part of the name
+        let file = parse_ls_line("-rw-rw---- root sdcard_rw 10 2024-01-02 10:11 a -> b.txt").unwrap();
+        assert_eq!(file.name, "a -> b.txt");
+        assert!(!file.is_symlink);
+        assert_eq!(file.symlink_target, None);
     }
 
     #[test]
-    fn test_content_sync_delete_missing() {
-        let local = vec![make_file("orphan.txt", 100, 50, Some("abc123"))];
-        let device = vec![];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, true);
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "delete");
+    fn test_cancel_transfer_registration() {
+        assert!(!cancel_transfer("transfer-unknown".to_string()).unwrap());
+
+        let registration = TransferRegistration::new("transfer-1");
+        assert!(!registration.is_cancelled());
+        assert!(cancel_transfer("transfer-1".to_string()).unwrap());
+        assert!(registration.is_cancelled());
+
+        drop(registration);
+        assert!(!is_transfer_cancelled("transfer-1"));
+    }
+
+    // Minimal UTF-8 ResStringPool chunk
+    fn string_pool_chunk(strings: &[&str]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for s in strings {
+            offsets.push(data.len() as u32);
+            data.extend([s.len() as u8, s.len() as u8]);
+            data.extend(s.as_bytes());
+            data.push(0);
+        }
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        let strings_start = 28 + 4 * strings.len() as u32;
+        let mut chunk = Vec::new();
+        chunk.extend(RES_STRING_POOL_TYPE.to_le_bytes());
+        chunk.extend(28u16.to_le_bytes());
+        chunk.extend((strings_start + data.len() as u32).to_le_bytes());
+        chunk.extend((strings.len() as u32).to_le_bytes());
+        chunk.extend(0u32.to_le_bytes());
+        chunk.extend(0x100u32.to_le_bytes());
+        chunk.extend(strings_start.to_le_bytes());
+        chunk.extend(0u32.to_le_bytes());
+        offsets.iter().for_each(|o| chunk.extend(o.to_le_bytes()));
+        chunk.extend(data);
+        chunk
+    }
+
+    fn with_chunk_header(chunk_type: u16, header: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        chunk.extend(chunk_type.to_le_bytes());
+        chunk.extend((8 + header.len() as u16).to_le_bytes());
+        chunk.extend((8 + header.len() as u32 + body.len() as u32).to_le_bytes());
+        chunk.extend(header);
+        chunk.extend(body);
+        chunk
     }
 
     #[test]
-    fn test_content_sync_no_delete_when_flag_off() {
-        let local = vec![make_file("orphan.txt", 100, 50, Some("abc123"))];
-        let device = vec![];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::PhoneToComputer, false);
-        assert_eq!(actions.len(), 0);
+    fn test_resolve_manifest_icon() {
+        // <application android:icon="@mipmap/a"> with an R8-shortened resource name
+        let mut element = Vec::new();
+        for value in [0xffff_ffffu32, 1] {
+            element.extend(value.to_le_bytes());
+        }
+        for value in [20u16, 20, 1, 0, 0, 0] {
+            element.extend(value.to_le_bytes());
+        }
+        for value in [0xffff_ffffu32, 0, 0xffff_ffff] {
+            element.extend(value.to_le_bytes());
+        }
+        element.extend([8, 0, 0, RES_VALUE_REFERENCE]);
+        element.extend(0x7f01_0000u32.to_le_bytes());
+        let mut manifest_body = string_pool_chunk(&["icon", "application"]);
+        manifest_body.extend(with_chunk_header(RES_XML_RESOURCE_MAP_TYPE, &[], &ANDROID_ATTR_ICON.to_le_bytes()));
+        manifest_body.extend(with_chunk_header(RES_XML_START_ELEMENT_TYPE, &[0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff], &element));
+        let manifest = with_chunk_header(0x0003, &[], &manifest_body);
+
+        // One ResTable_type chunk per density, each holding entry 0 as a path string
+        let type_chunk = |density: u16, path_index: u32| {
+            let mut header = vec![1, 0, 0, 0];
+            header.extend(1u32.to_le_bytes());
+            header.extend((8 + 12 + 64 + 4u32).to_le_bytes());
+            let mut config = vec![0u8; 64];
+            config[..4].copy_from_slice(&64u32.to_le_bytes());
+            config[14..16].copy_from_slice(&density.to_le_bytes());
+            header.extend(config);
+            let mut body = 0u32.to_le_bytes().to_vec();
+            body.extend([8, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, RES_VALUE_STRING]);
+            body.extend(path_index.to_le_bytes());
+            with_chunk_header(RES_TABLE_TYPE_TYPE, &header, &body)
+        };
+        let mut package_body = type_chunk(160, 0);
+        package_body.extend(type_chunk(480, 1));
+        let mut table_body = string_pool_chunk(&["res/Xy.png", "res/Zq.webp"]);
+        table_body.extend(with_chunk_header(RES_TABLE_PACKAGE_TYPE, &0x7fu32.to_le_bytes(), &package_body));
+        let arsc = with_chunk_header(0x0002, &1u32.to_le_bytes(), &table_body);
+
+        assert_eq!(axml_reference_attr(&manifest, "application", ANDROID_ATTR_ICON), Some(0x7f01_0000));
+        let mut read_entry = |name: &str| match name {
+            "AndroidManifest.xml" => Some(manifest.clone()),
+            "resources.arsc" => Some(arsc.clone()),
+            _ => None,
+        };
+        assert_eq!(resolve_manifest_icon(&mut read_entry).as_deref(), Some("res/Zq.webp"));
     }
 
     #[test]
-    fn test_content_sync_both_ways_rename_uses_newer() {
-        let local = vec![make_file("a.txt", 100, 200, Some("abc123"))];
-        let device = vec![make_file("b.txt", 100, 100, Some("abc123"))];
-        let actions = compute_sync_actions_by_content(&local, &device, &SyncDirection::BothWays, false);
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, "rename");
-        // Local is newer, so device should be renamed to match local
-        assert!(actions[0].direction.contains("Phone"));
-        assert_eq!(actions[0].file_path, "a.txt");
+    fn test_move_target_force_onto_existing_directory() {
+        // An existing folder receives the source under its own name, so the copy and checksum
+        // run on Pictures/photo.jpg and never on the folder's other files
+        assert_eq!(move_target("/sdcard/DCIM/photo.jpg", "/storage/1234-5678/Pictures", true), "/storage/1234-5678/Pictures/photo.jpg");
+        assert_eq!(move_target("/sdcard/DCIM/Trip/", "/storage/1234-5678/Pictures/", true), "/storage/1234-5678/Pictures/Trip");
+        assert_eq!(move_target("/sdcard/DCIM/photo.jpg", "/storage/1234-5678/renamed.jpg", false), "/storage/1234-5678/renamed.jpg");
+    }
+
+    #[test]
+    fn test_merge_device_settings() {
+        let mut existing = DeviceSettings {
+            last_path: Some("/sdcard/DCIM".to_string()),
+            nickname: Some("Lab Pixel".to_string()),
+            wireless_address: Some("192.168.1.20:5555".to_string()),
+            stale: true,
+            ..Default::default()
+        };
+        let update: DeviceSettings = serde_json::from_str(r#"{ "last_path": "/sdcard/Download" }"#).unwrap();
+        merge_device_settings(&mut existing, update);
+
+        assert_eq!(existing.last_path.as_deref(), Some("/sdcard/Download"));
+        assert_eq!(existing.nickname.as_deref(), Some("Lab Pixel"));
+        assert_eq!(existing.wireless_address.as_deref(), Some("192.168.1.20:5555"));
+        assert!(existing.stale);
+    }
+
+    #[test]
+    fn test_backup_folder_names() {
+        let folders: Vec<String> = ["/sdcard/DCIM/Camera", "/sdcard/Pictures/Camera/", "/sdcard/Download", "/", "/sdcard/Other/camera"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(backup_folder_names(&folders), vec!["Camera", "Camera (1)", "Download", "root", "camera (2)"]);
     }
 }
@@ -9,6 +9,13 @@ interface AdbDevice {
   id: string;
   status: string;
   model: string;
+  alias: string;
+  is_emulator: boolean;
+  transport_type: 'usb' | 'tcp' | 'emulator';
+  tcp_host: string | null;
+  tcp_port: number | null;
+  product: string | null;
+  transport_id: string | null;
 }
 
 interface FileEntry {
@@ -18,6 +25,11 @@ interface FileEntry {
   date: string;
   is_directory: boolean;
   extension: string | null;
+  modified_epoch: number | null;
+  mime_type: string | null;
+  permissions_octal: string | null;
+  is_symlink: boolean;
+  symlink_target: string | null;
 }
 
 interface StorageInfo {
@@ -115,6 +127,19 @@ function formatBytes(bytes: number): string {
   return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i];
 }
 
+// Structured error returned by the file commands; other commands still reject with a plain string
+interface AdbError {
+  kind: string;
+  message: string;
+}
+
+function errorMessage(err: unknown): string {
+  if (typeof err === "object" && err !== null && "message" in err) {
+    return (err as AdbError).message;
+  }
+  return String(err);
+}
+
 interface StatusBarProps {
   storageInfo: StorageInfo | null;
   fileCount: number;
@@ -265,8 +290,10 @@ function FileRow({ file, fileIndex, currentPath, thumbnailsEnabled, thumbnailCac
                 onNavigate();
               }
             }}
+            title={file.symlink_target ? `${file.name} → ${file.symlink_target}` : undefined}
           >
             {file.name}
+            {file.is_symlink && <span className="symlink-indicator"> 🔗</span>}
           </span>
         )}
       </td>
@@ -370,7 +397,13 @@ function GridItem({ file, fileIndex, currentPath, thumbnailsEnabled, thumbnailCa
           <span className="icon-large">{file.is_directory ? "📁" : "📄"}</span>
         )}
       </div>
-      <div className="grid-item-name">{file.name}</div>
+      <div
+        className="grid-item-name"
+        title={file.symlink_target ? `${file.name} → ${file.symlink_target}` : undefined}
+      >
+        {file.name}
+        {file.is_symlink && <span className="symlink-indicator"> 🔗</span>}
+      </div>
     </div>
   );
 }
@@ -1351,7 +1384,7 @@ function App() {
         );
       }
     } catch (err) {
-      setError(`Failed to list files: ${err}`);
+      setError(`Failed to list files: ${errorMessage(err)}`);
       setFiles([]);
     } finally {
       setLoading(false);
@@ -1686,11 +1719,12 @@ function App() {
           deviceId: selectedDevice,
           filePath: filePath,
           isDirectory: file.is_directory,
+          dryRun: false,
         });
         successCount++;
       } catch (err) {
         errorCount++;
-        setError(`Failed to delete ${fileName}: ${err}`);
+        setError(`Failed to delete ${fileName}: ${errorMessage(err)}`);
         console.error(`Delete error for ${fileName}:`, err);
       }
     }
@@ -1779,7 +1813,7 @@ function App() {
         await loadFiles();
       }
     } catch (err) {
-      setError(`Failed to rename: ${err}`);
+      setError(`Failed to rename: ${errorMessage(err)}`);
       console.error("Rename error:", err);
     } finally {
       setLoading(false);
@@ -1799,6 +1833,9 @@ function App() {
         searchPath: currentPath,
         pattern: searchQuery,
         recursive: searchRecursive,
+        excludePaths: [],
+        caseSensitive: false,
+        followSymlinks: false,
       });
       setSearchResults(results);
       setSearchMode(true);
@@ -2184,6 +2221,7 @@ function App() {
         deviceId: selectedDevice,
         localPath: localPath,
         devicePath: devicePath,
+        overwrite: true,
       });
 
       setUploading(false);
@@ -2192,7 +2230,7 @@ function App() {
       await loadFiles();
     } catch (err) {
       setUploading(false);
-      setError(`Failed to upload file: ${err}`);
+      setError(`Failed to upload file: ${errorMessage(err)}`);
       console.error(`Upload error:`, err);
     }
   }
@@ -2449,6 +2487,7 @@ function App() {
             deviceId: selectedDevice,
             localPath: localPath,
             devicePath: devicePath,
+            overwrite: true,
           });
 
           console.log(`Successfully uploaded ${fileName}`);
@@ -2574,7 +2613,8 @@ function App() {
               <option value="">Select a device</option>
               {devices.map((device) => (
                 <option key={device.id} value={device.id}>
-                  {device.model ? `${device.model} (${device.id})` : `${device.id} (${device.status})`}
+                  {device.alias !== device.id ? `${device.alias} (${device.id})` : `${device.id} (${device.status})`}
+                  {device.is_emulator && ' [Emulator]'}
                 </option>
               ))}
             </select>